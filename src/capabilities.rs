@@ -14,6 +14,10 @@ pub trait ClientCapabilitiesExt {
     fn has_push_configuration_support(&self) -> bool;
 
     fn has_file_watching_support(&self) -> bool;
+
+    fn has_show_document_support(&self) -> bool;
+
+    fn has_completion_snippet_support(&self) -> bool;
 }
 
 impl ClientCapabilitiesExt for ClientCapabilities {
@@ -65,14 +69,31 @@ impl ClientCapabilitiesExt for ClientCapabilities {
             .and_then(|cap| cap.dynamic_registration)
             == Some(true)
     }
+
+    fn has_show_document_support(&self) -> bool {
+        self.window
+            .as_ref()
+            .and_then(|cap| cap.show_document.as_ref())
+            .map_or(false, |cap| cap.support)
+    }
+
+    fn has_completion_snippet_support(&self) -> bool {
+        self.text_document
+            .as_ref()
+            .and_then(|cap| cap.completion.as_ref())
+            .and_then(|cap| cap.completion_item.as_ref())
+            .and_then(|cap| cap.snippet_support)
+            == Some(true)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use lsp_types::{
-        DocumentSymbolClientCapabilities, GotoCapability, HoverClientCapabilities,
-        TextDocumentClientCapabilities, WindowClientCapabilities,
+        CompletionClientCapabilities, CompletionItemCapability, DocumentSymbolClientCapabilities,
+        GotoCapability, HoverClientCapabilities, TextDocumentClientCapabilities,
+        WindowClientCapabilities,
     };
 
     #[test]
@@ -155,4 +176,28 @@ mod tests {
         let capabilities = ClientCapabilities::default();
         assert!(!capabilities.has_hover_markdown_support());
     }
+
+    #[test]
+    fn test_has_completion_snippet_support_true() {
+        let capabilities = ClientCapabilities {
+            text_document: Some(TextDocumentClientCapabilities {
+                completion: Some(CompletionClientCapabilities {
+                    completion_item: Some(CompletionItemCapability {
+                        snippet_support: Some(true),
+                        ..CompletionItemCapability::default()
+                    }),
+                    ..CompletionClientCapabilities::default()
+                }),
+                ..TextDocumentClientCapabilities::default()
+            }),
+            ..ClientCapabilities::default()
+        };
+        assert!(capabilities.has_completion_snippet_support());
+    }
+
+    #[test]
+    fn test_has_completion_snippet_support_false() {
+        let capabilities = ClientCapabilities::default();
+        assert!(!capabilities.has_completion_snippet_support());
+    }
 }
@@ -7,7 +7,12 @@ use unicode_normalization::UnicodeNormalization;
 
 use crate::syntax::bibtex;
 
-use self::{driver::Driver, output::Inline};
+use self::{
+    driver::Driver,
+    entry::EntryData,
+    field::{author::AuthorField, date::DateFieldData},
+    output::Inline,
+};
 
 #[must_use]
 pub fn render(entry: &bibtex::Entry) -> Option<String> {
@@ -33,5 +38,36 @@ pub fn render(entry: &bibtex::Entry) -> Option<String> {
     }
 }
 
+/// Renders an author-year label like `Rivest et al. 1978` that is short enough
+/// to be shown next to a citation.
+#[must_use]
+pub fn render_short(entry: &bibtex::Entry) -> Option<String> {
+    let data = EntryData::from(entry);
+    let authors = [AuthorField::Author, AuthorField::Editor]
+        .iter()
+        .find_map(|field| data.author.get(field))
+        .map(|author| match author.authors.as_slice() {
+            [] => String::new(),
+            [first] => first.surname().to_string(),
+            [first, second] => format!("{} and {}", first.surname(), second.surname()),
+            [first, ..] => format!("{} et al.", first.surname()),
+        })
+        .filter(|authors| !authors.is_empty());
+
+    let year = data.date.values().find_map(|date| match date {
+        DateFieldData::Year(year) => Some(year.to_string()),
+        DateFieldData::Date(date) => Some(date.format("%Y").to_string()),
+        DateFieldData::Month(_) | DateFieldData::Other(_) => None,
+    });
+
+    let output = match (authors, year) {
+        (Some(authors), Some(year)) => format!("{} {}", authors, year),
+        (Some(text), None) | (None, Some(text)) => text,
+        (None, None) => return None,
+    };
+
+    Some(output.nfc().collect())
+}
+
 #[cfg(test)]
 mod tests;
@@ -10,6 +10,30 @@ fn render_entry(input: &str) -> String {
     super::render(&entry).unwrap()
 }
 
+#[test]
+fn short_rivest_1978() {
+    let green = bibtex::parse(
+        r#"@article{foo, author = {Rivest, R. L. and Shamir, A. and Adleman, L.}, year = {1978}}"#,
+    );
+    let root = bibtex::Root::cast(bibtex::SyntaxNode::new_root(green)).unwrap();
+    let entry = root.entries().next().unwrap();
+    assert_eq!(
+        super::render_short(&entry).as_deref(),
+        Some("Rivest et al. 1978")
+    );
+}
+
+#[test]
+fn short_two_authors_without_year() {
+    let green = bibtex::parse(r#"@book{foo, editor = {Donald Knuth and Leslie Lamport}}"#);
+    let root = bibtex::Root::cast(bibtex::SyntaxNode::new_root(green)).unwrap();
+    let entry = root.entries().next().unwrap();
+    assert_eq!(
+        super::render_short(&entry).as_deref(),
+        Some("Knuth and Lamport")
+    );
+}
+
 #[test]
 fn article_rivest_1978() {
     assert_snapshot!(render_entry(
@@ -3,6 +3,11 @@ use std::sync::Mutex;
 use anyhow::{anyhow, Result};
 use crossbeam_channel::{Receiver, Sender};
 use lsp_server::{Message, ResponseError};
+use lsp_types::{
+    notification::Progress, request::WorkDoneProgressCreate, NumberOrString, ProgressParams,
+    ProgressParamsValue, WorkDoneProgress, WorkDoneProgressBegin, WorkDoneProgressCreateParams,
+    WorkDoneProgressEnd, WorkDoneProgressReport,
+};
 use serde::{de::DeserializeOwned, Serialize};
 
 use crate::req_queue::{OutgoingData, ReqQueue};
@@ -51,3 +56,80 @@ where
     lsp_sender.send(req.into())?;
     Ok(receiver)
 }
+
+/// Reports the progress of a long running operation using `$/progress` notifications
+/// if the client supports it. The operation ends when the reporter is dropped.
+pub struct ProgressReporter<'a> {
+    pub supports_progress: bool,
+    pub req_queue: &'a Mutex<ReqQueue>,
+    pub lsp_sender: Sender<Message>,
+    pub token: &'a str,
+    pub title: &'a str,
+    pub cancellable: bool,
+}
+
+impl<'a> ProgressReporter<'a> {
+    /// Begins the operation. An initial percentage makes the progress finite,
+    /// which clients need to show the percentages of later reports.
+    pub fn start(&self, message: Option<String>, percentage: Option<u32>) -> Result<()> {
+        if self.supports_progress {
+            send_request::<WorkDoneProgressCreate>(
+                self.req_queue,
+                &self.lsp_sender,
+                WorkDoneProgressCreateParams {
+                    token: NumberOrString::String(self.token.to_string()),
+                },
+            )?;
+            send_notification::<Progress>(
+                &self.lsp_sender,
+                ProgressParams {
+                    token: NumberOrString::String(self.token.to_string()),
+                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
+                        WorkDoneProgressBegin {
+                            title: self.title.to_string(),
+                            message,
+                            cancellable: Some(self.cancellable),
+                            percentage,
+                        },
+                    )),
+                },
+            )?;
+        };
+        Ok(())
+    }
+
+    pub fn report(&self, message: String, percentage: Option<u32>) -> Result<()> {
+        if self.supports_progress {
+            send_notification::<Progress>(
+                &self.lsp_sender,
+                ProgressParams {
+                    token: NumberOrString::String(self.token.to_string()),
+                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::Report(
+                        WorkDoneProgressReport {
+                            cancellable: Some(self.cancellable),
+                            message: Some(message),
+                            percentage,
+                        },
+                    )),
+                },
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Drop for ProgressReporter<'a> {
+    fn drop(&mut self) {
+        if self.supports_progress {
+            drop(send_notification::<Progress>(
+                &self.lsp_sender,
+                ProgressParams {
+                    token: NumberOrString::String(self.token.to_string()),
+                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(
+                        WorkDoneProgressEnd { message: None },
+                    )),
+                },
+            ));
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use std::io::Read;
+mod indexer;
+
+use std::{io::Read, sync::Arc};
 
 use flate2::read::GzDecoder;
 use itertools::Itertools;
@@ -9,6 +11,8 @@ use smol_str::SmolStr;
 
 use crate::{syntax::latex::ExplicitLink, Workspace};
 
+pub use self::indexer::{default_cache_path, index_components};
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComponentDatabase {
@@ -111,6 +115,8 @@ pub struct Component {
     pub references: Vec<SmolStr>,
     pub commands: Vec<ComponentCommand>,
     pub environments: Vec<SmolStr>,
+    #[serde(default)]
+    pub options: Vec<ComponentOption>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
@@ -133,6 +139,14 @@ pub struct ComponentArgument {
     pub image: Option<String>,
 }
 
+/// An option that can be passed to a package or class like `\usepackage[colorlinks]{hyperref}`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentOption {
+    pub name: SmolStr,
+    pub documentation: Option<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ComponentMetadata {
@@ -143,9 +157,10 @@ pub struct ComponentMetadata {
 
 const JSON_GZ: &[u8] = include_bytes!("../data/components.json.gz");
 
-pub static COMPONENT_DATABASE: Lazy<ComponentDatabase> = Lazy::new(|| {
+/// The bundled database. The database of the TeX distribution is part of the `Environment`.
+pub static COMPONENT_DATABASE: Lazy<Arc<ComponentDatabase>> = Lazy::new(|| {
     let mut decoder = GzDecoder::new(JSON_GZ);
     let mut buf = String::new();
     decoder.read_to_string(&mut buf).unwrap();
-    serde_json::from_str(&buf).unwrap()
+    Arc::new(serde_json::from_str(&buf).unwrap())
 });
@@ -0,0 +1,336 @@
+use std::{
+    ffi::OsStr,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use anyhow::Result;
+use lsp_types::Url;
+use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use smol_str::SmolStr;
+
+use crate::{
+    distro::Resolver, syntax::latex::ExplicitLink, Document, DocumentLanguage, Environment,
+};
+
+use super::{Component, ComponentCommand, ComponentDatabase, ComponentOption, ComponentParameter};
+
+/// The components of previously indexed files together with their modification time.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ComponentCache {
+    entries: FxHashMap<PathBuf, CacheEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    modified: SystemTime,
+    component: Component,
+}
+
+impl ComponentCache {
+    fn load(path: &Path) -> Self {
+        fs::read(path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default()
+    }
+
+    /// Writes the cache to a temporary file first and then renames it,
+    /// so that other servers never read a partially written cache.
+    fn save(&self, path: &Path) -> Result<()> {
+        let parent = path.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent)?;
+
+        let mut file = tempfile::NamedTempFile::new_in(parent)?;
+        file.write_all(&serde_json::to_vec(self)?)?;
+        file.persist(path)?;
+        Ok(())
+    }
+}
+
+/// Returns the default location of the component cache.
+#[must_use]
+pub fn default_cache_path() -> PathBuf {
+    let cache_dir = std::env::var_os("XDG_CACHE_HOME")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+        .unwrap_or_else(std::env::temp_dir);
+
+    cache_dir.join("texlab").join("components.json")
+}
+
+/// Extracts the components from the packages and classes of the TeX distribution
+/// as well as the given project-local files and merges them into the bundled database.
+/// Files that have not changed since the last run are read from the cache.
+#[must_use]
+pub fn index_components(
+    base: &ComponentDatabase,
+    resolver: &Resolver,
+    local_files: &[PathBuf],
+    cache_path: &Path,
+) -> ComponentDatabase {
+    let old_cache = ComponentCache::load(cache_path);
+    let mut new_cache = ComponentCache::default();
+    let environment = Environment::default();
+    let paths = resolver
+        .files_by_name
+        .values()
+        .chain(local_files)
+        .filter(|path| is_component_file(path));
+
+    for path in paths {
+        let modified = match fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) => continue,
+        };
+
+        let component = match old_cache.entries.get(path) {
+            Some(entry) if entry.modified == modified => entry.component.clone(),
+            _ => match analyze_file(&environment, path) {
+                Some(component) => component,
+                None => continue,
+            },
+        };
+
+        new_cache.entries.insert(
+            path.clone(),
+            CacheEntry {
+                modified,
+                component,
+            },
+        );
+    }
+
+    if let Err(why) = new_cache.save(cache_path) {
+        log::warn!("Failed to save the component cache: {}", why);
+    }
+
+    let mut database = base.clone();
+    for entry in new_cache.entries.into_values() {
+        merge_component(&mut database, entry.component);
+    }
+
+    database
+}
+
+fn is_component_file(path: &Path) -> bool {
+    matches!(
+        path.extension().and_then(OsStr::to_str),
+        Some("sty" | "cls")
+    )
+}
+
+fn analyze_file(environment: &Environment, path: &Path) -> Option<Component> {
+    let uri = Arc::new(Url::from_file_path(path).ok()?);
+    let data = fs::read(path).ok()?;
+    let text = Arc::new(String::from_utf8_lossy(&data).into_owned());
+    let document = Document::parse(environment, uri, text, DocumentLanguage::Latex);
+    let file_name = path.file_name()?.to_str()?;
+    Some(create_component(file_name, &document))
+}
+
+fn create_component(file_name: &str, document: &Document) -> Component {
+    let extras = &document.data.as_latex().unwrap().extras;
+
+    let references = extras
+        .explicit_links
+        .iter()
+        .filter_map(ExplicitLink::as_component_name)
+        .map(SmolStr::from)
+        .collect();
+
+    let mut commands: Vec<_> = extras
+        .command_definitions
+        .iter()
+        .filter_map(|(name, parameters)| {
+            let name = name.strip_prefix('\\')?;
+            if !is_public_name(name) {
+                return None;
+            }
+
+            let parameters = parameters
+                .iter()
+                .filter(|parameter| !parameter.is_optional)
+                .map(|_| ComponentParameter(Vec::new()))
+                .collect();
+
+            Some(ComponentCommand {
+                name: name.into(),
+                image: None,
+                glyph: None,
+                parameters,
+            })
+        })
+        .collect();
+
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let mut environments: Vec<SmolStr> = extras
+        .environment_definitions
+        .keys()
+        .filter(|name| is_public_name(name))
+        .map(Into::into)
+        .collect();
+
+    environments.sort();
+
+    let mut options: Vec<_> = extras
+        .option_names
+        .iter()
+        .map(|name| ComponentOption {
+            name: name.clone(),
+            documentation: None,
+        })
+        .collect();
+
+    options.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Component {
+        file_names: vec![file_name.into()],
+        references,
+        commands,
+        environments,
+        options,
+    }
+}
+
+/// Internal commands of packages contain characters like `@`, `_` or `:`.
+fn is_public_name(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphabetic() || c == '*')
+}
+
+/// Adds a component to the database.
+/// Components of the bundled database are extended with the commands, environments and options they are missing.
+fn merge_component(database: &mut ComponentDatabase, component: Component) {
+    let existing = database.components.iter_mut().find(|existing| {
+        existing
+            .file_names
+            .iter()
+            .any(|file_name| component.file_names.contains(file_name))
+    });
+
+    match existing {
+        Some(existing) => {
+            for command in component.commands {
+                if !existing.commands.iter().any(|cmd| cmd.name == command.name) {
+                    existing.commands.push(command);
+                }
+            }
+
+            for environment in component.environments {
+                if !existing.environments.contains(&environment) {
+                    existing.environments.push(environment);
+                }
+            }
+
+            for option in component.options {
+                if !existing.options.iter().any(|opt| opt.name == option.name) {
+                    existing.options.push(option);
+                }
+            }
+
+            for reference in component.references {
+                if !existing.references.contains(&reference) {
+                    existing.references.push(reference);
+                }
+            }
+        }
+        None => database.components.push(component),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_local_package() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let path = directory.path().join("foo.sty");
+        fs::write(
+            &path,
+            r#"\ProvidesPackage{foo}
+\RequirePackage{amsmath}
+\newcommand{\foo}[2]{#1 #2}
+\DeclareRobustCommand{\bar}{bar}
+\NewDocumentCommand{\baz}{s m}{#2}
+\newcommand{\foo@internal}{}
+\newenvironment{qux}{}{}
+\DeclareOption{draft}{}
+\DeclareKeys{margin .store = \foo@margin}"#,
+        )?;
+
+        let cache_path = directory.path().join("cache.json");
+        let base = ComponentDatabase {
+            components: Vec::new(),
+            metadata: Vec::new(),
+        };
+
+        let database = index_components(&base, &Resolver::default(), &[path], &cache_path);
+        let component = database.find("foo.sty").unwrap();
+        let commands: Vec<_> = component
+            .commands
+            .iter()
+            .map(|command| (command.name.as_str(), command.parameters.len()))
+            .collect();
+
+        assert_eq!(commands, vec![("bar", 0), ("baz", 1), ("foo", 2)]);
+        assert_eq!(component.environments, vec![SmolStr::from("qux")]);
+        assert_eq!(component.references, vec![SmolStr::from("amsmath.sty")]);
+        assert_eq!(
+            component
+                .options
+                .iter()
+                .map(|option| option.name.as_str())
+                .collect::<Vec<_>>(),
+            vec!["draft", "margin"]
+        );
+        assert!(cache_path.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn save_replaces_cache() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let cache_path = directory.path().join("cache.json");
+        fs::write(&cache_path, "invalid")?;
+
+        ComponentCache::default().save(&cache_path)?;
+        let cache: ComponentCache = serde_json::from_slice(&fs::read(&cache_path)?)?;
+        assert!(cache.entries.is_empty());
+        assert_eq!(fs::read_dir(directory.path())?.count(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn merge_into_existing_component() {
+        let component = |commands: &[&str]| Component {
+            file_names: vec!["foo.sty".into()],
+            references: Vec::new(),
+            commands: commands
+                .iter()
+                .map(|name| ComponentCommand {
+                    name: (*name).into(),
+                    image: None,
+                    glyph: None,
+                    parameters: Vec::new(),
+                })
+                .collect(),
+            environments: Vec::new(),
+            options: Vec::new(),
+        };
+
+        let mut database = ComponentDatabase {
+            components: vec![component(&["foo"])],
+            metadata: Vec::new(),
+        };
+
+        merge_component(&mut database, component(&["foo", "bar"]));
+        assert_eq!(database.components, vec![component(&["foo", "bar"])]);
+    }
+}
@@ -0,0 +1,160 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use ignore::{WalkBuilder, WalkState};
+use rustc_hash::FxHashSet;
+
+use crate::DocumentLanguage;
+
+/// Collects the LaTeX and BibTeX files inside of the given directories.
+/// Hidden files and files that are excluded by `.gitignore` or `.ignore` files are skipped.
+/// This includes the ignore files of parent directories, `.git/info/exclude`
+/// and the global ignore file of Git (`core.excludesFile`).
+pub fn find_source_files(directories: &[PathBuf]) -> Vec<PathBuf> {
+    let (first, others) = match directories.split_first() {
+        Some(directories) => directories,
+        None => return Vec::new(),
+    };
+
+    let mut builder = WalkBuilder::new(first);
+    for directory in others {
+        builder.add(directory);
+    }
+
+    // Workspace folders can overlap, so the same file might be found more than once.
+    let files = Mutex::new(FxHashSet::default());
+    builder.require_git(false).build_parallel().run(|| {
+        Box::new(|entry| {
+            if let Ok(entry) = entry {
+                let path = entry.path();
+                if entry.file_type().map_or(false, |ty| ty.is_file())
+                    && matches!(
+                        DocumentLanguage::by_path(path),
+                        Some(DocumentLanguage::Latex | DocumentLanguage::Bibtex)
+                    )
+                {
+                    files.lock().unwrap().insert(path.to_path_buf());
+                }
+            }
+
+            WalkState::Continue
+        })
+    });
+
+    let mut files: Vec<_> = files.into_inner().unwrap().into_iter().collect();
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::Path};
+
+    use anyhow::Result;
+
+    use super::*;
+
+    fn create_files(root: &Path, names: &[&str]) -> Result<()> {
+        for name in names {
+            let path = root.join(name);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, "")?;
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn find_files() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let root = directory.path();
+        create_files(
+            root,
+            &[
+                "main.tex",
+                "references.bib",
+                "chapters/intro.tex",
+                "chapters/notes.txt",
+                "build/main.tex",
+                ".git/main.tex",
+            ],
+        )?;
+
+        fs::write(root.join(".gitignore"), "build/\n")?;
+        fs::create_dir_all(root.join(".git/info"))?;
+        fs::write(root.join(".git/info/exclude"), "references.bib\n")?;
+
+        assert_eq!(
+            find_source_files(&[root.to_path_buf()]),
+            vec![root.join("chapters/intro.tex"), root.join("main.tex")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_files_with_patterns() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let root = directory.path();
+        create_files(
+            root,
+            &[
+                "chapter1.tex",
+                "chapterA.tex",
+                "draft1.tex",
+                "drafts.tex",
+                "keep.tex",
+            ],
+        )?;
+
+        fs::write(
+            root.join(".ignore"),
+            "chapter[0-9].tex\ndraft[!s].tex\n*.tex  \n!keep.tex\n!chapterA.tex\n!drafts.tex\n",
+        )?;
+
+        assert_eq!(
+            find_source_files(&[root.to_path_buf()]),
+            vec![
+                root.join("chapterA.tex"),
+                root.join("drafts.tex"),
+                root.join("keep.tex")
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_files_in_repository_subdirectory() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let root = directory.path();
+        create_files(
+            root,
+            &[
+                "paper/main.tex",
+                "paper/build/main.tex",
+                "paper/references.bib",
+            ],
+        )?;
+
+        fs::write(root.join(".gitignore"), "build/\n")?;
+        fs::create_dir_all(root.join(".git/info"))?;
+        fs::write(root.join(".git/info/exclude"), "*.bib\n")?;
+
+        assert_eq!(
+            find_source_files(&[root.join("paper")]),
+            vec![root.join("paper/main.tex")]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_files_in_overlapping_directories() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let root = directory.path();
+        create_files(root, &["main.tex", "chapters/intro.tex"])?;
+
+        assert_eq!(
+            find_source_files(&[root.to_path_buf(), root.join("chapters")]),
+            vec![root.join("chapters/intro.tex"), root.join("main.tex")]
+        );
+        Ok(())
+    }
+}
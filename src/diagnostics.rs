@@ -1,19 +1,26 @@
+mod bib_log;
 mod bibtex;
 mod build;
 mod chktex;
+mod citations;
+mod labels;
 mod latex;
 
 use std::sync::Arc;
 
 use dashmap::DashMap;
-use lsp_types::{DiagnosticSeverity, NumberOrString, Range, Url};
+use lsp_types::{
+    DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, NumberOrString, Range, Url,
+};
 use regex::Regex;
 
-use crate::Workspace;
+use crate::{syntax::latex::ImplicitLinks, Document, Workspace};
 
 use self::{
-    bibtex::collect_bibtex_diagnostics, build::collect_build_diagnostics,
-    chktex::collect_chktex_diagnostics, latex::collect_latex_diagnostics,
+    bib_log::collect_bib_log_diagnostics, bibtex::collect_bibtex_diagnostics,
+    build::collect_build_diagnostics, chktex::collect_chktex_diagnostics,
+    citations::collect_citation_diagnostics, labels::collect_label_diagnostics,
+    latex::collect_latex_diagnostics,
 };
 
 #[derive(Debug, PartialEq, Eq, Clone)]
@@ -22,21 +29,50 @@ pub struct Diagnostic {
     pub range: Range,
     pub code: DiagnosticCode,
     pub message: String,
+    pub related_information: Vec<DiagnosticRelatedInformation>,
 }
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Hash)]
 pub enum DiagnosticCode {
     Latex(LatexCode),
     Bibtex(BibtexCode),
+    Label(LabelCode),
+    Citation(CitationCode),
     Chktex(String),
     Build(Arc<Url>),
 }
 
+/// Assigns the numbers that are sent to the client to the variants of a code
+/// and derives the conversions in both directions from this single table.
+macro_rules! diagnostic_code_numbers {
+    ($code:ident { $($variant:ident = $number:literal),* $(,)? }) => {
+        impl From<$code> for NumberOrString {
+            fn from(code: $code) -> Self {
+                match code {
+                    $($code::$variant => NumberOrString::Number($number),)*
+                }
+            }
+        }
+
+        impl TryFrom<&NumberOrString> for $code {
+            type Error = ();
+
+            fn try_from(code: &NumberOrString) -> Result<Self, Self::Error> {
+                match code {
+                    $(NumberOrString::Number($number) => Ok($code::$variant),)*
+                    _ => Err(()),
+                }
+            }
+        }
+    };
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 pub enum LatexCode {
     UnexpectedRCurly,
     RCurlyInserted,
     MismatchedEnvironment,
+    UnknownKey,
 }
 
 impl From<LatexCode> for String {
@@ -45,19 +81,17 @@ impl From<LatexCode> for String {
             LatexCode::UnexpectedRCurly => "Unexpected \"}\"".to_string(),
             LatexCode::RCurlyInserted => "Missing \"}\" inserted".to_string(),
             LatexCode::MismatchedEnvironment => "Mismatched environment".to_string(),
+            LatexCode::UnknownKey => "Unknown key".to_string(),
         }
     }
 }
 
-impl From<LatexCode> for NumberOrString {
-    fn from(code: LatexCode) -> Self {
-        match code {
-            LatexCode::UnexpectedRCurly => NumberOrString::Number(1),
-            LatexCode::RCurlyInserted => NumberOrString::Number(2),
-            LatexCode::MismatchedEnvironment => NumberOrString::Number(3),
-        }
-    }
-}
+diagnostic_code_numbers!(LatexCode {
+    UnexpectedRCurly = 1,
+    RCurlyInserted = 2,
+    MismatchedEnvironment = 3,
+    UnknownKey = 15,
+});
 
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
 #[allow(clippy::enum_variant_names)]
@@ -81,18 +115,60 @@ impl From<BibtexCode> for String {
     }
 }
 
-impl From<BibtexCode> for NumberOrString {
-    fn from(code: BibtexCode) -> Self {
+diagnostic_code_numbers!(BibtexCode {
+    ExpectingLCurly = 4,
+    ExpectingKey = 5,
+    ExpectingRCurly = 6,
+    ExpectingEq = 7,
+    ExpectingFieldValue = 8,
+});
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum LabelCode {
+    UndefinedReference,
+    UnusedLabel,
+    DuplicateLabel,
+}
+
+impl From<LabelCode> for String {
+    fn from(code: LabelCode) -> Self {
         match code {
-            BibtexCode::ExpectingLCurly => NumberOrString::Number(4),
-            BibtexCode::ExpectingKey => NumberOrString::Number(5),
-            BibtexCode::ExpectingRCurly => NumberOrString::Number(6),
-            BibtexCode::ExpectingEq => NumberOrString::Number(7),
-            BibtexCode::ExpectingFieldValue => NumberOrString::Number(8),
+            LabelCode::UndefinedReference => "Undefined reference".to_string(),
+            LabelCode::UnusedLabel => "Unused label".to_string(),
+            LabelCode::DuplicateLabel => "Duplicate label".to_string(),
         }
     }
 }
 
+diagnostic_code_numbers!(LabelCode {
+    UndefinedReference = 9,
+    UnusedLabel = 10,
+    DuplicateLabel = 11,
+});
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum CitationCode {
+    UndefinedCitation,
+    UnusedEntry,
+    DuplicateEntry,
+}
+
+impl From<CitationCode> for String {
+    fn from(code: CitationCode) -> Self {
+        match code {
+            CitationCode::UndefinedCitation => "Undefined citation".to_string(),
+            CitationCode::UnusedEntry => "Unused entry".to_string(),
+            CitationCode::DuplicateEntry => "Duplicate entry key".to_string(),
+        }
+    }
+}
+
+diagnostic_code_numbers!(CitationCode {
+    UndefinedCitation = 12,
+    UnusedEntry = 13,
+    DuplicateEntry = 14,
+});
+
 #[derive(Default, Clone)]
 pub struct DiagnosticManager {
     all_diagnostics: Arc<DashMap<Arc<Url>, Vec<Diagnostic>>>,
@@ -102,15 +178,34 @@ impl DiagnosticManager {
     pub fn push_syntax(&self, workspace: &Workspace, uri: &Url) {
         collect_bibtex_diagnostics(&self.all_diagnostics, workspace, uri)
             .or_else(|| collect_latex_diagnostics(&self.all_diagnostics, workspace, uri))
-            .or_else(|| collect_build_diagnostics(&self.all_diagnostics, workspace, uri));
+            .or_else(|| collect_build_diagnostics(&self.all_diagnostics, workspace, uri))
+            .or_else(|| collect_bib_log_diagnostics(&self.all_diagnostics, workspace, uri));
+    }
+
+    pub fn push_references(&self, workspace: &Workspace, uri: &Url) {
+        let slice = workspace.slice(uri);
+        collect_label_diagnostics(&self.all_diagnostics, &slice);
+        collect_citation_diagnostics(&self.all_diagnostics, &slice);
     }
 
     pub fn push_chktex(&self, workspace: &Workspace, uri: &Url) {
         collect_chktex_diagnostics(&self.all_diagnostics, workspace, uri);
     }
 
+    /// Removes the diagnostics of a document as well as the build diagnostics
+    /// that originate from it.
+    pub fn remove(&self, uri: &Url) {
+        self.all_diagnostics.remove(uri);
+        for mut diagnostics in self.all_diagnostics.iter_mut() {
+            diagnostics.retain(
+                |diag| !matches!(&diag.code, DiagnosticCode::Build(log) if log.as_ref() == uri),
+            );
+        }
+    }
+
     pub fn publish(&self, workspace: &Workspace, uri: &Url) -> Vec<lsp_types::Diagnostic> {
-        let options = &workspace.environment.options.diagnostics;
+        let environment = workspace.environment.scoped(uri);
+        let options = &environment.options.diagnostics;
 
         let mut results = Vec::new();
         if let Some(diagnostics) = self.all_diagnostics.get(uri) {
@@ -133,7 +228,10 @@ impl DiagnosticManager {
                 }
 
                 let source = match diagnostic.code {
-                    DiagnosticCode::Latex(_) | DiagnosticCode::Bibtex(_) => "texlab",
+                    DiagnosticCode::Latex(_)
+                    | DiagnosticCode::Bibtex(_)
+                    | DiagnosticCode::Label(_)
+                    | DiagnosticCode::Citation(_) => "texlab",
                     DiagnosticCode::Chktex(_) => "chktex",
                     DiagnosticCode::Build(_) => "latex-build",
                 };
@@ -141,16 +239,32 @@ impl DiagnosticManager {
                 let code = match diagnostic.code.clone() {
                     DiagnosticCode::Latex(code) => Some(code.into()),
                     DiagnosticCode::Bibtex(code) => Some(code.into()),
+                    DiagnosticCode::Label(code) => Some(code.into()),
+                    DiagnosticCode::Citation(code) => Some(code.into()),
                     DiagnosticCode::Chktex(code) => Some(NumberOrString::String(code)),
                     DiagnosticCode::Build(_) => None,
                 };
 
+                let tags = match diagnostic.code {
+                    DiagnosticCode::Label(LabelCode::UnusedLabel)
+                    | DiagnosticCode::Citation(CitationCode::UnusedEntry) => {
+                        Some(vec![DiagnosticTag::UNNECESSARY])
+                    }
+                    _ => None,
+                };
+
                 results.push(lsp_types::Diagnostic {
                     range: diagnostic.range,
                     code,
                     severity: Some(diagnostic.severity),
                     message: diagnostic.message.clone(),
                     source: Some(source.to_string()),
+                    tags,
+                    related_information: if diagnostic.related_information.is_empty() {
+                        None
+                    } else {
+                        Some(diagnostic.related_information.clone())
+                    },
                     ..Default::default()
                 });
             }
@@ -160,6 +274,31 @@ impl DiagnosticManager {
     }
 }
 
+/// Removes the diagnostics that were reported by the given log file
+/// and finds the root document whose compilation produced it.
+fn reset_log_diagnostics<'a>(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    workspace: &'a Workspace,
+    log_uri: &Url,
+    links: impl Fn(&ImplicitLinks) -> &[Arc<Url>],
+) -> Option<&'a Document> {
+    all_diagnostics.alter_all(|_, mut diagnostics| {
+        diagnostics.retain(
+            |diag| !matches!(&diag.code, DiagnosticCode::Build(uri) if uri.as_ref() == log_uri),
+        );
+        diagnostics
+    });
+
+    workspace.documents_by_uri.values().find(|document| {
+        document.data.as_latex().map_or(false, |data| {
+            !document.uri.as_str().ends_with(".aux")
+                && links(&data.extras.implicit_links)
+                    .iter()
+                    .any(|u| u.as_ref() == log_uri)
+        })
+    })
+}
+
 #[derive(Debug, Default)]
 pub struct DiagnosticFilter {
     pub allowed_patterns: Vec<Regex>,
@@ -0,0 +1,161 @@
+use std::{path::Path, sync::Arc};
+
+use dashmap::DashMap;
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range, Url};
+use rowan::{ast::AstNode, TextRange, TextSize};
+
+use crate::{
+    syntax::{
+        bib_log::{BibLogMessage, BibLogMessageKind},
+        bibtex::{self, HasName},
+        build_log::BuildErrorLevel,
+        latex,
+    },
+    Document, LineCol, LineIndexExt, Workspace,
+};
+
+use super::{reset_log_diagnostics, Diagnostic, DiagnosticCode};
+
+pub fn collect_bib_log_diagnostics(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    workspace: &Workspace,
+    bib_log_uri: &Url,
+) -> Option<()> {
+    let bib_log_document = workspace.documents_by_uri.get(bib_log_uri)?;
+    let bib_log = bib_log_document.data.as_bib_log()?;
+
+    let root_document =
+        reset_log_diagnostics(all_diagnostics, workspace, bib_log_uri, |links| &links.blg)?;
+
+    let slice = workspace.slice(&root_document.uri);
+    for message in &bib_log.messages {
+        let severity = match message.level {
+            BuildErrorLevel::Error => DiagnosticSeverity::ERROR,
+            BuildErrorLevel::Warning => DiagnosticSeverity::WARNING,
+        };
+
+        let mut locations = find_locations(&slice, message);
+        if locations.is_empty() {
+            locations.push((root_document, TextRange::default()));
+        }
+
+        let log_position = Position::new(message.log_line, 0);
+        for (document, range) in locations {
+            let diagnostic = Diagnostic {
+                severity,
+                range: document.line_index.line_col_lsp_range(range),
+                code: DiagnosticCode::Build(Arc::clone(&bib_log_document.uri)),
+                message: message.message.clone(),
+                related_information: vec![DiagnosticRelatedInformation {
+                    location: Location::new(
+                        bib_log_document.uri.as_ref().clone(),
+                        Range::new(log_position, log_position),
+                    ),
+                    message: "Reported in the bibliography log".to_string(),
+                }],
+            };
+
+            all_diagnostics
+                .entry(Arc::clone(&document.uri))
+                .or_default()
+                .push(diagnostic);
+        }
+    }
+
+    Some(())
+}
+
+fn find_locations<'a>(
+    workspace: &'a Workspace,
+    message: &BibLogMessage,
+) -> Vec<(&'a Document, TextRange)> {
+    let bib_documents = || {
+        workspace.documents_by_uri.values().filter(|document| {
+            document.data.as_bibtex().is_some()
+                && message
+                    .file
+                    .as_deref()
+                    .map_or(true, |file| has_file_name(document, file))
+        })
+    };
+
+    if let Some(line) = message.line {
+        return bib_documents()
+            .filter_map(|document| {
+                let key = match &message.kind {
+                    BibLogMessageKind::DuplicateEntry(key) | BibLogMessageKind::Entry(key) => {
+                        Some(key.as_str())
+                    }
+                    BibLogMessageKind::Generic | BibLogMessageKind::MissingEntry(_) => None,
+                };
+
+                Some((document, find_line_range(document, line, key)?))
+            })
+            .collect();
+    }
+
+    match &message.kind {
+        BibLogMessageKind::MissingEntry(key) => workspace
+            .documents_by_uri
+            .values()
+            .filter_map(|document| Some((document, document.data.as_latex()?)))
+            .flat_map(|(document, data)| {
+                latex::SyntaxNode::new_root(data.green.clone())
+                    .descendants()
+                    .filter_map(latex::Citation::cast)
+                    .filter_map(|citation| citation.key_list())
+                    .flat_map(|keys| keys.keys())
+                    .filter(|citation_key| citation_key.to_string() == *key)
+                    .map(|citation_key| (document, latex::small_range(&citation_key)))
+                    .collect::<Vec<_>>()
+            })
+            .collect(),
+        BibLogMessageKind::DuplicateEntry(key) => find_entries(bib_documents(), key)
+            .into_iter()
+            .skip(1)
+            .collect(),
+        BibLogMessageKind::Entry(key) => find_entries(bib_documents(), key)
+            .into_iter()
+            .take(1)
+            .collect(),
+        BibLogMessageKind::Generic => Vec::new(),
+    }
+}
+
+fn find_entries<'a>(
+    documents: impl Iterator<Item = &'a Document>,
+    key: &str,
+) -> Vec<(&'a Document, TextRange)> {
+    documents
+        .filter_map(|document| Some((document, document.data.as_bibtex()?)))
+        .flat_map(|(document, data)| {
+            bibtex::SyntaxNode::new_root(data.green.clone())
+                .children()
+                .filter_map(bibtex::Entry::cast)
+                .filter_map(|entry| entry.name_token())
+                .filter(|name| name.text() == key)
+                .map(|name| (document, name.text_range()))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
+fn find_line_range(document: &Document, line: u32, key: Option<&str>) -> Option<TextRange> {
+    let text = document.text.lines().nth(line as usize)?;
+    let start = document.line_index.offset(LineCol { line, col: 0 });
+    let (begin, end) = match key.and_then(|key| Some((text.find(key)?, key))) {
+        Some((begin, key)) => (begin, begin + key.len()),
+        None => (text.len() - text.trim_start().len(), text.trim_end().len()),
+    };
+
+    Some(TextRange::new(
+        start + TextSize::from(begin as u32),
+        start + TextSize::from(end.max(begin) as u32),
+    ))
+}
+
+fn has_file_name(document: &Document, file: &Path) -> bool {
+    document.uri.to_file_path().ok().map_or(false, |path| {
+        path.file_name().is_some() && path.file_name() == file.file_name()
+    })
+}
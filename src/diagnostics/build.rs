@@ -1,11 +1,18 @@
 use std::{path::PathBuf, sync::Arc};
 
 use dashmap::DashMap;
-use lsp_types::{DiagnosticSeverity, Position, Range, Url};
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, Location, Position, Range, Url};
+use rowan::{ast::AstNode, TextRange, TextSize};
 
-use crate::{syntax::build_log::BuildErrorLevel, Workspace};
+use crate::{
+    syntax::{
+        build_log::{BuildError, BuildErrorKind, BuildErrorLevel},
+        latex,
+    },
+    Document, LineCol, LineIndexExt, Workspace,
+};
 
-use super::{Diagnostic, DiagnosticCode};
+use super::{reset_log_diagnostics, Diagnostic, DiagnosticCode};
 
 pub fn collect_build_diagnostics(
     all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
@@ -15,39 +22,17 @@ pub fn collect_build_diagnostics(
     let build_log_document = workspace.documents_by_uri.get(build_log_uri)?;
     let build_log = build_log_document.data.as_build_log()?;
 
-    all_diagnostics.alter_all(|_, mut diagnostics| {
-        diagnostics.retain(
-            |diag| !matches!(&diag.code, DiagnosticCode::Build(uri) if uri.as_ref() == build_log_uri),
-        );
-        diagnostics
-    });
-
-    let root_document = workspace.documents_by_uri.values().find(|document| {
-        document.data.as_latex().map_or(false, |data| {
-            !document.uri.as_str().ends_with(".aux")
-                && data
-                    .extras
-                    .implicit_links
-                    .log
-                    .iter()
-                    .any(|u| u.as_ref() == build_log_uri)
-        })
-    })?;
+    let root_document =
+        reset_log_diagnostics(all_diagnostics, workspace, build_log_uri, |links| {
+            &links.log
+        })?;
 
     let base_path = PathBuf::from(root_document.uri.path());
     for error in &build_log.errors {
-        let position = Position::new(error.line.unwrap_or(0), 0);
         let severity = match error.level {
             BuildErrorLevel::Error => DiagnosticSeverity::ERROR,
             BuildErrorLevel::Warning => DiagnosticSeverity::WARNING,
         };
-        let range = Range::new(position, position);
-        let diagnostic = Diagnostic {
-            severity,
-            range,
-            code: DiagnosticCode::Build(Arc::clone(&build_log_document.uri)),
-            message: error.message.clone(),
-        };
 
         let full_path = base_path.join(&error.relative_path);
 
@@ -61,8 +46,233 @@ pub fn collect_build_diagnostics(
             Arc::clone(&root_document.uri)
         };
 
+        let range = workspace
+            .documents_by_uri
+            .get(&uri)
+            .and_then(|document| {
+                find_range(document, error)
+                    .map(|range| document.line_index.line_col_lsp_range(range))
+            })
+            .unwrap_or_else(|| {
+                let position = Position::new(error.line.unwrap_or(0), 0);
+                Range::new(position, position)
+            });
+
+        let log_position = Position::new(error.log_line, 0);
+        let diagnostic = Diagnostic {
+            severity,
+            range,
+            code: DiagnosticCode::Build(Arc::clone(&build_log_document.uri)),
+            message: error.message.clone(),
+            related_information: vec![DiagnosticRelatedInformation {
+                location: Location::new(
+                    build_log_document.uri.as_ref().clone(),
+                    Range::new(log_position, log_position),
+                ),
+                message: "Reported in the build log".to_string(),
+            }],
+        };
+
         all_diagnostics.entry(uri).or_default().push(diagnostic);
     }
 
     Some(())
 }
+
+fn find_range(document: &Document, error: &BuildError) -> Option<TextRange> {
+    let line = error.line?;
+    let text = document.text.lines().nth(line as usize)?;
+    let start = document.line_index.offset(LineCol { line, col: 0 });
+    let range = |begin: usize, end: usize| {
+        TextRange::new(
+            start + TextSize::from(begin as u32),
+            start + TextSize::from(end as u32),
+        )
+    };
+
+    match &error.kind {
+        // Only the keys of references and citations are considered here,
+        // so that a key does not match parts of other words on the same line.
+        BuildErrorKind::UndefinedReference(key) => document
+            .data
+            .as_latex()?
+            .extras
+            .label_names
+            .iter()
+            .filter(|label| !label.is_definition && label.text == key.as_str())
+            .map(|label| label.range)
+            .find(|label_range| range(0, text.len()).contains_range(*label_range)),
+        BuildErrorKind::UndefinedCitation(key) => {
+            let data = document.data.as_latex()?;
+            latex::SyntaxNode::new_root(data.green.clone())
+                .descendants()
+                .filter_map(latex::Citation::cast)
+                .filter_map(|citation| citation.key_list())
+                .flat_map(|keys| keys.keys())
+                .filter(|citation_key| citation_key.to_string() == *key)
+                .map(|citation_key| latex::small_range(&citation_key))
+                .find(|key_range| range(0, text.len()).contains_range(*key_range))
+        }
+        BuildErrorKind::BadBox(bad_box) => {
+            let end_line = bad_box.end_line.unwrap_or(line);
+            let end_text = document.text.lines().nth(end_line as usize)?;
+            let end = document.line_index.offset(LineCol {
+                line: end_line,
+                col: end_text.trim_end().len() as u32,
+            });
+
+            // The log may be outdated, so the range can be empty or even reversed.
+            let indent = text.len() - text.trim_start().len();
+            let begin = start + TextSize::from(indent as u32);
+            (begin <= end).then(|| TextRange::new(begin, end))
+        }
+        BuildErrorKind::Generic | BuildErrorKind::Rerun | BuildErrorKind::Font => {
+            let context = error.context.as_ref()?;
+            let before = context.before.trim_start_matches("...");
+            if before.is_empty() {
+                let after = context.after.trim();
+                let begin = text.find(after).filter(|_| !after.is_empty())?;
+                let length = leading_token(after).len();
+                Some(range(begin, begin + length))
+            } else {
+                let end = text.find(before)? + before.len();
+                let length = trailing_token(before).len();
+                Some(range(end - length, end))
+            }
+        }
+    }
+}
+
+/// Returns the control sequence or word that ends the given text.
+fn trailing_token(text: &str) -> &str {
+    let mut start = text
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_alphabetic() || *c == '@')
+        .last()
+        .map_or(text.len(), |(i, _)| i);
+
+    if start == text.len() {
+        start = text.char_indices().last().map_or(0, |(i, _)| i);
+    }
+
+    match text[..start].strip_suffix('\\') {
+        Some(prefix) => &text[prefix.len()..],
+        None => &text[start..],
+    }
+}
+
+/// Returns the control sequence or word that starts the given text.
+fn leading_token(text: &str) -> &str {
+    let (prefix, rest) = match text.strip_prefix('\\') {
+        Some(rest) => ("\\", rest),
+        None => ("", text),
+    };
+
+    let length = rest
+        .char_indices()
+        .find(|(_, c)| !(c.is_alphabetic() || *c == '@'))
+        .map_or(rest.len(), |(i, _)| i);
+
+    match length {
+        0 => rest
+            .chars()
+            .next()
+            .map_or(text, |c| &text[..prefix.len() + c.len_utf8()]),
+        _ => &text[..prefix.len() + length],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::{
+        syntax::build_log::{BadBox, BadBoxKind},
+        DocumentLanguage, Environment,
+    };
+
+    use super::*;
+
+    fn parse(text: &str) -> Document {
+        Document::parse(
+            &Environment::default(),
+            Arc::new(Url::parse("http://example.com/main.tex").unwrap()),
+            Arc::new(text.to_string()),
+            DocumentLanguage::Latex,
+        )
+    }
+
+    fn undefined_key(text: &str, kind: BuildErrorKind) -> Option<TextRange> {
+        let error = BuildError {
+            relative_path: PathBuf::from("main.tex"),
+            level: BuildErrorLevel::Warning,
+            kind,
+            message: String::new(),
+            line: Some(0),
+            context: None,
+            log_line: 0,
+        };
+
+        find_range(&parse(text), &error)
+    }
+
+    fn bad_box(text: &str, line: u32, end_line: u32) -> Option<TextRange> {
+        let document = parse(text);
+        let error = BuildError {
+            relative_path: PathBuf::from("main.tex"),
+            level: BuildErrorLevel::Warning,
+            kind: BuildErrorKind::BadBox(BadBox {
+                kind: BadBoxKind::Overfull,
+                detail: "1.0pt too wide".to_string(),
+                end_line: Some(end_line),
+            }),
+            message: "Overfull \\hbox".to_string(),
+            line: Some(line),
+            context: None,
+            log_line: 0,
+        };
+
+        find_range(&document, &error)
+    }
+
+    #[test]
+    fn bad_box_paragraph() {
+        assert_eq!(
+            bad_box("foo\n  bar baz\nqux \n", 1, 2),
+            Some(TextRange::new(6.into(), 17.into()))
+        );
+    }
+
+    #[test]
+    fn bad_box_blank_line() {
+        assert_eq!(bad_box("foo\n   \nbar", 1, 1), None);
+    }
+
+    #[test]
+    fn bad_box_outdated_log() {
+        assert_eq!(bad_box("foo bar\n    baz", 1, 0), None);
+    }
+
+    #[test]
+    fn undefined_reference_key() {
+        assert_eq!(
+            undefined_key(
+                "\\ref{figure} \\ref{fig}",
+                BuildErrorKind::UndefinedReference("fig".into())
+            ),
+            Some(TextRange::new(18.into(), 21.into()))
+        );
+    }
+
+    #[test]
+    fn undefined_citation_key() {
+        assert_eq!(
+            undefined_key(
+                "\\cite{foobar, foo}",
+                BuildErrorKind::UndefinedCitation("foo".into())
+            ),
+            Some(TextRange::new(14.into(), 17.into()))
+        );
+    }
+}
@@ -28,12 +28,12 @@ pub fn collect_chktex_diagnostics(
         diagnostics
     });
 
-    let current_dir = workspace
-        .environment
+    let environment = workspace.environment.scoped(uri);
+    let current_dir = environment
         .options
         .root_directory
         .as_ref()
-        .cloned()
+        .map(|root_dir| environment.current_directory.join(root_dir))
         .or_else(|| {
             if document.uri.scheme() == "file" {
                 document
@@ -96,6 +96,7 @@ fn lint(text: &str, current_dir: &Path) -> io::Result<Vec<Diagnostic>> {
             severity,
             code: DiagnosticCode::Chktex(code.into()),
             message,
+            related_information: Vec::new(),
         });
     }
 
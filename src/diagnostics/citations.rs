@@ -0,0 +1,123 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use lsp_types::{DiagnosticSeverity, Url};
+use rowan::{ast::AstNode, TextRange};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    syntax::{
+        bibtex::{self, HasName},
+        latex,
+    },
+    Document, DocumentData, LineIndexExt, Workspace,
+};
+
+use super::{CitationCode, Diagnostic, DiagnosticCode};
+
+pub fn collect_citation_diagnostics(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    workspace: &Workspace,
+) -> Option<()> {
+    for uri in workspace.documents_by_uri.keys() {
+        all_diagnostics.alter(uri, |_, mut diagnostics| {
+            diagnostics.retain(|diag| !matches!(diag.code, DiagnosticCode::Citation(_)));
+            diagnostics
+        });
+    }
+
+    let mut citations = Vec::new();
+    let mut entries = Vec::new();
+    for document in workspace.documents_by_uri.values() {
+        match &document.data {
+            DocumentData::Latex(data) => {
+                latex::SyntaxNode::new_root(data.green.clone())
+                    .descendants()
+                    .filter_map(latex::Citation::cast)
+                    .filter_map(|citation| citation.key_list())
+                    .flat_map(|keys| keys.keys())
+                    .for_each(|key| {
+                        citations.push((document, key.to_string(), latex::small_range(&key)));
+                    });
+            }
+            DocumentData::Bibtex(data) => {
+                bibtex::SyntaxNode::new_root(data.green.clone())
+                    .children()
+                    .filter_map(bibtex::Entry::cast)
+                    .filter_map(|entry| entry.name_token())
+                    .for_each(|key| {
+                        entries.push((document, key.text().to_string(), key.text_range()));
+                    });
+            }
+            DocumentData::BuildLog(_) | DocumentData::BibLog(_) => {}
+        }
+    }
+
+    // Without any BibTeX file, the bibliography is most likely written by hand.
+    if entries.is_empty() {
+        return Some(());
+    }
+
+    let mut entry_counts: FxHashMap<&str, usize> = FxHashMap::default();
+    for (_, key, _) in &entries {
+        *entry_counts.entry(key).or_default() += 1;
+    }
+
+    let cited_keys: FxHashSet<&str> = citations.iter().map(|(_, key, _)| key.as_str()).collect();
+    for (document, key, range) in &citations {
+        if key != "*" && !entry_counts.contains_key(key.as_str()) {
+            push_diagnostic(
+                all_diagnostics,
+                document,
+                *range,
+                CitationCode::UndefinedCitation,
+                DiagnosticSeverity::WARNING,
+            );
+        }
+    }
+
+    let options = &workspace.environment.options.diagnostics;
+    for (document, key, range) in &entries {
+        if entry_counts[key.as_str()] > 1 {
+            push_diagnostic(
+                all_diagnostics,
+                document,
+                *range,
+                CitationCode::DuplicateEntry,
+                DiagnosticSeverity::WARNING,
+            );
+        } else if options.unused_entries
+            && !cited_keys.contains("*")
+            && !cited_keys.contains(key.as_str())
+        {
+            push_diagnostic(
+                all_diagnostics,
+                document,
+                *range,
+                CitationCode::UnusedEntry,
+                DiagnosticSeverity::HINT,
+            );
+        }
+    }
+
+    Some(())
+}
+
+fn push_diagnostic(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    document: &Document,
+    range: TextRange,
+    code: CitationCode,
+    severity: DiagnosticSeverity,
+) {
+    all_diagnostics
+        .entry(Arc::clone(&document.uri))
+        .or_default()
+        .push(Diagnostic {
+            severity,
+            range: document.line_index.line_col_lsp_range(range),
+            code: DiagnosticCode::Citation(code),
+            message: String::from(code),
+            related_information: Vec::new(),
+        });
+}
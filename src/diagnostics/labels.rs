@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use dashmap::DashMap;
+use lsp_types::{DiagnosticSeverity, Url};
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{LineIndexExt, Workspace};
+
+use super::{Diagnostic, DiagnosticCode, LabelCode};
+
+pub fn collect_label_diagnostics(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    workspace: &Workspace,
+) -> Option<()> {
+    for uri in workspace.documents_by_uri.keys() {
+        all_diagnostics.alter(uri, |_, mut diagnostics| {
+            diagnostics.retain(|diag| !matches!(diag.code, DiagnosticCode::Label(_)));
+            diagnostics
+        });
+    }
+
+    let mut definitions: FxHashMap<&str, usize> = FxHashMap::default();
+    let mut references: FxHashSet<&str> = FxHashSet::default();
+    for data in workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| document.data.as_latex())
+    {
+        for label in &data.extras.label_names {
+            if label.is_definition {
+                *definitions.entry(&label.text).or_default() += 1;
+            } else {
+                references.insert(&label.text);
+            }
+        }
+    }
+
+    let options = &workspace.environment.options.diagnostics;
+    for document in workspace.documents_by_uri.values() {
+        let data = match document.data.as_latex() {
+            Some(data) => data,
+            None => continue,
+        };
+
+        for label in &data.extras.label_names {
+            let (code, severity) = if !label.is_definition {
+                if definitions.contains_key(label.text.as_str()) {
+                    continue;
+                }
+
+                (LabelCode::UndefinedReference, DiagnosticSeverity::WARNING)
+            } else if definitions[label.text.as_str()] > 1 {
+                (LabelCode::DuplicateLabel, DiagnosticSeverity::WARNING)
+            } else if options.unused_labels && !references.contains(label.text.as_str()) {
+                (LabelCode::UnusedLabel, DiagnosticSeverity::HINT)
+            } else {
+                continue;
+            };
+
+            all_diagnostics
+                .entry(Arc::clone(&document.uri))
+                .or_default()
+                .push(Diagnostic {
+                    severity,
+                    range: document.line_index.line_col_lsp_range(label.range),
+                    code: DiagnosticCode::Label(code),
+                    message: String::from(code),
+                    related_information: Vec::new(),
+                });
+        }
+    }
+
+    Some(())
+}
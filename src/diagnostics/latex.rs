@@ -4,7 +4,7 @@ use dashmap::DashMap;
 use lsp_types::{DiagnosticSeverity, Url};
 use rowan::{ast::AstNode, NodeOrToken, TextRange};
 
-use crate::{syntax::latex, Document, LineIndexExt, Workspace};
+use crate::{key_value::KeyValueGroup, syntax::latex, Document, LineIndexExt, Workspace};
 
 use super::{Diagnostic, DiagnosticCode, LatexCode};
 
@@ -25,7 +25,13 @@ pub fn collect_latex_diagnostics(
         diagnostics
     });
 
+    let environment = workspace.environment.scoped(uri);
+    let options = &environment.options.diagnostics;
     for node in latex::SyntaxNode::new_root(data.green.clone()).descendants() {
+        if options.unknown_keys {
+            analyze_key_value_group(all_diagnostics, document, node.clone());
+        }
+
         analyze_environment(all_diagnostics, document, node.clone())
             .or_else(|| analyze_curly_group(all_diagnostics, document, &node))
             .or_else(|| {
@@ -39,6 +45,7 @@ pub fn collect_latex_diagnostics(
                             range: document.line_index.line_col_lsp_range(node.text_range()),
                             code: DiagnosticCode::Latex(code),
                             message: String::from(code),
+                            related_information: Vec::new(),
                         });
 
                     Some(())
@@ -71,11 +78,50 @@ fn analyze_environment(
                     .line_col_lsp_range(latex::small_range(&name1)),
                 code: DiagnosticCode::Latex(code),
                 message: String::from(code),
+                related_information: Vec::new(),
             });
     }
     Some(())
 }
 
+fn analyze_key_value_group(
+    all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
+    document: &Document,
+    node: latex::SyntaxNode,
+) -> Option<()> {
+    let group = KeyValueGroup::cast(node)?;
+    if group.schema.allows_unknown_keys {
+        return None;
+    }
+
+    for entry in group.entries() {
+        let is_known = if group.schema.is_compact {
+            entry
+                .key
+                .chars()
+                .all(|c| group.schema.find_key(&c.to_string()).is_some())
+        } else {
+            group.schema.find_key(&entry.key).is_some()
+        };
+
+        if !is_known {
+            let code = LatexCode::UnknownKey;
+            all_diagnostics
+                .entry(Arc::clone(&document.uri))
+                .or_default()
+                .push(Diagnostic {
+                    severity: DiagnosticSeverity::WARNING,
+                    range: document.line_index.line_col_lsp_range(entry.key_range),
+                    code: DiagnosticCode::Latex(code),
+                    message: String::from(code),
+                    related_information: Vec::new(),
+                });
+        }
+    }
+
+    Some(())
+}
+
 fn analyze_curly_group(
     all_diagnostics: &DashMap<Arc<Url>, Vec<Diagnostic>>,
     document: &Document,
@@ -119,6 +165,7 @@ fn analyze_curly_group(
                     .line_col_lsp_range(TextRange::empty(node.text_range().end())),
                 code: DiagnosticCode::Latex(code),
                 message: String::from(code),
+                related_information: Vec::new(),
             });
     }
 
@@ -6,7 +6,7 @@ use lsp_types::Url;
 use crate::{
     line_index::LineIndex,
     syntax::{
-        bibtex, build_log,
+        bib_log, bibtex, build_log,
         latex::{self, LatexAnalyzerContext},
     },
     DocumentLanguage, Environment,
@@ -28,6 +28,7 @@ pub enum DocumentData {
     Latex(Box<LatexDocumentData>),
     Bibtex(BibtexDocumentData),
     BuildLog(Arc<build_log::Parse>),
+    BibLog(Arc<bib_log::Parse>),
 }
 
 impl DocumentData {
@@ -37,6 +38,7 @@ impl DocumentData {
             Self::Latex(_) => DocumentLanguage::Latex,
             Self::Bibtex(_) => DocumentLanguage::Bibtex,
             Self::BuildLog(_) => DocumentLanguage::BuildLog,
+            Self::BibLog(_) => DocumentLanguage::BibLog,
         }
     }
 
@@ -66,6 +68,15 @@ impl DocumentData {
             None
         }
     }
+
+    #[must_use]
+    pub fn as_bib_log(&self) -> Option<&bib_log::Parse> {
+        if let Self::BibLog(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -93,7 +104,8 @@ impl Document {
         let line_index = Arc::new(LineIndex::new(&text));
         let data = match language {
             DocumentLanguage::Latex => {
-                let green = latex::parse(&text).green;
+                let config = environment.options.experimental.syntax_config();
+                let green = latex::parse_with_config(&text, &config).green;
                 let root = latex::SyntaxNode::new_root(green.clone());
 
                 let base_uri = match &environment.options.root_directory {
@@ -123,6 +135,10 @@ impl Document {
                 let data = Arc::new(build_log::parse(&text));
                 DocumentData::BuildLog(data)
             }
+            DocumentLanguage::BibLog => {
+                let data = Arc::new(bib_log::parse(&text));
+                DocumentData::BibLog(data)
+            }
         };
 
         Self {
@@ -1,16 +1,29 @@
 use std::{path::PathBuf, sync::Arc};
 
-use lsp_types::{ClientCapabilities, ClientInfo};
+use lsp_types::{ClientCapabilities, ClientInfo, Url};
 
-use crate::{distro::Resolver, Options};
+use crate::{
+    component_db::{ComponentDatabase, COMPONENT_DATABASE},
+    distro::Resolver,
+    Options,
+};
 
 #[derive(Debug, Clone)]
 pub struct Environment {
     pub current_directory: Arc<PathBuf>,
+    pub workspace_folders: Arc<Vec<WorkspaceFolder>>,
+    pub project_folders: Arc<Vec<WorkspaceFolder>>,
     pub client_capabilities: Arc<ClientCapabilities>,
     pub client_info: Option<Arc<ClientInfo>>,
     pub options: Arc<Options>,
     pub resolver: Arc<Resolver>,
+    pub component_db: Arc<ComponentDatabase>,
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkspaceFolder {
+    pub path: PathBuf,
+    pub options: Arc<Options>,
 }
 
 impl Environment {
@@ -18,10 +31,37 @@ impl Environment {
     pub fn new(current_directory: Arc<PathBuf>) -> Self {
         Self {
             current_directory,
+            workspace_folders: Arc::default(),
+            project_folders: Arc::default(),
             client_capabilities: Arc::new(ClientCapabilities::default()),
             client_info: None,
             options: Arc::new(Options::default()),
             resolver: Arc::new(Resolver::default()),
+            component_db: Arc::clone(&COMPONENT_DATABASE),
+        }
+    }
+
+    /// Returns the environment of the innermost workspace folder or project directory
+    /// that contains the given document.
+    /// Inside of a folder, relative paths are resolved against the folder.
+    #[must_use]
+    pub fn scoped(&self, uri: &Url) -> Self {
+        let folder = uri.to_file_path().ok().and_then(|path| {
+            self.workspace_folders
+                .iter()
+                // `max_by_key` returns the last folder, so projects take precedence on ties.
+                .chain(self.project_folders.iter())
+                .filter(|folder| path.starts_with(&folder.path))
+                .max_by_key(|folder| folder.path.components().count())
+        });
+
+        match folder {
+            Some(folder) => Self {
+                current_directory: Arc::new(folder.path.clone()),
+                options: Arc::clone(&folder.options),
+                ..self.clone()
+            },
+            None => self.clone(),
         }
     }
 }
@@ -31,3 +71,61 @@ impl Default for Environment {
         Self::new(Arc::new(std::env::temp_dir()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scoped_innermost_folder() {
+        let root = std::env::temp_dir();
+        let folder = |name: &str, line_length| WorkspaceFolder {
+            path: root.join(name),
+            options: Arc::new(Options {
+                formatter_line_length: Some(line_length),
+                ..Options::default()
+            }),
+        };
+
+        let environment = Environment {
+            workspace_folders: Arc::new(vec![folder("repo", 80), folder("repo/paper", 100)]),
+            ..Environment::default()
+        };
+
+        let uri = Url::from_file_path(root.join("repo/paper/main.tex")).unwrap();
+        let scoped = environment.scoped(&uri);
+        assert_eq!(*scoped.current_directory, root.join("repo/paper"));
+        assert_eq!(scoped.options.formatter_line_length, Some(100));
+
+        let uri = Url::from_file_path(root.join("other/main.tex")).unwrap();
+        let scoped = environment.scoped(&uri);
+        assert_eq!(scoped.current_directory, environment.current_directory);
+        assert_eq!(scoped.options.formatter_line_length, None);
+    }
+
+    #[test]
+    fn scoped_project_folder() {
+        let root = std::env::temp_dir();
+        let folder = |name: &str, line_length| WorkspaceFolder {
+            path: root.join(name),
+            options: Arc::new(Options {
+                formatter_line_length: Some(line_length),
+                ..Options::default()
+            }),
+        };
+
+        let environment = Environment {
+            workspace_folders: Arc::new(vec![folder("repo", 80)]),
+            project_folders: Arc::new(vec![folder("repo", 90), folder("repo/paper", 100)]),
+            ..Environment::default()
+        };
+
+        let uri = Url::from_file_path(root.join("repo/main.tex")).unwrap();
+        let scoped = environment.scoped(&uri);
+        assert_eq!(scoped.options.formatter_line_length, Some(90));
+
+        let uri = Url::from_file_path(root.join("repo/paper/main.tex")).unwrap();
+        let scoped = environment.scoped(&uri);
+        assert_eq!(scoped.options.formatter_line_length, Some(100));
+    }
+}
@@ -1,4 +1,5 @@
 mod build;
+mod code_action;
 #[cfg(feature = "completion")]
 mod completion;
 mod cursor;
@@ -9,10 +10,15 @@ mod formatting;
 mod forward_search;
 mod highlight;
 mod hover;
+mod include_graph;
+mod inlay_hint;
+mod inverse_search;
 mod link;
 mod lsp_kinds;
 mod reference;
 mod rename;
+mod semantic_tokens;
+mod signature_help;
 mod symbol;
 
 use std::sync::Arc;
@@ -25,16 +31,31 @@ use crate::{Document, Workspace};
 pub use self::completion::{complete, CompletionItemData, COMPLETION_LIMIT};
 pub use self::{
     build::{BuildEngine, BuildParams, BuildResult, BuildStatus},
+    code_action::find_code_actions,
     definition::goto_definition,
     execute_command::execute_command,
     folding::find_foldings,
-    formatting::format_source_code,
-    forward_search::{execute_forward_search, ForwardSearchResult, ForwardSearchStatus},
+    formatting::{format_on_type, format_source_code, format_source_code_range},
+    forward_search::{
+        execute_forward_search, find_pdf_location, ForwardSearchResult, ForwardSearchStatus,
+        PdfLocation, PdfRect,
+    },
     highlight::find_document_highlights,
     hover::find_hover,
+    include_graph::{
+        find_include_tree, find_incoming_calls, find_outgoing_calls, find_project_root,
+        prepare_call_hierarchy, Include, IncludeGraphParams, IncludeKind, IncludeTree,
+    },
+    inlay_hint::find_inlay_hints,
+    inverse_search::{
+        execute_inverse_search, find_inverse_search_target, InverseSearchParams,
+        InverseSearchTarget,
+    },
     link::find_document_links,
     reference::find_all_references,
     rename::{prepare_rename_all, rename_all},
+    semantic_tokens::{find_semantic_tokens, find_semantic_tokens_range, semantic_tokens_legend},
+    signature_help::find_signature_help,
     symbol::{find_document_symbols, find_workspace_symbols},
 };
 
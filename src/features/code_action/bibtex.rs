@@ -0,0 +1,74 @@
+use std::sync::Arc;
+
+use lsp_types::{CodeActionParams, Diagnostic};
+use rowan::{ast::AstNode, TextRange};
+
+use crate::{
+    diagnostics::BibtexCode,
+    features::cursor::CursorContext,
+    syntax::bibtex::{self, HasEq, HasName},
+    LineIndexExt,
+};
+
+use super::CodeActionResult;
+
+pub(super) fn find_bibtex_quick_fixes(
+    context: &CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let main_document = context.request.main_document();
+    let data = main_document.data.as_bibtex()?;
+    let code = BibtexCode::try_from(diagnostic.code.as_ref()?).ok()?;
+    let offset = main_document.line_index.offset_lsp(diagnostic.range.start);
+    let root = bibtex::SyntaxNode::new_root(data.green.clone());
+
+    let (title, range, new_text) = match code {
+        BibtexCode::ExpectingRCurly => (
+            "Insert missing \"}\"".to_string(),
+            TextRange::empty(offset),
+            "}".to_string(),
+        ),
+        BibtexCode::ExpectingEq => {
+            let field = find_field(&root, offset)?;
+            let name = field.name_token()?;
+            (
+                "Insert \"=\"".to_string(),
+                TextRange::empty(name.text_range().end()),
+                " =".to_string(),
+            )
+        }
+        BibtexCode::ExpectingFieldValue => {
+            let field = find_field(&root, offset)?;
+            let eq = field.eq_token()?;
+            let position = eq
+                .next_token()
+                .filter(|token| token.kind() == bibtex::WHITESPACE)
+                .map_or_else(|| eq.text_range().end(), |token| token.text_range().end());
+
+            (
+                "Insert empty field value".to_string(),
+                TextRange::empty(position),
+                "{}".to_string(),
+            )
+        }
+        BibtexCode::ExpectingLCurly | BibtexCode::ExpectingKey => return None,
+    };
+
+    results.push(CodeActionResult {
+        title,
+        uri: Arc::clone(&main_document.uri),
+        range,
+        new_text,
+        diagnostic: Some(diagnostic.clone()),
+    });
+
+    Some(())
+}
+
+fn find_field(root: &bibtex::SyntaxNode, offset: rowan::TextSize) -> Option<bibtex::Field> {
+    root.token_at_offset(offset)
+        .right_biased()?
+        .parent_ancestors()
+        .find_map(bibtex::Field::cast)
+}
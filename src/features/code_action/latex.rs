@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use lsp_types::{CodeActionParams, Diagnostic};
+use rowan::{ast::AstNode, TextRange};
+
+use crate::{diagnostics::LatexCode, features::cursor::CursorContext, syntax::latex, LineIndexExt};
+
+use super::CodeActionResult;
+
+pub(super) fn find_latex_quick_fixes(
+    context: &CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let main_document = context.request.main_document();
+    let data = main_document.data.as_latex()?;
+    let code = LatexCode::try_from(diagnostic.code.as_ref()?).ok()?;
+    let offset = main_document.line_index.offset_lsp(diagnostic.range.start);
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+
+    let (title, range, new_text) = match code {
+        LatexCode::UnexpectedRCurly => {
+            let token = root.token_at_offset(offset).right_biased()?;
+            if token.kind() != latex::R_CURLY {
+                return None;
+            }
+
+            (
+                "Remove unexpected \"}\"".to_string(),
+                token.text_range(),
+                String::new(),
+            )
+        }
+        LatexCode::RCurlyInserted => (
+            "Insert missing \"}\"".to_string(),
+            TextRange::empty(offset),
+            "}".to_string(),
+        ),
+        LatexCode::MismatchedEnvironment => {
+            let environment = root
+                .token_at_offset(offset)
+                .right_biased()?
+                .parent_ancestors()
+                .find_map(latex::Environment::cast)?;
+
+            let begin_name = environment.begin()?.name()?.key()?;
+            let end_name = environment.end()?.name()?.key()?;
+            (
+                format!(
+                    "Change \\end{{{}}} to \\end{{{}}}",
+                    end_name.to_string(),
+                    begin_name.to_string()
+                ),
+                latex::small_range(&end_name),
+                begin_name.to_string(),
+            )
+        }
+        LatexCode::UnknownKey => return None,
+    };
+
+    results.push(CodeActionResult {
+        title,
+        uri: Arc::clone(&main_document.uri),
+        range,
+        new_text,
+        diagnostic: Some(diagnostic.clone()),
+    });
+
+    Some(())
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use lsp_types::CodeActionParams;
+use rowan::{ast::AstNode, TextRange};
+
+use crate::{features::cursor::CursorContext, syntax::latex, Document};
+
+use super::CodeActionResult;
+
+pub(super) fn find_package_import_actions(
+    context: &CursorContext<CodeActionParams>,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let token = context
+        .cursor
+        .as_latex()
+        .filter(|token| token.kind().is_command_name())?;
+
+    let name = &token.text()[1..];
+    let workspace = &context.request.workspace;
+    let component_db = &workspace.environment.component_db;
+    if is_user_defined(context, token.text())
+        || component_db
+            .linked_components(workspace)
+            .into_iter()
+            .flat_map(|component| component.commands.iter())
+            .any(|command| command.name == name)
+    {
+        return None;
+    }
+
+    let root_document = workspace
+        .find_parent(&context.request.uri)
+        .unwrap_or_else(|| context.request.main_document().clone());
+
+    let (range, prefix, suffix) = find_insert_position(&root_document)?;
+    for file_name in component_db
+        .components
+        .iter()
+        .filter(|component| component.commands.iter().any(|cmd| cmd.name == name))
+        .flat_map(|component| component.file_names.iter())
+        .filter(|file_name| file_name.ends_with(".sty"))
+    {
+        let stem = &file_name[0..file_name.len() - 4];
+        results.push(CodeActionResult {
+            title: format!("Add \\usepackage{{{}}}", stem),
+            uri: Arc::clone(&root_document.uri),
+            range,
+            new_text: format!("{}\\usepackage{{{}}}{}", prefix, stem, suffix),
+            diagnostic: None,
+        });
+    }
+
+    Some(())
+}
+
+fn is_user_defined(context: &CursorContext<CodeActionParams>, name: &str) -> bool {
+    context
+        .request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| latex::SyntaxNode::new_root(data.green.clone()).descendants())
+        .filter_map(latex::CommandDefinition::cast)
+        .filter_map(|definition| definition.name())
+        .any(|token| token.text() == name)
+}
+
+fn find_insert_position(document: &Document) -> Option<(TextRange, &'static str, &'static str)> {
+    let data = document.data.as_latex()?;
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+    let last_include = root
+        .descendants()
+        .filter(|node| matches!(node.kind(), latex::PACKAGE_INCLUDE | latex::CLASS_INCLUDE))
+        .filter_map(latex::Include::cast)
+        .last();
+
+    Some(match last_include {
+        Some(include) => {
+            let end = latex::small_range(&include).end();
+            (TextRange::empty(end), "\n", "")
+        }
+        None => (TextRange::empty(0.into()), "", "\n"),
+    })
+}
@@ -0,0 +1,127 @@
+use std::sync::Arc;
+
+use lsp_types::{CodeActionParams, Diagnostic};
+use rowan::{ast::AstNode, TextRange, TextSize};
+
+use crate::{
+    diagnostics::{CitationCode, LabelCode},
+    features::cursor::CursorContext,
+    syntax::latex,
+    LineIndexExt,
+};
+
+use super::CodeActionResult;
+
+pub(super) fn find_undefined_reference_quick_fixes(
+    context: &CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let code = diagnostic.code.as_ref()?;
+    if LabelCode::try_from(code) == Ok(LabelCode::UndefinedReference) {
+        find_undefined_label_fix(context, diagnostic, results)
+    } else if CitationCode::try_from(code) == Ok(CitationCode::UndefinedCitation) {
+        find_undefined_citation_fix(context, diagnostic, results)
+    } else {
+        None
+    }
+}
+
+fn find_undefined_label_fix(
+    context: &CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let main_document = context.request.main_document();
+    let data = main_document.data.as_latex()?;
+    let name = find_diagnostic_text(context, diagnostic)?;
+
+    // The stub goes to the end of the document instead of next to the reference,
+    // so that it does not silently resolve the reference at the wrong place.
+    let end_document = latex::SyntaxNode::new_root(data.green.clone())
+        .descendants()
+        .filter_map(latex::Environment::cast)
+        .find(|environment| {
+            environment
+                .begin()
+                .and_then(|begin| begin.name())
+                .and_then(|name| name.key())
+                .map_or(false, |name| name.to_string() == "document")
+        })
+        .and_then(|environment| environment.end());
+
+    let (offset, new_text) = match end_document {
+        Some(end) => (
+            end.syntax().text_range().start(),
+            format!("\\label{{{}}}\n", name),
+        ),
+        None => (
+            TextSize::try_from(main_document.text.len()).ok()?,
+            format!("{}\\label{{{}}}\n", line_prefix(&main_document.text), name),
+        ),
+    };
+
+    results.push(CodeActionResult {
+        title: format!("Create label \"{}\" at the end of the document", name),
+        uri: Arc::clone(&main_document.uri),
+        range: TextRange::empty(offset),
+        new_text,
+        diagnostic: Some(diagnostic.clone()),
+    });
+
+    Some(())
+}
+
+fn find_undefined_citation_fix(
+    context: &CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+    results: &mut Vec<CodeActionResult>,
+) -> Option<()> {
+    let key = find_diagnostic_text(context, diagnostic)?;
+
+    for document in context
+        .request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter(|document| document.data.as_bibtex().is_some())
+    {
+        let file_name = document
+            .uri
+            .path_segments()
+            .and_then(|mut segments| segments.next_back())
+            .unwrap_or_default();
+
+        let end = TextSize::try_from(document.text.len()).ok()?;
+        results.push(CodeActionResult {
+            title: format!("Create BibTeX entry \"{}\" in {}", key, file_name),
+            uri: Arc::clone(&document.uri),
+            range: TextRange::empty(end),
+            new_text: format!("{}\n@misc{{{},\n}}\n", line_prefix(&document.text), key),
+            diagnostic: Some(diagnostic.clone()),
+        });
+    }
+
+    Some(())
+}
+
+/// Returns the name of the label or citation key that the diagnostic points to.
+fn find_diagnostic_text<'a>(
+    context: &'a CursorContext<CodeActionParams>,
+    diagnostic: &Diagnostic,
+) -> Option<&'a str> {
+    let main_document = context.request.main_document();
+    let range = main_document.line_index.offset_lsp_range(diagnostic.range);
+    main_document
+        .text
+        .get(std::ops::Range::<usize>::from(range))
+        .filter(|text| !text.is_empty())
+}
+
+fn line_prefix(text: &str) -> &'static str {
+    if text.is_empty() || text.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    }
+}
@@ -6,12 +6,16 @@ mod color;
 mod color_model;
 mod component_command;
 mod component_environment;
+mod component_option;
 mod entry_type;
+mod environment_template;
 mod field;
 mod glossary_ref;
 mod import;
 mod include;
+mod key_value;
 mod label;
+mod math_symbol;
 mod theorem;
 mod tikz_library;
 mod types;
@@ -27,15 +31,15 @@ use lsp_types::{
     CompletionItem, CompletionList, CompletionParams, CompletionTextEdit, Documentation,
     InsertTextFormat, MarkupContent, MarkupKind, TextEdit,
 };
-use rowan::{ast::AstNode, TextSize};
+use rowan::{ast::AstNode, TextRange, TextSize};
 use rustc_hash::FxHashSet;
 
 use crate::{
     syntax::{
         bibtex::{self},
-        latex,
+        latex::{self, HasCurly},
     },
-    LineIndexExt,
+    ClientCapabilitiesExt, LineIndexExt,
 };
 
 use self::{
@@ -47,12 +51,16 @@ use self::{
     color_model::complete_color_models,
     component_command::complete_component_commands,
     component_environment::complete_component_environments,
+    component_option::complete_component_options,
     entry_type::complete_entry_types,
+    environment_template::complete_environment_templates,
     field::complete_fields,
     glossary_ref::complete_glossary_entries,
     import::complete_imports,
     include::complete_includes,
+    key_value::complete_key_values,
     label::complete_labels,
+    math_symbol::complete_math_symbols,
     theorem::complete_theorem_environments,
     tikz_library::complete_tikz_libraries,
     types::{InternalCompletionItem, InternalCompletionItemData},
@@ -81,6 +89,8 @@ pub fn complete(request: FeatureRequest<CompletionParams>) -> Option<CompletionL
     complete_arguments(&context, &mut items);
     complete_citations(&context, &mut items);
     complete_imports(&context, &mut items);
+    complete_component_options(&context, &mut items);
+    complete_key_values(&context, &mut items);
     complete_colors(&context, &mut items);
     complete_color_models(&context, &mut items);
     complete_acronyms(&context, &mut items);
@@ -91,7 +101,9 @@ pub fn complete(request: FeatureRequest<CompletionParams>) -> Option<CompletionL
     complete_component_environments(&context, &mut items);
     complete_theorem_environments(&context, &mut items);
     complete_user_environments(&context, &mut items);
+    complete_environment_templates(&context, &mut items);
     complete_begin_command(&context, &mut items);
+    complete_math_symbols(&context, &mut items);
     complete_component_commands(&context, &mut items);
     complete_user_commands(&context, &mut items);
 
@@ -136,6 +148,109 @@ pub fn complete(request: FeatureRequest<CompletionParams>) -> Option<CompletionL
     })
 }
 
+fn supports_snippets(request: &FeatureRequest<CompletionParams>) -> bool {
+    request
+        .workspace
+        .environment
+        .client_capabilities
+        .has_completion_snippet_support()
+}
+
+fn escape_snippet(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
+
+/// Escapes the backslashes of a template so that they are inserted literally,
+/// while keeping its snippet placeholders.
+fn escape_backslashes(text: &str) -> String {
+    text.replace('\\', "\\\\")
+}
+
+/// Creates the edit for the name of an environment. Inside of a `\begin` that is not
+/// followed by a matching `\end`, the whole environment is inserted as a snippet.
+fn environment_edit(
+    context: &CursorContext<CompletionParams>,
+    name: &str,
+    range: TextRange,
+) -> (TextEdit, Option<InsertTextFormat>) {
+    let line_index = &context.request.main_document().line_index;
+    match environment_snippet(context, name, range) {
+        Some((range, snippet)) => (
+            TextEdit::new(line_index.line_col_lsp_range(range), snippet),
+            Some(InsertTextFormat::SNIPPET),
+        ),
+        None => (
+            TextEdit::new(line_index.line_col_lsp_range(range), name.into()),
+            None,
+        ),
+    }
+}
+
+fn environment_snippet(
+    context: &CursorContext<CompletionParams>,
+    name: &str,
+    range: TextRange,
+) -> Option<(TextRange, String)> {
+    if !supports_snippets(&context.request) {
+        return None;
+    }
+
+    let (current_name, _, group) = context.find_curly_group_word()?;
+    let begin = latex::Begin::cast(group.syntax().parent()?)?;
+    let environment = latex::Environment::cast(begin.syntax().parent()?)?;
+
+    // The parser assigns the next `\end` to the environment, even if it belongs to an outer one.
+    let end_name = environment
+        .end()
+        .and_then(|end| end.name())
+        .and_then(|name| name.key())
+        .map(|name| name.to_string());
+
+    if end_name.map_or(false, |end_name| {
+        end_name == current_name || end_name == name
+    }) {
+        return None;
+    }
+
+    let end = group
+        .right_curly()
+        .map_or(range.end(), |token| token.text_range().end());
+
+    let options = &context
+        .request
+        .workspace
+        .environment
+        .scoped(&context.request.uri)
+        .options;
+
+    let template = options.completion.find_environment_template(name);
+    let arguments = template.map_or("", |template| template.arguments.as_str());
+    let body = template
+        .filter(|template| !template.body.is_empty())
+        .map_or_else(
+            || "\t$0".to_string(),
+            |template| {
+                template
+                    .body
+                    .iter()
+                    .map(|line| format!("\t{}", escape_backslashes(line)))
+                    .join("\n")
+            },
+        );
+
+    let name = escape_snippet(name);
+    let snippet = format!(
+        "{}}}{}\n{}\n\\\\end{{{}}}",
+        name,
+        escape_backslashes(arguments),
+        body,
+        name
+    );
+    Some((TextRange::new(range.start(), end), snippet))
+}
+
 fn dedup(items: Vec<InternalCompletionItem>) -> Vec<InternalCompletionItem> {
     let mut labels = FxHashSet::default();
     let mut insert = vec![false; items.len()];
@@ -206,9 +321,30 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
             InternalCompletionItemData::ComponentCommand { name, .. } => {
                 matcher.fuzzy_match(name, &pattern[1..])
             }
+            InternalCompletionItemData::MathSymbol {
+                name, unicode_name, ..
+            } => std::iter::once(matcher.fuzzy_match(name, &pattern[1..]))
+                .chain(unicode_name.map(|text| matcher.fuzzy_match(text, &pattern[1..])))
+                .max()
+                .flatten(),
             InternalCompletionItemData::ComponentEnvironment { name, .. } => {
                 matcher.fuzzy_match(name, &pattern)
             }
+            InternalCompletionItemData::EnvironmentTemplate { name } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
+            InternalCompletionItemData::UserEnvironmentTemplate { name } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
+            InternalCompletionItemData::ComponentOption { name, .. } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
+            InternalCompletionItemData::KeyValueKey { name, .. } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
+            InternalCompletionItemData::KeyValueValue { name } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
             InternalCompletionItemData::Class { name } => matcher.fuzzy_match(name, &pattern),
             InternalCompletionItemData::Package { name } => matcher.fuzzy_match(name, &pattern),
             InternalCompletionItemData::Color { name } => matcher.fuzzy_match(name, &pattern),
@@ -222,7 +358,9 @@ fn score(context: &CursorContext<CompletionParams>, items: &mut Vec<InternalComp
                 matcher.fuzzy_match(name, file_pattern)
             }
             InternalCompletionItemData::Label { name, .. } => matcher.fuzzy_match(name, &pattern),
-            InternalCompletionItemData::UserCommand { name } => matcher.fuzzy_match(name, &pattern),
+            InternalCompletionItemData::UserCommand { name, .. } => {
+                matcher.fuzzy_match(name, &pattern)
+            }
             InternalCompletionItemData::UserEnvironment { name } => {
                 matcher.fuzzy_match(name, &pattern)
             }
@@ -311,18 +449,7 @@ fn convert_internal_items(
             }
         }
         InternalCompletionItemData::BeginCommand => {
-            if context
-                .request
-                .workspace
-                .environment
-                .client_capabilities
-                .text_document
-                .as_ref()
-                .and_then(|cap| cap.completion.as_ref())
-                .and_then(|cap| cap.completion_item.as_ref())
-                .and_then(|cap| cap.snippet_support)
-                == Some(true)
-            {
+            if supports_snippets(&context.request) {
                 let text_edit = TextEdit::new(range, "begin{$1}\n\t$0\n\\end{$1}".into());
                 CompletionItem {
                     kind: Some(adjust_kind(
@@ -389,8 +516,39 @@ fn convert_internal_items(
                 ..CompletionItem::new_simple(name.to_string(), detail)
             }
         }
-        InternalCompletionItemData::ComponentEnvironment { name, file_names } => {
+        InternalCompletionItemData::MathSymbol {
+            name,
+            glyph,
+            unicode_name,
+            image,
+            package,
+        } => {
+            let package = package.unwrap_or("built-in");
+            let detail = unicode_name.map_or_else(
+                || format!("{}, {}", glyph, package),
+                |text| format!("{}, {}, {}", glyph, text, package),
+            );
+            let filter_text = std::iter::once(name)
+                .chain(unicode_name)
+                .chain(std::iter::once(glyph))
+                .join(" ");
+            let documentation =
+                image.and_then(|img| image_documentation(&context.request, name, img));
             let text_edit = TextEdit::new(range, name.to_string());
+            CompletionItem {
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Command.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Command).unwrap()),
+                filter_text: Some(filter_text),
+                documentation,
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::new_simple(name.to_string(), detail)
+            }
+        }
+        InternalCompletionItemData::ComponentEnvironment { name, file_names } => {
+            let (text_edit, insert_text_format) = environment_edit(context, name, item.range);
             CompletionItem {
                 kind: Some(adjust_kind(
                     &context.request,
@@ -398,9 +556,90 @@ fn convert_internal_items(
                 )),
                 data: Some(serde_json::to_value(CompletionItemData::Environment).unwrap()),
                 text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                insert_text_format,
                 ..CompletionItem::new_simple(name.to_string(), component_detail(file_names))
             }
         }
+        InternalCompletionItemData::EnvironmentTemplate { name } => {
+            let (text_edit, insert_text_format) = environment_edit(context, name, item.range);
+            CompletionItem {
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Environment.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Environment).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                insert_text_format,
+                ..CompletionItem::new_simple(name.into(), component_detail(&[]))
+            }
+        }
+        InternalCompletionItemData::UserEnvironmentTemplate { name } => {
+            let detail = "user-defined template".into();
+            let (text_edit, insert_text_format) = environment_edit(context, &name, item.range);
+            CompletionItem {
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Environment.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Environment).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                insert_text_format,
+                ..CompletionItem::new_simple(name, detail)
+            }
+        }
+        InternalCompletionItemData::ComponentOption {
+            name,
+            file_name,
+            documentation,
+        } => {
+            let text_edit = TextEdit::new(range, name.to_string());
+            CompletionItem {
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Argument).unwrap()),
+                documentation: documentation.map(|doc| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: doc.into(),
+                    })
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::new_simple(name.to_string(), file_name.into())
+            }
+        }
+        InternalCompletionItemData::KeyValueKey { name, key } => {
+            let text_edit = TextEdit::new(range, name.clone());
+            CompletionItem {
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Argument).unwrap()),
+                documentation: key.documentation.as_ref().map(|doc| {
+                    Documentation::MarkupContent(MarkupContent {
+                        kind: MarkupKind::Markdown,
+                        value: doc.into(),
+                    })
+                }),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::new_simple(name, key.value_type.as_str().into())
+            }
+        }
+        InternalCompletionItemData::KeyValueValue { name } => {
+            let text_edit = TextEdit::new(range, name.into());
+            CompletionItem {
+                label: name.into(),
+                kind: Some(adjust_kind(
+                    &context.request,
+                    Structure::Argument.completion_kind(),
+                )),
+                data: Some(serde_json::to_value(CompletionItemData::Argument).unwrap()),
+                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                ..CompletionItem::default()
+            }
+        }
         InternalCompletionItemData::Class { name } => {
             let text_edit = TextEdit::new(range, name.to_string());
             CompletionItem {
@@ -525,23 +764,49 @@ fn convert_internal_items(
                 ..CompletionItem::default()
             }
         }
-        InternalCompletionItemData::UserCommand { name } => {
+        InternalCompletionItemData::UserCommand { name, parameters } => {
             let detail = "user-defined".into();
             let name = &name[1..];
-            let text_edit = TextEdit::new(range, name.to_string());
-            CompletionItem {
-                kind: Some(adjust_kind(
-                    &context.request,
-                    Structure::Command.completion_kind(),
-                )),
-                data: Some(serde_json::to_value(CompletionItemData::Command).unwrap()),
-                text_edit: Some(CompletionTextEdit::Edit(text_edit)),
-                ..CompletionItem::new_simple(name.into(), detail)
+            let mandatory_parameters: Vec<_> = parameters
+                .iter()
+                .filter(|parameter| !parameter.is_optional)
+                .collect();
+
+            if !mandatory_parameters.is_empty() && supports_snippets(&context.request) {
+                let mut snippet = escape_snippet(name);
+                for (i, parameter) in mandatory_parameters.into_iter().enumerate() {
+                    snippet.push_str(&escape_snippet(&parameter.open));
+                    snippet.push_str(&format!("${{{}}}", i + 1));
+                    snippet.push_str(&escape_snippet(&parameter.close));
+                }
+
+                let text_edit = TextEdit::new(range, snippet);
+                CompletionItem {
+                    kind: Some(adjust_kind(
+                        &context.request,
+                        Structure::Command.completion_kind(),
+                    )),
+                    data: Some(serde_json::to_value(CompletionItemData::Command).unwrap()),
+                    text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                    insert_text_format: Some(InsertTextFormat::SNIPPET),
+                    ..CompletionItem::new_simple(name.into(), detail)
+                }
+            } else {
+                let text_edit = TextEdit::new(range, name.to_string());
+                CompletionItem {
+                    kind: Some(adjust_kind(
+                        &context.request,
+                        Structure::Command.completion_kind(),
+                    )),
+                    data: Some(serde_json::to_value(CompletionItemData::Command).unwrap()),
+                    text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                    ..CompletionItem::new_simple(name.into(), detail)
+                }
             }
         }
         InternalCompletionItemData::UserEnvironment { name } => {
             let detail = "user-defined".into();
-            let text_edit = TextEdit::new(range, name.clone());
+            let (text_edit, insert_text_format) = environment_edit(context, &name, item.range);
             CompletionItem {
                 kind: Some(adjust_kind(
                     &context.request,
@@ -549,6 +814,7 @@ fn convert_internal_items(
                 )),
                 data: Some(serde_json::to_value(CompletionItemData::Environment).unwrap()),
                 text_edit: Some(CompletionTextEdit::Edit(text_edit)),
+                insert_text_format,
                 ..CompletionItem::new_simple(name, detail)
             }
         }
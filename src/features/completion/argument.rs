@@ -1,7 +1,7 @@
 use lsp_types::CompletionParams;
 use rowan::{ast::AstNode, TextRange};
 
-use crate::{component_db::COMPONENT_DATABASE, features::cursor::CursorContext, syntax::latex};
+use crate::{features::cursor::CursorContext, syntax::latex};
 
 use super::types::{InternalCompletionItem, InternalCompletionItemData};
 
@@ -37,7 +37,12 @@ pub fn complete_arguments<'a>(
     let command_name = command.name()?;
     let command_name = &command_name.text()[1..];
 
-    for component in COMPONENT_DATABASE.linked_components(&context.request.workspace) {
+    let workspace = &context.request.workspace;
+    for component in workspace
+        .environment
+        .component_db
+        .linked_components(workspace)
+    {
         for component_command in component
             .commands
             .iter()
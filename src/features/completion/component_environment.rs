@@ -1,6 +1,6 @@
 use lsp_types::CompletionParams;
 
-use crate::{component_db::COMPONENT_DATABASE, features::cursor::CursorContext};
+use crate::features::cursor::CursorContext;
 
 use super::types::{InternalCompletionItem, InternalCompletionItemData};
 
@@ -10,7 +10,12 @@ pub fn complete_component_environments<'a>(
 ) -> Option<()> {
     let (_, range) = context.find_environment_name()?;
 
-    for component in COMPONENT_DATABASE.linked_components(&context.request.workspace) {
+    let workspace = &context.request.workspace;
+    for component in workspace
+        .environment
+        .component_db
+        .linked_components(workspace)
+    {
         for name in &component.environments {
             items.push(InternalCompletionItem::new(
                 range,
@@ -0,0 +1,60 @@
+use lsp_types::CompletionParams;
+use rustc_hash::FxHashSet;
+
+use crate::{features::cursor::CursorContext, LANGUAGE_DATA};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_component_options<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+) -> Option<()> {
+    let (_, range, file_names) = context.find_include_option()?;
+
+    let workspace = &context.request.workspace;
+    for file_name in &file_names {
+        // The bundled options come first because they are documented.
+        let bundled = LANGUAGE_DATA
+            .find_component_options(file_name)
+            .iter()
+            .map(|option| (option.name.as_str(), Some(option.documentation.as_str())));
+
+        let indexed = workspace
+            .environment
+            .component_db
+            .find(file_name)
+            .into_iter()
+            .flat_map(|component| &component.options)
+            .map(|option| (option.name.as_str(), option.documentation.as_deref()));
+
+        let local = workspace
+            .documents_by_uri
+            .values()
+            .filter(|document| {
+                document
+                    .uri
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    == Some(file_name.as_str())
+            })
+            .filter_map(|document| document.data.as_latex())
+            .flat_map(|data| &data.extras.option_names)
+            .map(|name| (name.as_str(), None));
+
+        let mut names = FxHashSet::default();
+        for (name, documentation) in bundled.chain(indexed).chain(local) {
+            if names.insert(name) {
+                items.push(InternalCompletionItem::new(
+                    range,
+                    InternalCompletionItemData::ComponentOption {
+                        name,
+                        file_name: file_name.into(),
+                        documentation,
+                    },
+                ));
+            }
+        }
+    }
+
+    Some(())
+}
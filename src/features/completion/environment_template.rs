@@ -0,0 +1,38 @@
+use lsp_types::CompletionParams;
+
+use crate::{features::cursor::CursorContext, LANGUAGE_DATA};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_environment_templates<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+) -> Option<()> {
+    let (_, range) = context.find_environment_name()?;
+
+    let environment = context
+        .request
+        .workspace
+        .environment
+        .scoped(&context.request.uri);
+
+    for template in &environment.options.completion.environment_templates {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::UserEnvironmentTemplate {
+                name: template.name.clone(),
+            },
+        ));
+    }
+
+    for template in &LANGUAGE_DATA.environment_templates {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::EnvironmentTemplate {
+                name: &template.name,
+            },
+        ));
+    }
+
+    Some(())
+}
@@ -3,7 +3,7 @@ use rowan::ast::AstNode;
 use rustc_hash::FxHashSet;
 use smol_str::SmolStr;
 
-use crate::{component_db::COMPONENT_DATABASE, features::cursor::CursorContext, syntax::latex};
+use crate::{features::cursor::CursorContext, syntax::latex};
 
 use super::types::{InternalCompletionItem, InternalCompletionItemData};
 
@@ -29,7 +29,11 @@ pub fn complete_imports<'a>(
     };
 
     let mut file_names = FxHashSet::default();
-    for file_name in COMPONENT_DATABASE
+    for file_name in context
+        .request
+        .workspace
+        .environment
+        .component_db
         .components
         .iter()
         .flat_map(|comp| comp.file_names.iter())
@@ -0,0 +1,82 @@
+use lsp_types::CompletionParams;
+use rowan::TextRange;
+
+use crate::{
+    features::cursor::CursorContext, key_value::KeyValueGroup, syntax::latex, KeyValueType,
+    LANGUAGE_DATA,
+};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_key_values<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+) -> Option<()> {
+    let data = context.request.main_document().data.as_latex()?;
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+    let group = KeyValueGroup::find(&root, context.offset)?;
+    let schema = group.schema;
+    let entries = group.entries();
+
+    let value = entries.iter().find_map(|entry| {
+        let (_, range) = entry.value.as_ref()?;
+        Some((entry, *range)).filter(|_| range.contains_inclusive(context.offset))
+    });
+
+    if let Some((entry, range)) = value {
+        let key = schema.find_key(&entry.key)?;
+        let values: Vec<&str> = match key.value_type {
+            KeyValueType::Bool => vec!["true", "false"],
+            KeyValueType::Choice => key.values.iter().map(String::as_str).collect(),
+            KeyValueType::Color => LANGUAGE_DATA.colors.iter().map(String::as_str).collect(),
+            KeyValueType::None
+            | KeyValueType::Number
+            | KeyValueType::Length
+            | KeyValueType::Text => return None,
+        };
+
+        for name in values {
+            items.push(InternalCompletionItem::new(
+                range,
+                InternalCompletionItemData::KeyValueValue { name },
+            ));
+        }
+
+        return Some(());
+    }
+
+    let current = entries
+        .iter()
+        .find(|entry| entry.key_range.contains_inclusive(context.offset));
+
+    let range = current.map_or_else(|| TextRange::empty(context.offset), |entry| entry.key_range);
+    if schema.is_compact {
+        // Flags like `[ht]` are extended by a single letter.
+        let prefix = current.map_or("", |entry| entry.key.as_str());
+        for key in schema.keys.iter().filter(|key| !prefix.contains(&key.name)) {
+            items.push(InternalCompletionItem::new(
+                range,
+                InternalCompletionItemData::KeyValueKey {
+                    name: format!("{}{}", prefix, key.name),
+                    key,
+                },
+            ));
+        }
+    } else {
+        for key in schema.keys.iter().filter(|key| {
+            !entries.iter().any(|entry| {
+                entry.key == key.name && Some(entry.key_range) != current.map(|e| e.key_range)
+            })
+        }) {
+            items.push(InternalCompletionItem::new(
+                range,
+                InternalCompletionItemData::KeyValueKey {
+                    name: key.name.clone(),
+                    key,
+                },
+            ));
+        }
+    }
+
+    Some(())
+}
@@ -0,0 +1,57 @@
+use lsp_types::CompletionParams;
+
+use crate::{
+    features::cursor::{is_math, CursorContext},
+    LANGUAGE_DATA,
+};
+
+use super::types::{InternalCompletionItem, InternalCompletionItemData};
+
+pub fn complete_math_symbols<'a>(
+    context: &'a CursorContext<CompletionParams>,
+    items: &mut Vec<InternalCompletionItem<'a>>,
+) -> Option<()> {
+    let range = context.cursor.command_range(context.offset)?;
+    if !is_math(context.cursor.as_latex()?) {
+        return None;
+    }
+
+    for symbol in &LANGUAGE_DATA.math_symbols {
+        items.push(InternalCompletionItem::new(
+            range,
+            InternalCompletionItemData::MathSymbol {
+                name: &symbol.command,
+                glyph: &symbol.glyph,
+                unicode_name: Some(&symbol.unicode_name),
+                image: None,
+                package: symbol.package.as_deref(),
+            },
+        ));
+    }
+
+    let workspace = &context.request.workspace;
+    for component in workspace
+        .environment
+        .component_db
+        .linked_components(workspace)
+    {
+        for command in &component.commands {
+            if let Some(glyph) = command.glyph.as_deref() {
+                items.push(InternalCompletionItem::new(
+                    range,
+                    InternalCompletionItemData::MathSymbol {
+                        name: &command.name,
+                        glyph,
+                        unicode_name: LANGUAGE_DATA
+                            .find_math_symbol_by_glyph(glyph)
+                            .map(|symbol| symbol.unicode_name.as_str()),
+                        image: command.image.as_deref(),
+                        package: component.file_names.first().map(|name| name.as_str()),
+                    },
+                ));
+            }
+        }
+    }
+
+    Some(())
+}
@@ -5,7 +5,10 @@ use rowan::TextRange;
 use serde::{Deserialize, Serialize};
 use smol_str::SmolStr;
 
-use crate::{features::lsp_kinds::Structure, BibtexEntryTypeDoc, BibtexFieldDoc};
+use crate::{
+    features::lsp_kinds::Structure, syntax::latex::CommandParameter, BibtexEntryTypeDoc,
+    BibtexFieldDoc, KeyValueKeyDoc,
+};
 
 #[derive(Debug, Clone)]
 pub struct InternalCompletionItem<'a> {
@@ -51,10 +54,35 @@ pub enum InternalCompletionItemData<'a> {
         glyph: Option<&'a str>,
         file_names: &'a [SmolStr],
     },
+    MathSymbol {
+        name: &'a str,
+        glyph: &'a str,
+        unicode_name: Option<&'a str>,
+        image: Option<&'a str>,
+        package: Option<&'a str>,
+    },
     ComponentEnvironment {
         name: &'a SmolStr,
         file_names: &'a [SmolStr],
     },
+    EnvironmentTemplate {
+        name: &'a str,
+    },
+    UserEnvironmentTemplate {
+        name: String,
+    },
+    ComponentOption {
+        name: &'a str,
+        file_name: SmolStr,
+        documentation: Option<&'a str>,
+    },
+    KeyValueKey {
+        name: String,
+        key: &'a KeyValueKeyDoc,
+    },
+    KeyValueValue {
+        name: &'a str,
+    },
     Class {
         name: SmolStr,
     },
@@ -88,6 +116,7 @@ pub enum InternalCompletionItemData<'a> {
     },
     UserCommand {
         name: SmolStr,
+        parameters: &'a [CommandParameter],
     },
     UserEnvironment {
         name: String,
@@ -109,7 +138,13 @@ impl<'a> InternalCompletionItemData<'a> {
             Self::BeginCommand => "begin",
             Self::Citation { key, .. } => key,
             Self::ComponentCommand { name, .. } => name,
+            Self::MathSymbol { name, .. } => name,
             Self::ComponentEnvironment { name, .. } => name,
+            Self::EnvironmentTemplate { name } => name,
+            Self::UserEnvironmentTemplate { name } => name,
+            Self::ComponentOption { name, .. } => name,
+            Self::KeyValueKey { name, .. } => name,
+            Self::KeyValueValue { name } => name,
             Self::Class { name } => name,
             Self::Package { name } => name,
             Self::Color { name } => name,
@@ -119,7 +154,7 @@ impl<'a> InternalCompletionItemData<'a> {
             Self::File { name } => name,
             Self::Directory { name } => name,
             Self::Label { name, .. } => name,
-            Self::UserCommand { name } => name,
+            Self::UserCommand { name, .. } => name,
             Self::UserEnvironment { name } => name,
             Self::PgfLibrary { name } => name,
             Self::TikzLibrary { name } => name,
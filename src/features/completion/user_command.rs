@@ -1,4 +1,5 @@
 use lsp_types::CompletionParams;
+use rustc_hash::FxHashMap;
 
 use crate::features::cursor::CursorContext;
 
@@ -11,6 +12,15 @@ pub fn complete_user_commands<'a>(
     let range = context.cursor.command_range(context.offset)?;
     let token = context.cursor.as_latex()?;
 
+    let definitions: FxHashMap<_, _> = context
+        .request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| document.data.as_latex())
+        .flat_map(|data| data.extras.command_definitions.iter())
+        .collect();
+
     for document in context.request.workspace.documents_by_uri.values() {
         if let Some(data) = document.data.as_latex() {
             for name in data
@@ -18,11 +28,14 @@ pub fn complete_user_commands<'a>(
                 .command_names
                 .iter()
                 .filter(|name| name.as_str() != token.text())
-                .cloned()
             {
+                let parameters = definitions.get(name).map_or(&[][..], |params| params);
                 items.push(InternalCompletionItem::new(
                     range,
-                    InternalCompletionItemData::UserCommand { name },
+                    InternalCompletionItemData::UserCommand {
+                        name: name.clone(),
+                        parameters,
+                    },
                 ));
             }
         }
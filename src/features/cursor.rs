@@ -1,12 +1,12 @@
 use lsp_types::{
-    CompletionParams, DocumentHighlightParams, GotoDefinitionParams, HoverParams, Position,
-    ReferenceParams, RenameParams, TextDocumentPositionParams,
+    CodeActionParams, CompletionParams, DocumentHighlightParams, GotoDefinitionParams, HoverParams,
+    Position, ReferenceParams, RenameParams, SignatureHelpParams, TextDocumentPositionParams,
 };
 use rowan::{ast::AstNode, TextRange, TextSize};
 
 use crate::{
     syntax::{bibtex, latex},
-    DocumentData, LineIndexExt,
+    DocumentData, LineIndexExt, LANGUAGE_DATA,
 };
 
 use super::FeatureRequest;
@@ -123,6 +123,24 @@ impl Cursor {
     }
 }
 
+/// Returns whether the token is placed inside of a formula or a math environment.
+pub fn is_math(token: &latex::SyntaxToken) -> bool {
+    token.parent_ancestors().any(|node| match node.kind() {
+        latex::FORMULA | latex::EQUATION => true,
+        latex::ENVIRONMENT => latex::Environment::cast(node)
+            .and_then(|env| env.begin())
+            .and_then(|begin| begin.name())
+            .and_then(|name| name.key())
+            .map_or(false, |name| {
+                LANGUAGE_DATA
+                    .math_environments
+                    .iter()
+                    .any(|env| env == &name.to_string())
+            }),
+        _ => false,
+    })
+}
+
 pub struct CursorContext<P> {
     pub request: FeatureRequest<P>,
     pub cursor: Cursor,
@@ -149,7 +167,7 @@ impl<P: HasPosition> CursorContext<P> {
                 let right = root.token_at_offset(offset).right_biased();
                 Cursor::new_bibtex(left, right)
             }
-            DocumentData::BuildLog(_) => None,
+            DocumentData::BuildLog(_) | DocumentData::BibLog(_) => None,
         };
 
         Self {
@@ -238,6 +256,62 @@ impl<P: HasPosition> CursorContext<P> {
         Some((name, range))
     }
 
+    /// Finds the key of a key-value pair like `\documentclass[a4paper]{article}` at the cursor.
+    /// The key is empty if the cursor is placed in front of a new pair.
+    pub fn find_key_value_key(&self) -> Option<(String, TextRange, latex::KeyValueBody)> {
+        let token = self.cursor.as_latex()?;
+        if let Some(key) = token.parent().and_then(latex::Key::cast) {
+            let pair = latex::KeyValuePair::cast(key.syntax().parent()?)?;
+            let body = latex::KeyValueBody::cast(pair.syntax().parent()?)?;
+            return Some((key.to_string(), latex::small_range(&key), body));
+        }
+
+        let root = token.parent_ancestors().last()?;
+        let mut previous = root.token_at_offset(self.offset).left_biased();
+
+        while let Some(current) = previous.as_ref().filter(|tok| {
+            matches!(
+                tok.kind(),
+                latex::WHITESPACE | latex::LINE_BREAK | latex::COMMENT
+            )
+        }) {
+            previous = current.prev_token();
+        }
+
+        let previous = previous?;
+        let body = match previous.kind() {
+            latex::COMMA => latex::KeyValueBody::cast(previous.parent()?)?,
+            latex::L_BRACK | latex::L_CURLY => previous
+                .parent()?
+                .children()
+                .find_map(latex::KeyValueBody::cast)?,
+            _ => return None,
+        };
+
+        Some((String::new(), TextRange::empty(self.offset), body))
+    }
+
+    /// Finds the option at the cursor inside of `\usepackage[...]{...}` or `\documentclass[...]{...}`
+    /// together with the file names of the included packages or classes.
+    pub fn find_include_option(&self) -> Option<(String, TextRange, Vec<String>)> {
+        let (name, range, body) = self.find_key_value_key()?;
+        let group = latex::BrackGroupKeyValue::cast(body.syntax().parent()?)?;
+        let include = latex::Include::cast(group.syntax().parent()?)?;
+        let extension = match include.syntax().kind() {
+            latex::PACKAGE_INCLUDE => "sty",
+            latex::CLASS_INCLUDE => "cls",
+            _ => return None,
+        };
+
+        let file_names = include
+            .path_list()?
+            .keys()
+            .map(|stem| format!("{}.{}", stem.to_string(), extension))
+            .collect();
+
+        Some((name, range, file_names))
+    }
+
     pub fn find_curly_group_word(&self) -> Option<(String, TextRange, latex::CurlyGroupWord)> {
         let token = self.cursor.as_latex()?;
         let key = latex::Key::cast(token.parent()?);
@@ -331,3 +405,15 @@ impl HasPosition for DocumentHighlightParams {
         self.text_document_position_params.position
     }
 }
+
+impl HasPosition for SignatureHelpParams {
+    fn position(&self) -> Position {
+        self.text_document_position_params.position
+    }
+}
+
+impl HasPosition for CodeActionParams {
+    fn position(&self) -> Position {
+        self.range.start
+    }
+}
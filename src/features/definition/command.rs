@@ -24,17 +24,13 @@ pub(super) fn goto_command_definition(
             if let Some(result) = root
                 .descendants()
                 .filter_map(latex::CommandDefinition::cast)
-                .filter(|def| {
-                    def.name()
-                        .and_then(|name| name.command())
-                        .map_or(false, |node| node.text() == name.text())
-                })
+                .filter(|def| def.name().map_or(false, |node| node.text() == name.text()))
                 .find_map(|def| {
                     Some(DefinitionResult {
                         origin_selection_range,
                         target_uri: Arc::clone(&document.uri),
                         target_range: latex::small_range(&def),
-                        target_selection_range: def.name()?.command()?.text_range(),
+                        target_selection_range: def.name()?.text_range(),
                     })
                 })
             {
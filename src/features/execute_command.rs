@@ -82,8 +82,9 @@ impl BuildContext {
         }
 
         let input_file = uri.to_file_path().ok()?;
-        let options = &workspace.environment.options;
-        let current_dir = &workspace.environment.current_directory;
+        let environment = workspace.environment.scoped(uri);
+        let options = &environment.options;
+        let current_dir = &environment.current_directory;
         let output_dir = match (
             options.root_directory.as_ref(),
             options.aux_directory.as_ref(),
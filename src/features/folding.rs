@@ -41,7 +41,7 @@ pub fn find_foldings(request: FeatureRequest<FoldingRangeParams>) -> Vec<Folding
                 }
             }
         }
-        DocumentData::BuildLog(_) => {}
+        DocumentData::BuildLog(_) | DocumentData::BibLog(_) => {}
     }
     foldings
 }
@@ -1,26 +1,89 @@
 mod bibtex_internal;
+mod latex_internal;
 mod latexindent;
 
-use lsp_types::{DocumentFormattingParams, TextEdit};
+use lsp_types::{
+    DocumentFormattingParams, DocumentOnTypeFormattingParams, DocumentRangeFormattingParams,
+    Position, Range, TextEdit,
+};
+use rowan::{TextRange, TextSize};
 
-use crate::{BibtexFormatter, LatexFormatter};
+use crate::{BibtexFormatter, Document, LatexFormatter, LineIndexExt};
 
-use self::{bibtex_internal::format_bibtex_internal, latexindent::format_with_latexindent};
+use self::{
+    bibtex_internal::format_bibtex_internal, latex_internal::format_latex_internal,
+    latexindent::format_with_latexindent,
+};
 
 use super::FeatureRequest;
 
 pub fn format_source_code(
     request: FeatureRequest<DocumentFormattingParams>,
 ) -> Option<Vec<TextEdit>> {
+    let options = &request.params.options;
     let mut edits = None;
     if request.workspace.environment.options.bibtex_formatter == BibtexFormatter::Texlab {
-        edits = edits.or_else(|| format_bibtex_internal(&request));
+        edits = edits.or_else(|| format_bibtex_internal(&request, options, None));
     }
 
     if request.workspace.environment.options.latex_formatter == LatexFormatter::Texlab {
-        edits = edits.or_else(|| Some(vec![]));
+        edits = edits.or_else(|| format_latex_internal(&request, options, None));
     }
 
-    edits = edits.or_else(|| format_with_latexindent(&request));
+    edits = edits.or_else(|| format_with_latexindent(&request, None));
     edits
 }
+
+pub fn format_source_code_range(
+    request: FeatureRequest<DocumentRangeFormattingParams>,
+) -> Option<Vec<TextEdit>> {
+    let document = request.main_document();
+    let range = document.line_index.offset_lsp_range(request.params.range);
+    let lines = expand_to_lines(document, request.params.range)?;
+
+    let options = &request.params.options;
+    let mut edits = None;
+    if request.workspace.environment.options.bibtex_formatter == BibtexFormatter::Texlab {
+        edits = edits.or_else(|| format_bibtex_internal(&request, options, Some(range)));
+    }
+
+    if request.workspace.environment.options.latex_formatter == LatexFormatter::Texlab {
+        edits = edits.or_else(|| format_latex_internal(&request, options, Some(lines)));
+    }
+
+    let Range { start, end } = request.params.range;
+    let end_line = if end.character == 0 && end.line > start.line {
+        end.line - 1
+    } else {
+        end.line
+    };
+
+    edits = edits.or_else(|| format_with_latexindent(&request, Some((start.line, end_line))));
+    edits
+}
+
+pub fn format_on_type(
+    request: FeatureRequest<DocumentOnTypeFormattingParams>,
+) -> Option<Vec<TextEdit>> {
+    if request.workspace.environment.options.latex_formatter != LatexFormatter::Texlab {
+        return None;
+    }
+
+    let document = request.main_document();
+    let position = request.params.text_document_position.position;
+    let lines = expand_to_lines(document, Range::new(position, position))?;
+    format_latex_internal(&request, &request.params.options, Some(lines))
+}
+
+fn expand_to_lines(document: &Document, range: Range) -> Option<TextRange> {
+    let start = document
+        .line_index
+        .offset_lsp(Position::new(range.start.line, 0));
+
+    let end = usize::from(document.line_index.offset_lsp(range.end));
+    let end = document.text[end..]
+        .find(['\r', '\n'])
+        .map_or(document.text.len(), |i| end + i);
+
+    Some(TextRange::new(start, TextSize::try_from(end).ok()?))
+}
@@ -1,5 +1,5 @@
-use lsp_types::{DocumentFormattingParams, TextEdit};
-use rowan::{ast::AstNode, NodeOrToken};
+use lsp_types::{FormattingOptions, TextEdit};
+use rowan::{ast::AstNode, NodeOrToken, TextRange};
 
 use crate::{
     features::FeatureRequest,
@@ -7,12 +7,14 @@ use crate::{
     LineIndex, LineIndexExt,
 };
 
-pub fn format_bibtex_internal(
-    request: &FeatureRequest<DocumentFormattingParams>,
+pub fn format_bibtex_internal<P>(
+    request: &FeatureRequest<P>,
+    options: &FormattingOptions,
+    range: Option<TextRange>,
 ) -> Option<Vec<TextEdit>> {
     let mut indent = String::new();
-    if request.params.options.insert_spaces {
-        for _ in 0..request.params.options.tab_size {
+    if options.insert_spaces {
+        for _ in 0..options.tab_size {
             indent.push(' ');
         }
     } else {
@@ -45,12 +47,13 @@ pub fn format_bibtex_internal(
                 bibtex::PREAMBLE | bibtex::STRING | bibtex::ENTRY
             )
         })
+        .filter(|node| range.map_or(true, |range| node.text_range().intersect(range).is_some()))
     {
         let range = node.text_range();
 
         let mut formatter = Formatter::new(
             indent.clone(),
-            request.params.options.tab_size,
+            options.tab_size,
             line_length,
             &document.line_index,
         );
@@ -0,0 +1,333 @@
+use lsp_types::{FormattingOptions, TextEdit};
+use rowan::{ast::AstNode, TextRange};
+
+use crate::{features::FeatureRequest, syntax::latex, LineIndexExt};
+
+pub fn format_latex_internal<P>(
+    request: &FeatureRequest<P>,
+    options: &FormattingOptions,
+    range: Option<TextRange>,
+) -> Option<Vec<TextEdit>> {
+    let mut indent = String::new();
+    if options.insert_spaces {
+        for _ in 0..options.tab_size {
+            indent.push(' ');
+        }
+    } else {
+        indent.push('\t');
+    }
+
+    let line_length = request
+        .workspace
+        .environment
+        .options
+        .formatter_line_length
+        .map(|value| {
+            if value <= 0 {
+                usize::MAX
+            } else {
+                value as usize
+            }
+        })
+        .unwrap_or(80);
+
+    let document = request.main_document();
+    let data = document.data.as_latex()?;
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+
+    let mut formatter = Formatter::new(indent, options.tab_size, line_length);
+    formatter.visit_root(&root);
+
+    let edits = formatter
+        .edits
+        .into_iter()
+        .filter(|(edit_range, _)| {
+            range.map_or(true, |range| range.contains_inclusive(edit_range.end()))
+        })
+        .map(|(range, new_text)| TextEdit {
+            range: document.line_index.line_col_lsp_range(range),
+            new_text,
+        })
+        .collect();
+
+    Some(edits)
+}
+
+struct Formatter {
+    indent: String,
+    tab_size: u32,
+    line_length: usize,
+    column: usize,
+    line_indent: usize,
+    line_has_verb: bool,
+    edits: Vec<(TextRange, String)>,
+}
+
+impl Formatter {
+    fn new(indent: String, tab_size: u32, line_length: usize) -> Self {
+        Self {
+            indent,
+            tab_size,
+            line_length,
+            column: 0,
+            line_indent: 0,
+            line_has_verb: false,
+            edits: Vec::new(),
+        }
+    }
+
+    fn visit_root(&mut self, root: &latex::SyntaxNode) {
+        let tokens: Vec<_> = root
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .filter(|token| !token.text().is_empty())
+            .collect();
+
+        for (i, token) in tokens.iter().enumerate() {
+            let next = tokens.get(i + 1);
+            match token.kind() {
+                latex::WHITESPACE | latex::LINE_BREAK if token.text().contains(['\r', '\n']) => {
+                    let mut new_text: String = token
+                        .text()
+                        .chars()
+                        .filter(|c| matches!(c, '\r' | '\n'))
+                        .collect();
+
+                    if let Some(next) = next {
+                        new_text.push_str(&self.indentation(next));
+                    }
+
+                    self.line_has_verb = false;
+                    self.replace(token, new_text);
+                }
+                latex::WHITESPACE => match next {
+                    None => self.replace(token, String::new()),
+                    Some(next) if i == 0 => {
+                        let indent = self.indentation(next);
+                        self.replace(token, indent);
+                    }
+                    Some(next) if next.kind() == latex::COMMENT || self.line_has_verb => {
+                        self.column += token.text().chars().count();
+                    }
+                    Some(next) => {
+                        let should_break = is_line_break_after(token, next)
+                            || is_line_break_before(next)
+                            || (self.column > self.line_indent
+                                && self.column + 1 + chunk_length(&tokens[i + 1..])
+                                    > self.line_length
+                                && can_wrap(token));
+
+                        if should_break {
+                            let indent = self.indentation(next);
+                            self.replace(token, format!("\n{}", indent));
+                        } else {
+                            self.column += token.text().chars().count();
+                        }
+                    }
+                },
+                _ => {
+                    match i.checked_sub(1).map(|j| &tokens[j]) {
+                        None => self.insert_line_start(token, ""),
+                        Some(prev)
+                            if matches!(prev.kind(), latex::WHITESPACE | latex::LINE_BREAK) => {}
+                        // The content of verbatim environments must stay untouched.
+                        Some(prev) if prev.kind() == latex::VERBATIM => {
+                            if prev.text().ends_with('\n') && is_line_break_before(token) {
+                                self.insert_line_start(token, "");
+                            }
+                        }
+                        Some(prev) => {
+                            if is_line_break_before(token) || is_line_break_between(prev, token) {
+                                self.insert_line_start(token, "\n");
+                            }
+                        }
+                    };
+
+                    if matches!(token.text(), "\\verb" | "\\verb*") {
+                        self.line_has_verb = true;
+                    }
+
+                    let text = token.text();
+                    match text.rfind('\n') {
+                        Some(index) => {
+                            self.column = text[index + 1..].chars().count();
+                            self.line_indent = 0;
+                        }
+                        None => self.column += text.chars().count(),
+                    };
+                }
+            };
+        }
+    }
+
+    /// Starts a new line in front of the token without replacing any whitespace.
+    fn insert_line_start(&mut self, token: &latex::SyntaxToken, line_break: &str) {
+        let indent = self.indentation(token);
+        if line_break.is_empty() && indent.is_empty() {
+            return;
+        }
+
+        self.column = self.width(&indent);
+        self.line_indent = self.column;
+        let start = token.text_range().start();
+        self.edits
+            .push((TextRange::empty(start), format!("{}{}", line_break, indent)));
+    }
+
+    fn replace(&mut self, token: &latex::SyntaxToken, new_text: String) {
+        match new_text.rfind('\n') {
+            Some(index) => {
+                self.column = self.width(&new_text[index + 1..]);
+                self.line_indent = self.column;
+            }
+            None if self.column == 0 => {
+                self.column = self.width(&new_text);
+                self.line_indent = self.column;
+            }
+            None => self.column += self.width(&new_text),
+        };
+
+        if token.text() != new_text {
+            self.edits.push((token.text_range(), new_text));
+        }
+    }
+
+    fn width(&self, text: &str) -> usize {
+        text.chars()
+            .map(|c| if c == '\t' { self.tab_size as usize } else { 1 })
+            .sum()
+    }
+
+    fn indentation(&self, token: &latex::SyntaxToken) -> String {
+        self.indent.repeat(indentation_level(token))
+    }
+}
+
+fn indentation_level(token: &latex::SyntaxToken) -> usize {
+    let mut level = 0;
+    let mut child_kind = None;
+    for node in token.parent_ancestors() {
+        match node.kind() {
+            latex::ENVIRONMENT
+                if child_kind != Some(latex::END)
+                    && (child_kind != Some(latex::BEGIN)
+                        || token.kind() == latex::COMMENT
+                            && is_trailing_trivia(token, &node.first_child().unwrap())) =>
+            {
+                let is_document = latex::Environment::cast(node.clone())
+                    .and_then(|environment| environment.begin())
+                    .and_then(|begin| begin.name())
+                    .and_then(|name| name.key())
+                    .map_or(false, |name| name.to_string() == "document");
+
+                if !is_document {
+                    level += 1;
+                }
+            }
+            latex::EQUATION
+                if child_kind.is_some()
+                    || !matches!(
+                        token.kind(),
+                        latex::BEGIN_EQUATION_NAME | latex::END_EQUATION_NAME
+                    ) =>
+            {
+                level += 1;
+            }
+            _ => {}
+        };
+
+        child_kind = Some(node.kind());
+    }
+
+    level
+}
+
+fn is_inside_formula(token: &latex::SyntaxToken) -> bool {
+    token
+        .parent_ancestors()
+        .any(|node| node.kind() == latex::FORMULA)
+}
+
+fn is_line_break_before(token: &latex::SyntaxToken) -> bool {
+    matches!(
+        token.kind(),
+        latex::BEGIN_ENVIRONMENT_NAME | latex::END_ENVIRONMENT_NAME
+    ) && !is_inside_formula(token)
+}
+
+fn is_line_break_after(whitespace: &latex::SyntaxToken, next: &latex::SyntaxToken) -> bool {
+    find_begin_or_end(whitespace).map_or(false, |node| {
+        is_trailing_trivia(whitespace, &node)
+            && !is_inside_formula(whitespace)
+            && !matches!(
+                next.kind(),
+                latex::L_CURLY | latex::L_BRACK | latex::COMMENT
+            )
+    })
+}
+
+/// Checks if a token ends the `\begin{...}` or `\end{...}` part of an environment
+/// and the next token follows without any whitespace like in `\begin{center}foo`.
+fn is_line_break_between(prev: &latex::SyntaxToken, next: &latex::SyntaxToken) -> bool {
+    find_begin_or_end(prev).map_or(false, |node| {
+        node.text_range().end() == prev.text_range().end()
+            && !is_inside_formula(prev)
+            && !matches!(
+                next.kind(),
+                latex::L_CURLY | latex::L_BRACK | latex::COMMENT | latex::VERBATIM
+            )
+    })
+}
+
+/// Finds the `\begin{...}` or `\end{...}` part of an environment that contains the token.
+/// Groups like `{cc}` in `\begin{tabular}{cc}` count as part of `\begin{...}`.
+fn find_begin_or_end(token: &latex::SyntaxToken) -> Option<latex::SyntaxNode> {
+    let is_group = |node: &latex::SyntaxNode| {
+        node.first_token().map_or(false, |token| {
+            matches!(token.kind(), latex::L_CURLY | latex::L_BRACK)
+        })
+    };
+
+    token.parent_ancestors().find(|node| match node.kind() {
+        latex::BEGIN | latex::END => true,
+        _ => node
+            .parent()
+            .filter(|parent| parent.kind() == latex::ENVIRONMENT && is_group(node))
+            .and_then(|parent| {
+                parent
+                    .children()
+                    .take_while(|child| child != node)
+                    .filter(|child| !is_group(child))
+                    .last()
+            })
+            .map_or(false, |sibling| sibling.kind() == latex::BEGIN),
+    })
+}
+
+fn is_trailing_trivia(token: &latex::SyntaxToken, node: &latex::SyntaxNode) -> bool {
+    let end = node.text_range().end();
+    std::iter::successors(Some(token.clone()), |token| token.next_token())
+        .take_while(|token| token.text_range().end() <= end)
+        .all(|token| {
+            matches!(
+                token.kind(),
+                latex::WHITESPACE | latex::LINE_BREAK | latex::COMMENT
+            )
+        })
+}
+
+fn can_wrap(token: &latex::SyntaxToken) -> bool {
+    !token
+        .parent_ancestors()
+        .filter_map(latex::GenericCommand::cast)
+        .filter_map(|command| command.name())
+        .any(|name| matches!(name.text(), "\\url" | "\\path" | "\\href"))
+}
+
+fn chunk_length(tokens: &[latex::SyntaxToken]) -> usize {
+    tokens
+        .iter()
+        .take_while(|token| !matches!(token.kind(), latex::WHITESPACE | latex::LINE_BREAK))
+        .map(|token| token.text().chars().count())
+        .sum()
+}
@@ -3,23 +3,27 @@ use std::{
     process::{Command, Stdio},
 };
 
-use lsp_types::{DocumentFormattingParams, TextEdit};
+use lsp_types::TextEdit;
 use rowan::{TextLen, TextRange};
 use tempfile::tempdir;
 
 use crate::{features::FeatureRequest, DocumentLanguage, LineIndexExt};
 
-pub fn format_with_latexindent(
-    request: &FeatureRequest<DocumentFormattingParams>,
+/// Formats the document with `latexindent`.
+/// If a range of (zero-based) lines is given, only these lines are formatted using the `--lines` option.
+pub fn format_with_latexindent<P>(
+    request: &FeatureRequest<P>,
+    lines: Option<(u32, u32)>,
 ) -> Option<Vec<TextEdit>> {
     let directory = tempdir().ok()?;
     let document = request.main_document();
 
-    let options = &request.workspace.environment.options;
+    let environment = &request.workspace.environment;
+    let options = &environment.options;
     let current_dir = options
         .root_directory
         .as_ref()
-        .cloned()
+        .map(|root_dir| environment.current_directory.join(root_dir))
         .or_else(|| {
             if document.uri.scheme() == "file" {
                 document
@@ -68,6 +72,11 @@ pub fn format_with_latexindent(
         args.push("--modifylinebreaks");
     }
     args.push(&local);
+    let lines = lines.map(|(start, end)| format!("--lines={}-{}", start + 1, end + 1));
+    if let Some(lines) = &lines {
+        args.push(lines);
+    }
+
     args.push(name);
 
     let output = Command::new("latexindent")
@@ -1,14 +1,16 @@
 use std::{
     io,
-    path::Path,
+    path::{Path, PathBuf},
     process::{Command, Stdio},
 };
 
-use log::error;
-use lsp_types::TextDocumentPositionParams;
+use log::{error, warn};
+use lsp_types::{TextDocumentPositionParams, Url};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
+use crate::{synctex::SyncTexFile, Workspace};
+
 use super::FeatureRequest;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize_repr, Deserialize_repr)]
@@ -25,6 +27,52 @@ pub struct ForwardSearchResult {
     pub status: ForwardSearchStatus,
 }
 
+/// A rectangle inside of a PDF page in PDF points measured from the top left corner.
+#[derive(Debug, PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub struct PdfRect {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct PdfLocation {
+    pub pdf: Url,
+    pub page: u32,
+    pub rect: PdfRect,
+}
+
+pub fn find_pdf_location(
+    request: FeatureRequest<TextDocumentPositionParams>,
+) -> Option<PdfLocation> {
+    let pdf_path = find_pdf_path(&request.workspace, &request.uri)?;
+    let tex_path = request.main_document().uri.to_file_path().ok()?;
+    let synctex = match SyncTexFile::read(&pdf_path) {
+        Ok(synctex) => synctex,
+        Err(why) => {
+            warn!(
+                "Unable to read SyncTeX file of {}: {}",
+                pdf_path.display(),
+                why
+            );
+            return None;
+        }
+    };
+
+    let location = synctex.forward_search(&tex_path, request.params.position.line)?;
+    Some(PdfLocation {
+        pdf: Url::from_file_path(&pdf_path).ok()?,
+        page: location.page,
+        rect: PdfRect {
+            left: location.rect.left,
+            top: location.rect.top,
+            width: location.rect.width,
+            height: location.rect.height,
+        },
+    })
+}
+
 pub fn execute_forward_search(
     request: FeatureRequest<TextDocumentPositionParams>,
 ) -> Option<ForwardSearchResult> {
@@ -36,34 +84,7 @@ pub fn execute_forward_search(
         });
     }
 
-    let root_document = request
-        .workspace
-        .documents_by_uri
-        .values()
-        .find(|document| {
-            if let Some(data) = document.data.as_latex() {
-                data.extras.has_document_environment
-                    && !data
-                        .extras
-                        .explicit_links
-                        .iter()
-                        .filter_map(|link| link.as_component_name())
-                        .any(|name| name == "subfiles.cls")
-            } else {
-                false
-            }
-        })
-        .filter(|document| document.uri.scheme() == "file")?;
-
-    let data = root_document.data.as_latex()?;
-    let pdf_path = data
-        .extras
-        .implicit_links
-        .pdf
-        .iter()
-        .filter_map(|uri| uri.to_file_path().ok())
-        .find(|path| path.exists())?;
-
+    let pdf_path = find_pdf_path(&request.workspace, &request.uri)?;
     let tex_path = request.main_document().uri.to_file_path().ok()?;
 
     let args: Vec<String> = options
@@ -86,6 +107,20 @@ pub fn execute_forward_search(
     Some(ForwardSearchResult { status })
 }
 
+fn find_pdf_path(workspace: &Workspace, uri: &Url) -> Option<PathBuf> {
+    let root_document = workspace
+        .find_parent(uri)
+        .filter(|document| document.uri.scheme() == "file")?;
+
+    let data = root_document.data.as_latex()?;
+    data.extras
+        .implicit_links
+        .pdf
+        .iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .find(|path| path.exists())
+}
+
 /// Iterate overs chunks of a string. Either returns a slice of the
 /// original string, or the placeholder replacement.
 pub struct PlaceHolderIterator<'a> {
@@ -1,7 +1,10 @@
 mod citation;
+mod command;
 mod component;
+mod component_option;
 mod entry_type;
 mod field;
+mod key_value;
 mod label;
 mod string_ref;
 
@@ -14,8 +17,10 @@ use crate::{
 };
 
 use self::{
-    component::find_component_hover, entry_type::find_entry_type_hover, field::find_field_hover,
-    label::find_label_hover, string_ref::find_string_reference_hover,
+    command::find_command_hover, component::find_component_hover,
+    component_option::find_component_option_hover, entry_type::find_entry_type_hover,
+    field::find_field_hover, key_value::find_key_value_hover, label::find_label_hover,
+    string_ref::find_string_reference_hover,
 };
 
 use super::FeatureRequest;
@@ -26,6 +31,9 @@ pub fn find_hover(request: FeatureRequest<HoverParams>) -> Option<Hover> {
     let result = find_label_hover(&context)
         .or_else(|| find_citation_hover(&context))
         .or_else(|| find_component_hover(&context))
+        .or_else(|| find_component_option_hover(&context))
+        .or_else(|| find_key_value_hover(&context))
+        .or_else(|| find_command_hover(&context))
         .or_else(|| find_string_reference_hover(&context))
         .or_else(|| find_field_hover(&context))
         .or_else(|| find_entry_type_hover(&context))?;
@@ -0,0 +1,33 @@
+use lsp_types::{HoverParams, MarkupKind};
+use rowan::ast::AstNode;
+
+use crate::{features::cursor::CursorContext, syntax::latex};
+
+use super::HoverResult;
+
+pub(super) fn find_command_hover(context: &CursorContext<HoverParams>) -> Option<HoverResult> {
+    let name = context
+        .cursor
+        .as_latex()
+        .filter(|token| token.kind() == latex::GENERIC_COMMAND_NAME)?;
+
+    context
+        .request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| Some((document, document.data.as_latex()?)))
+        .find_map(|(document, data)| {
+            let definition = latex::SyntaxNode::new_root(data.green.clone())
+                .descendants()
+                .filter_map(latex::CommandDefinition::cast)
+                .find(|def| def.name().map_or(false, |def| def.text() == name.text()))?;
+
+            let text = &document.text[latex::small_range(&definition)];
+            Some(HoverResult {
+                range: name.text_range(),
+                value: format!("```latex\n{}\n```", text),
+                value_kind: MarkupKind::Markdown,
+            })
+        })
+}
@@ -1,6 +1,6 @@
 use lsp_types::{HoverParams, MarkupKind};
 
-use crate::{component_db::COMPONENT_DATABASE, features::cursor::CursorContext, syntax::latex};
+use crate::{features::cursor::CursorContext, syntax::latex};
 
 use super::HoverResult;
 
@@ -12,7 +12,13 @@ pub(super) fn find_component_hover(context: &CursorContext<HoverParams>) -> Opti
             latex::ExplicitLinkKind::Package | latex::ExplicitLinkKind::Class
         ) && link.stem_range.contains_inclusive(context.offset)
         {
-            let value = COMPONENT_DATABASE.documentation(&link.stem)?.value;
+            let value = context
+                .request
+                .workspace
+                .environment
+                .component_db
+                .documentation(&link.stem)?
+                .value;
             return Some(HoverResult {
                 value,
                 value_kind: MarkupKind::PlainText,
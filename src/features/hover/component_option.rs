@@ -0,0 +1,60 @@
+use lsp_types::{HoverParams, MarkupKind};
+
+use crate::{features::cursor::CursorContext, LANGUAGE_DATA};
+
+use super::HoverResult;
+
+pub(super) fn find_component_option_hover(
+    context: &CursorContext<HoverParams>,
+) -> Option<HoverResult> {
+    let (name, range, file_names) = context.find_include_option()?;
+    if name.is_empty() {
+        return None;
+    }
+
+    let workspace = &context.request.workspace;
+    let (file_name, documentation) = file_names.iter().find_map(|file_name| {
+        if let Some(option) = LANGUAGE_DATA
+            .find_component_options(file_name)
+            .iter()
+            .find(|option| option.name == name)
+        {
+            return Some((file_name, Some(option.documentation.as_str())));
+        }
+
+        let option = workspace
+            .environment
+            .component_db
+            .find(file_name)
+            .and_then(|component| component.options.iter().find(|option| option.name == name));
+
+        if let Some(option) = option {
+            return Some((file_name, option.documentation.as_deref()));
+        }
+
+        workspace
+            .documents_by_uri
+            .values()
+            .filter(|document| {
+                document
+                    .uri
+                    .path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    == Some(file_name.as_str())
+            })
+            .filter_map(|document| document.data.as_latex())
+            .any(|data| data.extras.option_names.contains(name.as_str()))
+            .then(|| (file_name, None))
+    })?;
+
+    let value = match documentation {
+        Some(documentation) => format!("`{}` (`{}`)\n\n{}", name, file_name, documentation),
+        None => format!("`{}` (`{}`)", name, file_name),
+    };
+
+    Some(HoverResult {
+        range,
+        value,
+        value_kind: MarkupKind::Markdown,
+    })
+}
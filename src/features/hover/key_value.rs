@@ -0,0 +1,57 @@
+use lsp_types::{HoverParams, MarkupKind};
+
+use crate::{
+    features::cursor::CursorContext, key_value::KeyValueGroup, syntax::latex, KeyValueKeyDoc,
+    KeyValueType,
+};
+
+use super::HoverResult;
+
+pub(super) fn find_key_value_hover(context: &CursorContext<HoverParams>) -> Option<HoverResult> {
+    let data = context.request.main_document().data.as_latex()?;
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+    let group = KeyValueGroup::find(&root, context.offset)?;
+    let entry = group
+        .entries()
+        .into_iter()
+        .find(|entry| entry.key_range.contains_inclusive(context.offset))?;
+
+    let value = if group.schema.is_compact {
+        let lines: Vec<_> = entry
+            .key
+            .chars()
+            .filter_map(|c| group.schema.find_key(&c.to_string()))
+            .map(render_key)
+            .collect();
+
+        if lines.is_empty() {
+            return None;
+        }
+
+        lines.join("\n\n")
+    } else {
+        render_key(group.schema.find_key(&entry.key)?)
+    };
+
+    Some(HoverResult {
+        range: entry.key_range,
+        value,
+        value_kind: MarkupKind::Markdown,
+    })
+}
+
+fn render_key(key: &KeyValueKeyDoc) -> String {
+    let mut value = format!("`{}` ({})", key.name, key.value_type.as_str());
+    if let Some(documentation) = &key.documentation {
+        value.push_str("\n\n");
+        value.push_str(documentation);
+    }
+
+    if key.value_type == KeyValueType::Choice {
+        let values: Vec<_> = key.values.iter().map(|v| format!("`{}`", v)).collect();
+        value.push_str("\n\nValues: ");
+        value.push_str(&values.join(", "));
+    }
+
+    value
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use lsp_types::{
+    CallHierarchyIncomingCall, CallHierarchyIncomingCallsParams, CallHierarchyItem,
+    CallHierarchyOutgoingCall, CallHierarchyOutgoingCallsParams, CallHierarchyPrepareParams, Range,
+    SymbolKind, TextDocumentIdentifier, Url,
+};
+use rowan::{TextRange, TextSize};
+use rustc_hash::FxHashSet;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    syntax::latex::{ExplicitLink, ExplicitLinkKind},
+    Document, LineIndexExt, Workspace,
+};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeGraphParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IncludeTree {
+    pub uri: Url,
+    pub includes: Vec<Include>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Include {
+    pub path: String,
+    pub kind: IncludeKind,
+    pub range: Range,
+    pub target: Option<IncludeTree>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IncludeKind {
+    Latex,
+    Bibtex,
+}
+
+pub fn find_project_root(request: FeatureRequest<IncludeGraphParams>) -> Option<Url> {
+    let root = request
+        .workspace
+        .find_parent(&request.uri)
+        .unwrap_or_else(|| request.main_document().clone());
+
+    Some(root.uri.as_ref().clone())
+}
+
+pub fn find_include_tree(request: FeatureRequest<IncludeGraphParams>) -> Option<IncludeTree> {
+    let root = request
+        .workspace
+        .find_parent(&request.uri)
+        .unwrap_or_else(|| request.main_document().clone());
+
+    let mut visited = FxHashSet::default();
+    Some(build_include_tree(&request.workspace, &root, &mut visited))
+}
+
+fn build_include_tree(
+    workspace: &Workspace,
+    document: &Document,
+    visited: &mut FxHashSet<Arc<Url>>,
+) -> IncludeTree {
+    visited.insert(Arc::clone(&document.uri));
+    let mut includes = Vec::new();
+    for link in document_links(document) {
+        let kind = match link.kind {
+            ExplicitLinkKind::Latex => IncludeKind::Latex,
+            ExplicitLinkKind::Bibtex => IncludeKind::Bibtex,
+            ExplicitLinkKind::Package | ExplicitLinkKind::Class => continue,
+        };
+
+        let target = resolve_link(workspace, link).map(|child| {
+            if visited.contains(&child.uri) {
+                IncludeTree {
+                    uri: child.uri.as_ref().clone(),
+                    includes: Vec::new(),
+                }
+            } else {
+                build_include_tree(workspace, child, visited)
+            }
+        });
+
+        includes.push(Include {
+            path: link.stem.to_string(),
+            kind,
+            range: document.line_index.line_col_lsp_range(link.stem_range),
+            target,
+        });
+    }
+
+    IncludeTree {
+        uri: document.uri.as_ref().clone(),
+        includes,
+    }
+}
+
+pub fn prepare_call_hierarchy(
+    request: FeatureRequest<CallHierarchyPrepareParams>,
+) -> Option<Vec<CallHierarchyItem>> {
+    let main_document = request.main_document();
+    let offset = main_document
+        .line_index
+        .offset_lsp(request.params.text_document_position_params.position);
+
+    let document = document_links(main_document)
+        .filter(|link| link.stem_range.contains_inclusive(offset))
+        .find_map(|link| resolve_link(&request.workspace, link))
+        .unwrap_or(main_document);
+
+    Some(vec![create_item(document)])
+}
+
+pub fn find_incoming_calls(
+    request: FeatureRequest<CallHierarchyIncomingCallsParams>,
+) -> Option<Vec<CallHierarchyIncomingCall>> {
+    let calls = request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| {
+            let from_ranges: Vec<_> = document_links(document)
+                .filter(|link| {
+                    resolve_link(&request.workspace, link)
+                        .map_or(false, |target| target.uri == request.uri)
+                })
+                .map(|link| document.line_index.line_col_lsp_range(link.stem_range))
+                .collect();
+
+            if from_ranges.is_empty() {
+                None
+            } else {
+                Some(CallHierarchyIncomingCall {
+                    from: create_item(document),
+                    from_ranges,
+                })
+            }
+        })
+        .collect();
+
+    Some(calls)
+}
+
+pub fn find_outgoing_calls(
+    request: FeatureRequest<CallHierarchyOutgoingCallsParams>,
+) -> Option<Vec<CallHierarchyOutgoingCall>> {
+    let main_document = request.main_document();
+    let mut calls: Vec<CallHierarchyOutgoingCall> = Vec::new();
+    for link in document_links(main_document) {
+        let target = match resolve_link(&request.workspace, link) {
+            Some(target) => target,
+            None => continue,
+        };
+
+        let range = main_document.line_index.line_col_lsp_range(link.stem_range);
+        match calls
+            .iter_mut()
+            .find(|call| call.to.uri == *target.uri.as_ref())
+        {
+            Some(call) => call.from_ranges.push(range),
+            None => calls.push(CallHierarchyOutgoingCall {
+                to: create_item(target),
+                from_ranges: vec![range],
+            }),
+        };
+    }
+
+    Some(calls)
+}
+
+fn document_links(document: &Document) -> impl Iterator<Item = &ExplicitLink> {
+    document
+        .data
+        .as_latex()
+        .into_iter()
+        .flat_map(|data| data.extras.explicit_links.iter())
+        .filter(|link| {
+            matches!(
+                link.kind,
+                ExplicitLinkKind::Latex | ExplicitLinkKind::Bibtex
+            )
+        })
+}
+
+/// Returns the first target of the link that is part of the workspace.
+fn resolve_link<'a>(workspace: &'a Workspace, link: &ExplicitLink) -> Option<&'a Document> {
+    link.targets
+        .iter()
+        .find_map(|target| workspace.documents_by_uri.get(target))
+}
+
+fn create_item(document: &Document) -> CallHierarchyItem {
+    let name = document
+        .uri
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .unwrap_or_default()
+        .to_string();
+
+    let range = TextRange::up_to(TextSize::of(document.text.as_str()));
+    CallHierarchyItem {
+        name,
+        kind: SymbolKind::FILE,
+        tags: None,
+        detail: None,
+        uri: document.uri.as_ref().clone(),
+        range: document.line_index.line_col_lsp_range(range),
+        selection_range: document
+            .line_index
+            .line_col_lsp_range(TextRange::empty(0.into())),
+        data: None,
+    }
+}
@@ -0,0 +1,79 @@
+use lsp_types::{InlayHint, InlayHintLabel, InlayHintParams};
+use rowan::ast::AstNode;
+
+use crate::{
+    citation, find_label_number, render_label,
+    syntax::{bibtex, latex},
+    LineIndexExt, Workspace,
+};
+
+use super::FeatureRequest;
+
+pub fn find_inlay_hints(request: FeatureRequest<InlayHintParams>) -> Option<Vec<InlayHint>> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+    let range = main_document
+        .line_index
+        .offset_lsp_range(request.params.range);
+
+    let options = &request.workspace.environment.options.inlay_hints;
+    let mut hints = Vec::new();
+    for node in root
+        .descendants()
+        .filter(|node| node.text_range().intersect(range).is_some())
+    {
+        if options.label_references {
+            if let Some(reference) = latex::LabelReference::cast(node.clone()) {
+                for key in reference
+                    .name_list()
+                    .into_iter()
+                    .flat_map(|list| list.keys())
+                {
+                    let text = find_label_text(&request.workspace, &key.to_string());
+                    hints.extend(text.map(|text| (latex::small_range(&key).end(), text)));
+                }
+            }
+        }
+
+        if options.citations {
+            if let Some(citation) = latex::Citation::cast(node) {
+                for key in citation.key_list().into_iter().flat_map(|list| list.keys()) {
+                    let text = find_citation_text(&request.workspace, &key.to_string());
+                    hints.extend(text.map(|text| (latex::small_range(&key).end(), text)));
+                }
+            }
+        }
+    }
+
+    let hints = hints
+        .into_iter()
+        .filter(|(offset, _)| range.contains_inclusive(*offset))
+        .map(|(offset, text)| InlayHint {
+            position: main_document.line_index.line_col_lsp(offset),
+            label: InlayHintLabel::String(text),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        })
+        .collect();
+
+    Some(hints)
+}
+
+fn find_label_text(workspace: &Workspace, name: &str) -> Option<String> {
+    render_label(workspace, name, None)
+        .map(|label| label.reference())
+        .or_else(|| find_label_number(workspace, name).map(ToString::to_string))
+}
+
+fn find_citation_text(workspace: &Workspace, key: &str) -> Option<String> {
+    workspace.documents_by_uri.values().find_map(|document| {
+        let data = document.data.as_bibtex()?;
+        let root = bibtex::Root::cast(bibtex::SyntaxNode::new_root(data.green.clone()))?;
+        citation::render_short(&root.find_entry(key)?)
+    })
+}
@@ -0,0 +1,64 @@
+use std::path::Path;
+
+use log::warn;
+use lsp_types::{Location, Position, Range, Url};
+use serde::{Deserialize, Serialize};
+
+use crate::synctex::SyncTexFile;
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InverseSearchParams {
+    pub pdf: Url,
+    pub page: u32,
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A source location which should be revealed by the client.
+/// PDF viewers send this to the running server through `texlab inverse-search`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InverseSearchTarget {
+    pub uri: Url,
+    pub line: u32,
+}
+
+impl InverseSearchTarget {
+    pub fn location(&self) -> Location {
+        let position = Position::new(self.line, 0);
+        Location::new(self.uri.clone(), Range::new(position, position))
+    }
+}
+
+pub fn execute_inverse_search(params: &InverseSearchParams) -> Option<Location> {
+    let pdf_path = params.pdf.to_file_path().ok()?;
+    find_inverse_search_target(&pdf_path, params.page, params.x, params.y)
+        .map(|target| target.location())
+}
+
+pub fn find_inverse_search_target(
+    pdf_path: &Path,
+    page: u32,
+    x: f64,
+    y: f64,
+) -> Option<InverseSearchTarget> {
+    let synctex = match SyncTexFile::read(pdf_path) {
+        Ok(synctex) => synctex,
+        Err(why) => {
+            warn!(
+                "Unable to read SyncTeX file of {}: {}",
+                pdf_path.display(),
+                why
+            );
+            return None;
+        }
+    };
+
+    let location = synctex.inverse_search(page, x, y)?;
+    let path = location.path.canonicalize().ok()?;
+    Some(InverseSearchTarget {
+        uri: Url::from_file_path(path).ok()?,
+        line: location.line,
+    })
+}
@@ -50,7 +50,7 @@ pub(super) fn find_entry_references(
                         results.push(ReferenceResult { uri, range });
                     });
             }
-            DocumentData::Bibtex(_) | DocumentData::BuildLog(_) => {}
+            DocumentData::Bibtex(_) | DocumentData::BuildLog(_) | DocumentData::BibLog(_) => {}
         }
     }
 
@@ -64,7 +64,7 @@ pub(super) fn rename_entry(context: &CursorContext<RenameParams>) -> Option<Rena
                     .collect();
                 changes.insert(uri, edits);
             }
-            DocumentData::BuildLog(_) => {}
+            DocumentData::BuildLog(_) | DocumentData::BibLog(_) => {}
         }
     }
 
@@ -0,0 +1,172 @@
+mod bibtex;
+mod latex;
+
+use lsp_types::{
+    Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensLegend, SemanticTokensParams, SemanticTokensRangeParams,
+    SemanticTokensRangeResult, SemanticTokensResult,
+};
+use rowan::TextRange;
+
+use crate::{Document, LineIndexExt};
+
+use self::{bibtex::find_bibtex_tokens, latex::find_latex_tokens};
+
+use super::FeatureRequest;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+enum TokenKind {
+    Command,
+    UserCommand,
+    Keyword,
+    Environment,
+    Label,
+    Citation,
+    Math,
+    MathDelimiter,
+    Comment,
+    Verbatim,
+    EntryType,
+    EntryKey,
+    FieldName,
+    StringName,
+    Text,
+    Number,
+}
+
+const TOKEN_KINDS: [(TokenKind, SemanticTokenType); 16] = [
+    (TokenKind::Command, SemanticTokenType::MACRO),
+    (TokenKind::UserCommand, SemanticTokenType::FUNCTION),
+    (TokenKind::Keyword, SemanticTokenType::KEYWORD),
+    (TokenKind::Environment, SemanticTokenType::CLASS),
+    (TokenKind::Label, SemanticTokenType::VARIABLE),
+    (TokenKind::Citation, SemanticTokenType::PARAMETER),
+    (TokenKind::Math, SemanticTokenType::NUMBER),
+    (TokenKind::MathDelimiter, SemanticTokenType::OPERATOR),
+    (TokenKind::Comment, SemanticTokenType::COMMENT),
+    (TokenKind::Verbatim, SemanticTokenType::STRING),
+    (TokenKind::EntryType, SemanticTokenType::KEYWORD),
+    (TokenKind::EntryKey, SemanticTokenType::PARAMETER),
+    (TokenKind::FieldName, SemanticTokenType::PROPERTY),
+    (TokenKind::StringName, SemanticTokenType::VARIABLE),
+    (TokenKind::Text, SemanticTokenType::STRING),
+    (TokenKind::Number, SemanticTokenType::NUMBER),
+];
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+#[repr(u32)]
+enum TokenModifier {
+    Definition,
+}
+
+const TOKEN_MODIFIERS: [(TokenModifier, SemanticTokenModifier); 1] =
+    [(TokenModifier::Definition, SemanticTokenModifier::DEFINITION)];
+
+#[must_use]
+pub fn semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_KINDS.iter().map(|(_, ty)| ty.clone()).collect(),
+        token_modifiers: TOKEN_MODIFIERS
+            .iter()
+            .map(|(_, modifier)| modifier.clone())
+            .collect(),
+    }
+}
+
+pub fn find_semantic_tokens(
+    request: FeatureRequest<SemanticTokensParams>,
+) -> Option<SemanticTokensResult> {
+    let tokens = find_all_tokens(&request, None)?;
+    Some(SemanticTokensResult::Tokens(tokens))
+}
+
+pub fn find_semantic_tokens_range(
+    request: FeatureRequest<SemanticTokensRangeParams>,
+) -> Option<SemanticTokensRangeResult> {
+    let range = request.params.range;
+    let tokens = find_all_tokens(&request, Some(range))?;
+    Some(SemanticTokensRangeResult::Tokens(tokens))
+}
+
+fn find_all_tokens<P>(request: &FeatureRequest<P>, range: Option<Range>) -> Option<SemanticTokens> {
+    let document = request.main_document();
+    let range = range.map_or_else(
+        || TextRange::up_to(document.text.len().try_into().unwrap()),
+        |range| document.line_index.offset_lsp_range(range),
+    );
+
+    let mut builder = TokenBuilder::default();
+    find_latex_tokens(request, range, &mut builder)
+        .or_else(|| find_bibtex_tokens(request, range, &mut builder))?;
+
+    Some(SemanticTokens {
+        result_id: None,
+        data: builder.finish(document),
+    })
+}
+
+#[derive(Debug, Default)]
+struct TokenBuilder {
+    tokens: Vec<(TextRange, TokenKind, u32)>,
+}
+
+impl TokenBuilder {
+    fn push(&mut self, range: TextRange, kind: TokenKind) {
+        self.push_with_modifiers(range, kind, &[]);
+    }
+
+    fn push_with_modifiers(
+        &mut self,
+        range: TextRange,
+        kind: TokenKind,
+        modifiers: &[TokenModifier],
+    ) {
+        if range.is_empty() {
+            return;
+        }
+
+        let bitset = modifiers
+            .iter()
+            .fold(0, |bitset, modifier| bitset | (1 << *modifier as u32));
+        self.tokens.push((range, kind, bitset));
+    }
+
+    fn finish(mut self, document: &Document) -> Vec<SemanticToken> {
+        self.tokens.sort_by_key(|(range, _, _)| range.start());
+
+        let mut data = Vec::new();
+        let mut last_line = 0;
+        let mut last_start = 0;
+        for (range, kind, modifiers) in self.tokens {
+            for line_range in document.line_index.lines(range) {
+                let text = document.text[line_range].trim_end_matches(['\r', '\n']);
+                if text.is_empty() {
+                    continue;
+                }
+
+                let start = document.line_index.line_col_lsp(line_range.start());
+                let length = text.encode_utf16().count() as u32;
+                let delta_line = start.line - last_line;
+                let delta_start = if delta_line == 0 {
+                    start.character - last_start
+                } else {
+                    start.character
+                };
+
+                data.push(SemanticToken {
+                    delta_line,
+                    delta_start,
+                    length,
+                    token_type: kind as u32,
+                    token_modifiers_bitset: modifiers,
+                });
+
+                last_line = start.line;
+                last_start = start.character;
+            }
+        }
+
+        data
+    }
+}
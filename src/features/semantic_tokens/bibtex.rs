@@ -0,0 +1,60 @@
+use rowan::TextRange;
+
+use crate::{features::FeatureRequest, syntax::bibtex};
+
+use super::{TokenBuilder, TokenKind, TokenModifier};
+
+pub fn find_bibtex_tokens<P>(
+    request: &FeatureRequest<P>,
+    range: TextRange,
+    builder: &mut TokenBuilder,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_bibtex()?;
+
+    let root = bibtex::SyntaxNode::new_root(data.green.clone());
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.text_range().intersect(range).is_some())
+    {
+        let range = token.text_range();
+        let parent = token.parent().map(|node| node.kind());
+        match (token.kind(), parent) {
+            (bibtex::JUNK, _) => builder.push(range, TokenKind::Comment),
+            (bibtex::TYPE, _) => builder.push(range, TokenKind::EntryType),
+            (bibtex::NAME, Some(bibtex::ENTRY)) => {
+                builder.push_with_modifiers(
+                    range,
+                    TokenKind::EntryKey,
+                    &[TokenModifier::Definition],
+                );
+            }
+            (bibtex::NAME, Some(bibtex::STRING)) => {
+                builder.push_with_modifiers(
+                    range,
+                    TokenKind::StringName,
+                    &[TokenModifier::Definition],
+                );
+            }
+            (bibtex::NAME, Some(bibtex::FIELD)) => builder.push(range, TokenKind::FieldName),
+            (bibtex::NAME, Some(bibtex::LITERAL)) => builder.push(range, TokenKind::StringName),
+            (bibtex::INTEGER, _) => builder.push(range, TokenKind::Number),
+            (bibtex::COMMAND_NAME | bibtex::ACCENT_NAME, _) => {
+                builder.push(range, TokenKind::Command);
+            }
+            (
+                bibtex::WORD
+                | bibtex::QUOTE
+                | bibtex::L_CURLY
+                | bibtex::R_CURLY
+                | bibtex::COMMA
+                | bibtex::NBSP,
+                Some(bibtex::CURLY_GROUP | bibtex::QUOTE_GROUP | bibtex::ACCENT | bibtex::COMMAND),
+            ) => builder.push(range, TokenKind::Text),
+            _ => {}
+        };
+    }
+
+    Some(())
+}
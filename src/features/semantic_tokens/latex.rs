@@ -0,0 +1,141 @@
+use rowan::{ast::AstNode, TextRange};
+use rustc_hash::FxHashSet;
+
+use crate::{
+    features::{cursor::is_math, FeatureRequest},
+    syntax::latex,
+};
+
+use super::{TokenBuilder, TokenKind, TokenModifier};
+
+pub fn find_latex_tokens<P>(
+    request: &FeatureRequest<P>,
+    range: TextRange,
+    builder: &mut TokenBuilder,
+) -> Option<()> {
+    let main_document = request.main_document();
+    let data = main_document.data.as_latex()?;
+    let user_commands = find_user_commands(request);
+
+    let root = latex::SyntaxNode::new_root(data.green.clone());
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.text_range().intersect(range).is_some())
+    {
+        let kind = token.kind();
+        let range = token.text_range();
+        match kind {
+            latex::COMMENT => builder.push(range, TokenKind::Comment),
+            latex::VERBATIM => builder.push(range, TokenKind::Verbatim),
+            latex::DOLLAR | latex::BEGIN_EQUATION_NAME | latex::END_EQUATION_NAME => {
+                builder.push(range, TokenKind::MathDelimiter);
+            }
+            latex::GENERIC_COMMAND_NAME => {
+                let is_definition = token
+                    .parent_ancestors()
+                    .take(2)
+                    .find_map(latex::CommandDefinition::cast)
+                    .and_then(|def| def.name())
+                    .map_or(false, |name| name == token);
+
+                if is_definition {
+                    builder.push_with_modifiers(
+                        range,
+                        TokenKind::UserCommand,
+                        &[TokenModifier::Definition],
+                    );
+                } else if user_commands.contains(&token.text()[1..]) {
+                    builder.push(range, TokenKind::UserCommand);
+                } else {
+                    builder.push(range, TokenKind::Command);
+                }
+            }
+            latex::BEGIN_BLOCK_COMMENT_NAME | latex::END_BLOCK_COMMENT_NAME => {
+                builder.push(range, TokenKind::Comment);
+            }
+            latex::ENVIRONMENT_DEFINITION_NAME
+            | latex::DOCUMENT_ENVIRONMENT_DEFINITION_NAME
+            | latex::GRAPHICS_PATH_NAME => {
+                builder.push(range, TokenKind::Keyword);
+            }
+            kind if kind.is_command_name() => {
+                builder.push(range, classify_command_name(kind));
+            }
+            latex::WORD => {
+                if let Some((kind, modifiers)) = token
+                    .parent()
+                    .filter(|node| node.kind() == latex::KEY)
+                    .and_then(|node| classify_key(&node))
+                {
+                    builder.push_with_modifiers(range, kind, modifiers);
+                } else if is_math(&token) {
+                    builder.push(range, TokenKind::Math);
+                }
+            }
+            _ => {}
+        };
+    }
+
+    Some(())
+}
+
+fn classify_command_name(kind: latex::SyntaxKind) -> TokenKind {
+    match kind {
+        latex::BEGIN_ENVIRONMENT_NAME
+        | latex::END_ENVIRONMENT_NAME
+        | latex::PART_NAME
+        | latex::CHAPTER_NAME
+        | latex::SECTION_NAME
+        | latex::SUBSECTION_NAME
+        | latex::SUBSUBSECTION_NAME
+        | latex::PARAGRAPH_NAME
+        | latex::SUBPARAGRAPH_NAME
+        | latex::ENUM_ITEM_NAME
+        | latex::PACKAGE_INCLUDE_NAME
+        | latex::CLASS_INCLUDE_NAME
+        | latex::LATEX_INCLUDE_NAME
+        | latex::BIBLATEX_INCLUDE_NAME
+        | latex::BIBTEX_INCLUDE_NAME
+        | latex::IMPORT_NAME
+        | latex::COMMAND_DEFINITION_NAME
+        | latex::DOCUMENT_COMMAND_DEFINITION_NAME
+        | latex::TEX_DEFINITION_NAME
+        | latex::LET_DEFINITION_NAME
+        | latex::MATH_OPERATOR_NAME
+        | latex::THEOREM_DEFINITION_NAME => TokenKind::Keyword,
+        _ => TokenKind::Command,
+    }
+}
+
+fn classify_key(key: &latex::SyntaxNode) -> Option<(TokenKind, &'static [TokenModifier])> {
+    let group = key.parent()?;
+    let command = group.parent()?;
+    match command.kind() {
+        latex::BEGIN | latex::END => Some((TokenKind::Environment, &[])),
+        latex::ENVIRONMENT_DEFINITION => {
+            Some((TokenKind::Environment, &[TokenModifier::Definition]))
+        }
+        latex::LABEL_DEFINITION => Some((TokenKind::Label, &[TokenModifier::Definition])),
+        latex::LABEL_REFERENCE | latex::LABEL_REFERENCE_RANGE => Some((TokenKind::Label, &[])),
+        latex::CITATION => Some((TokenKind::Citation, &[])),
+        _ => None,
+    }
+}
+
+fn find_user_commands<P>(request: &FeatureRequest<P>) -> FxHashSet<String> {
+    let mut names = FxHashSet::default();
+    for document in request.workspace.documents_by_uri.values() {
+        if let Some(data) = document.data.as_latex() {
+            let root = latex::SyntaxNode::new_root(data.green.clone());
+            names.extend(
+                root.descendants()
+                    .filter_map(latex::CommandDefinition::cast)
+                    .filter_map(|def| def.name())
+                    .map(|name| name.text()[1..].to_string()),
+            );
+        }
+    }
+
+    names
+}
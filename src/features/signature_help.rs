@@ -0,0 +1,239 @@
+use lsp_types::{
+    Documentation, ParameterInformation, ParameterLabel, SignatureHelp, SignatureHelpParams,
+    SignatureInformation,
+};
+use rowan::{ast::AstNode, Direction, TextSize};
+
+use crate::syntax::latex::{self, CommandParameter};
+
+use super::{cursor::CursorContext, FeatureRequest};
+
+pub fn find_signature_help(request: FeatureRequest<SignatureHelpParams>) -> Option<SignatureHelp> {
+    let context = CursorContext::new(request);
+    let data = context.request.main_document().data.as_latex()?.clone();
+    let root = latex::SyntaxNode::new_root(data.green);
+    let token = root.token_at_offset(context.offset).left_biased()?;
+
+    token
+        .parent_ancestors()
+        .filter(|node| is_inside_group(node, context.offset))
+        .find_map(|group| {
+            let parent = group.parent()?;
+            if let Some(command) = latex::GenericCommand::cast(parent.clone()) {
+                find_command_signature(&context, &command, &group)
+            } else {
+                let begin = latex::Begin::cast(parent.clone())
+                    .filter(|_| group.kind() == latex::BRACK_GROUP)
+                    .or_else(|| {
+                        latex::Environment::cast(parent)?
+                            .begin()
+                            .filter(|begin| environment_arguments(begin).contains(&group))
+                    })?;
+
+                find_environment_signature(&context, &begin, &group)
+            }
+        })
+}
+
+fn find_command_signature(
+    context: &CursorContext<SignatureHelpParams>,
+    command: &latex::GenericCommand,
+    group: &latex::SyntaxNode,
+) -> Option<SignatureHelp> {
+    let name = command.name()?;
+    let name = name.text().trim_end_matches('*');
+    let arguments: Vec<_> = command
+        .syntax()
+        .children()
+        .filter(|node| matches!(node.kind(), latex::CURLY_GROUP | latex::MIXED_GROUP))
+        .collect();
+
+    let workspace = &context.request.workspace;
+    let user_parameters = workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| document.data.as_latex())
+        .find_map(|data| data.extras.command_definitions.get(name))
+        .filter(|parameters| !parameters.is_empty());
+
+    if let Some(parameters) = user_parameters {
+        return Some(create_signature_help(
+            name,
+            parameters,
+            &arguments,
+            group,
+            parameter_default,
+        ));
+    }
+
+    let component_command = workspace
+        .environment
+        .component_db
+        .linked_components(workspace)
+        .into_iter()
+        .flat_map(|component| component.commands.iter())
+        .find(|command| command.name == name[1..])
+        .filter(|command| !command.parameters.is_empty())?;
+
+    let parameters = vec![CommandParameter::mandatory(); component_command.parameters.len()];
+    let mut help = create_signature_help(name, &parameters, &arguments, group, |_| None);
+    for (info, parameter) in help.signatures[0]
+        .parameters
+        .iter_mut()
+        .flatten()
+        .zip(&component_command.parameters)
+    {
+        let values: Vec<_> = parameter.0.iter().map(|arg| arg.name.as_str()).collect();
+        if !values.is_empty() {
+            info.documentation = Some(Documentation::String(format!(
+                "Possible values: {}",
+                values.join(", ")
+            )));
+        }
+    }
+
+    Some(help)
+}
+
+fn find_environment_signature(
+    context: &CursorContext<SignatureHelpParams>,
+    begin: &latex::Begin,
+    group: &latex::SyntaxNode,
+) -> Option<SignatureHelp> {
+    let name = begin.name()?.key()?.to_string();
+    let parameters = context
+        .request
+        .workspace
+        .documents_by_uri
+        .values()
+        .filter_map(|document| document.data.as_latex())
+        .find_map(|data| data.extras.environment_definitions.get(&name))
+        .filter(|parameters| !parameters.is_empty())?;
+
+    Some(create_signature_help(
+        &format!("\\begin{{{}}}", name),
+        parameters,
+        &environment_arguments(begin),
+        group,
+        parameter_default,
+    ))
+}
+
+/// Collects the argument groups of an environment, which are either part of the `\begin`
+/// (`\begin{foo}[bar]`) or directly follow it (`\begin{foo}{bar}`).
+fn environment_arguments(begin: &latex::Begin) -> Vec<latex::SyntaxNode> {
+    let options = begin
+        .options()
+        .map(|options| options.syntax().clone())
+        .into_iter();
+
+    let rest = begin
+        .syntax()
+        .siblings_with_tokens(Direction::Next)
+        .skip(1)
+        .take_while(|element| {
+            matches!(
+                element.kind(),
+                latex::WHITESPACE
+                    | latex::LINE_BREAK
+                    | latex::COMMENT
+                    | latex::CURLY_GROUP
+                    | latex::MIXED_GROUP
+            )
+        })
+        .filter_map(|element| element.into_node());
+
+    options.chain(rest).collect()
+}
+
+fn is_inside_group(node: &latex::SyntaxNode, offset: TextSize) -> bool {
+    if !matches!(
+        node.kind(),
+        latex::CURLY_GROUP | latex::BRACK_GROUP | latex::MIXED_GROUP
+    ) {
+        return false;
+    }
+
+    let right = node
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| {
+            matches!(
+                token.kind(),
+                latex::R_CURLY | latex::R_BRACK | latex::R_PAREN
+            )
+        });
+
+    node.first_token()
+        .map_or(false, |left| left.text_range().end() <= offset)
+        && right.map_or(true, |right| offset <= right.text_range().start())
+}
+
+fn parameter_default(parameter: &CommandParameter) -> Option<Documentation> {
+    parameter
+        .default
+        .as_ref()
+        .map(|default| Documentation::String(format!("Default: {}", default)))
+}
+
+fn create_signature_help(
+    name: &str,
+    parameters: &[CommandParameter],
+    arguments: &[latex::SyntaxNode],
+    group: &latex::SyntaxNode,
+    documentation: impl Fn(&CommandParameter) -> Option<Documentation>,
+) -> SignatureHelp {
+    let mut label = String::from(name);
+    let mut infos = Vec::new();
+    for (i, parameter) in parameters.iter().enumerate() {
+        if parameter.close.is_empty() {
+            label.push_str(&parameter.open);
+        } else {
+            let text = format!("{}#{}{}", parameter.open, i + 1, parameter.close);
+            label.push_str(&text);
+            infos.push(ParameterInformation {
+                label: ParameterLabel::Simple(text),
+                documentation: documentation(parameter),
+            });
+        }
+    }
+
+    let active_parameter = find_active_parameter(parameters, arguments, group);
+    SignatureHelp {
+        signatures: vec![SignatureInformation {
+            label,
+            documentation: None,
+            parameters: Some(infos),
+            active_parameter: None,
+        }],
+        active_signature: Some(0),
+        active_parameter,
+    }
+}
+
+/// Matches the given argument groups against the parameters and
+/// returns the index of the parameter (excluding flags) that is filled by the group.
+fn find_active_parameter(
+    parameters: &[CommandParameter],
+    arguments: &[latex::SyntaxNode],
+    group: &latex::SyntaxNode,
+) -> Option<u32> {
+    let mut arguments = arguments.iter().peekable();
+    let delimited = parameters.iter().filter(|param| !param.close.is_empty());
+    for (i, parameter) in delimited.enumerate() {
+        let argument = arguments.next_if(|argument| {
+            argument
+                .first_token()
+                .map_or(false, |token| token.text() == parameter.open)
+        });
+
+        match argument {
+            Some(argument) if argument == group => return Some(i as u32),
+            Some(_) => {}
+            None if parameter.is_optional => {}
+            None => return None,
+        };
+    }
+
+    None
+}
@@ -0,0 +1,246 @@
+//! Allows other processes like `texlab inverse-search` to send messages to a running server.
+//! Every server listens on a local TCP port and publishes the port together with a random token
+//! inside of a directory that is only accessible by the current user.
+//! Messages without the correct token are rejected.
+
+use std::{
+    env,
+    fs::{self, DirBuilder, OpenOptions},
+    io::Write,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{bail, Result};
+use log::warn;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use uuid::Uuid;
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+struct Endpoint {
+    port: u16,
+    token: String,
+    root: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Envelope<T> {
+    token: String,
+    message: T,
+}
+
+/// Removes the published endpoint once the server shuts down.
+#[derive(Debug)]
+pub struct IpcServer {
+    endpoint_file: PathBuf,
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.endpoint_file);
+    }
+}
+
+fn endpoint_dir() -> PathBuf {
+    match env::var_os("XDG_RUNTIME_DIR") {
+        Some(dir) => PathBuf::from(dir).join("texlab"),
+        None => {
+            let user = env::var("USER")
+                .or_else(|_| env::var("USERNAME"))
+                .unwrap_or_default();
+            env::temp_dir().join(format!("texlab-{}", user))
+        }
+    }
+}
+
+fn create_endpoint_dir(dir: &Path) -> Result<()> {
+    let mut builder = DirBuilder::new();
+    builder.recursive(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+        builder.mode(0o700);
+        builder.create(dir)?;
+        if fs::metadata(dir)?.permissions().mode() & 0o077 != 0 {
+            bail!("{} is accessible by other users", dir.display());
+        }
+    }
+
+    #[cfg(not(unix))]
+    builder.create(dir)?;
+
+    Ok(())
+}
+
+fn write_endpoint(path: &Path, endpoint: &Endpoint) -> Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(serde_json::to_string(endpoint)?.as_bytes())?;
+    Ok(())
+}
+
+pub fn spawn_server<T, F>(root: &Path, handler: F) -> Result<IpcServer>
+where
+    T: DeserializeOwned,
+    F: Fn(T) + Send + 'static,
+{
+    spawn_server_in(&endpoint_dir(), root, handler)
+}
+
+fn spawn_server_in<T, F>(dir: &Path, root: &Path, handler: F) -> Result<IpcServer>
+where
+    T: DeserializeOwned,
+    F: Fn(T) + Send + 'static,
+{
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))?;
+    let endpoint = Endpoint {
+        port: listener.local_addr()?.port(),
+        token: Uuid::new_v4().to_string(),
+        root: root.to_path_buf(),
+    };
+
+    create_endpoint_dir(dir)?;
+    let endpoint_file = dir.join(format!("{}.json", std::process::id()));
+    write_endpoint(&endpoint_file, &endpoint)?;
+
+    let token = endpoint.token;
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(why) = stream.set_read_timeout(Some(READ_TIMEOUT)) {
+                warn!("Unable to set the timeout of an IPC connection: {}", why);
+                continue;
+            }
+
+            match serde_json::from_reader::<_, Envelope<T>>(stream) {
+                Ok(envelope) if envelope.token == token => handler(envelope.message),
+                Ok(_) => warn!("Rejected IPC message with an invalid token"),
+                Err(why) => warn!("Received invalid IPC message: {}", why),
+            };
+        }
+    });
+
+    Ok(IpcServer { endpoint_file })
+}
+
+/// Sends the message to the servers that are most likely responsible for the given path.
+/// Servers whose root contains the path are preferred over the most recently started ones.
+pub fn send<T: Serialize>(path: &Path, message: &T) -> Result<()> {
+    send_in(&endpoint_dir(), path, message)
+}
+
+fn send_in<T: Serialize>(dir: &Path, path: &Path, message: &T) -> Result<()> {
+    let mut endpoints = Vec::new();
+    for entry in fs::read_dir(dir)?.flatten() {
+        let modified = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if let Some(endpoint) = fs::read_to_string(entry.path())
+            .ok()
+            .and_then(|text| serde_json::from_str::<Endpoint>(&text).ok())
+        {
+            endpoints.push((endpoint, modified));
+        }
+    }
+
+    sort_endpoints(&mut endpoints, path);
+    for (endpoint, _) in endpoints {
+        if let Ok(stream) = TcpStream::connect((Ipv4Addr::LOCALHOST, endpoint.port)) {
+            let envelope = Envelope {
+                token: endpoint.token,
+                message,
+            };
+
+            serde_json::to_writer(stream, &envelope)?;
+            return Ok(());
+        }
+    }
+
+    bail!("No running server found")
+}
+
+fn sort_endpoints<M: Ord>(endpoints: &mut [(Endpoint, M)], path: &Path) {
+    endpoints.sort_by(|(a, a_modified), (b, b_modified)| {
+        let depth = |endpoint: &Endpoint| {
+            path.starts_with(&endpoint.root)
+                .then(|| endpoint.root.components().count())
+        };
+
+        depth(b)
+            .cmp(&depth(a))
+            .then_with(|| b_modified.cmp(a_modified))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn endpoint(port: u16, root: &str) -> Endpoint {
+        Endpoint {
+            port,
+            token: String::new(),
+            root: PathBuf::from(root),
+        }
+    }
+
+    #[test]
+    fn prefer_innermost_root() {
+        let mut endpoints = vec![
+            (endpoint(1, "/other"), 3),
+            (endpoint(2, "/repo"), 1),
+            (endpoint(3, "/repo/paper"), 0),
+            (endpoint(4, "/"), 2),
+        ];
+
+        sort_endpoints(&mut endpoints, Path::new("/repo/paper/main.tex"));
+        let ports: Vec<_> = endpoints
+            .iter()
+            .map(|(endpoint, _)| endpoint.port)
+            .collect();
+        assert_eq!(ports, vec![3, 2, 4, 1]);
+    }
+
+    #[test]
+    fn round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let endpoint_dir = dir.path().join("texlab");
+
+        let (tx, rx) = crossbeam_channel::unbounded();
+        let server = spawn_server_in(&endpoint_dir, dir.path(), move |message: String| {
+            tx.send(message).unwrap();
+        })
+        .unwrap();
+
+        let endpoint_file = server.endpoint_file.clone();
+        let endpoint: Endpoint =
+            serde_json::from_str(&fs::read_to_string(&endpoint_file).unwrap()).unwrap();
+
+        let stream = TcpStream::connect((Ipv4Addr::LOCALHOST, endpoint.port)).unwrap();
+        let forged = Envelope {
+            token: "foo".to_string(),
+            message: "forged",
+        };
+        serde_json::to_writer(stream, &forged).unwrap();
+
+        send_in(&endpoint_dir, &dir.path().join("main.tex"), &"valid").unwrap();
+        assert_eq!(rx.recv_timeout(Duration::from_secs(5)).unwrap(), "valid");
+        assert!(rx.try_recv().is_err());
+
+        drop(server);
+        assert!(!endpoint_file.exists());
+    }
+}
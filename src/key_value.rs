@@ -0,0 +1,221 @@
+use rowan::{ast::AstNode, NodeOrToken, TextRange, TextSize};
+
+use crate::{syntax::latex, KeyValueSchema, LANGUAGE_DATA};
+
+/// A group of options like `[width=3cm, keepaspectratio]` that is described by a schema.
+#[derive(Debug, Clone)]
+pub struct KeyValueGroup {
+    pub node: latex::SyntaxNode,
+    pub schema: &'static KeyValueSchema,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct KeyValueEntry {
+    pub key: String,
+    pub key_range: TextRange,
+    /// The value after the equality sign. The range is empty if the value is missing.
+    pub value: Option<(String, TextRange)>,
+}
+
+impl KeyValueGroup {
+    /// Finds the schema of a group that is passed to a command or environment.
+    /// Both key-value groups and regular groups are supported since most commands
+    /// with options are not known to the parser.
+    pub fn cast(node: latex::SyntaxNode) -> Option<Self> {
+        if !matches!(
+            node.kind(),
+            latex::BRACK_GROUP
+                | latex::BRACK_GROUP_KEY_VALUE
+                | latex::CURLY_GROUP
+                | latex::CURLY_GROUP_KEY_VALUE
+                | latex::MIXED_GROUP
+        ) {
+            return None;
+        }
+
+        let parent = node.parent()?;
+        let schema = match latex::Begin::cast(parent.clone()) {
+            Some(begin) => {
+                let name = begin.name()?.key()?.to_string();
+                LANGUAGE_DATA.find_environment_schema(&name)?
+            }
+            None => {
+                let command = parent
+                    .first_token()
+                    .filter(|token| token.kind().is_command_name())?;
+                LANGUAGE_DATA.find_command_schema(&command.text()[1..])?
+            }
+        };
+
+        Some(Self { node, schema })
+    }
+
+    /// Finds the innermost group that contains the given offset.
+    pub fn find(root: &latex::SyntaxNode, offset: TextSize) -> Option<Self> {
+        let token = root.token_at_offset(offset).left_biased()?;
+        let node = token.parent_ancestors().find(|node| {
+            matches!(
+                node.kind(),
+                latex::BRACK_GROUP
+                    | latex::BRACK_GROUP_KEY_VALUE
+                    | latex::CURLY_GROUP
+                    | latex::CURLY_GROUP_KEY_VALUE
+                    | latex::MIXED_GROUP
+            )
+        })?;
+
+        Self::cast(node).filter(|group| group.contains(offset))
+    }
+
+    /// Returns whether the offset is placed between the delimiters of the group.
+    pub fn contains(&self, offset: TextSize) -> bool {
+        let start = self.node.text_range().start() + TextSize::from(1);
+        let end = self
+            .node
+            .last_token()
+            .filter(|token| matches!(token.kind(), latex::R_BRACK | latex::R_CURLY))
+            .map_or(self.node.text_range().end(), |token| {
+                token.text_range().start()
+            });
+
+        start <= offset && offset <= end
+    }
+
+    pub fn entries(&self) -> Vec<KeyValueEntry> {
+        let mut builder = EntryBuilder::default();
+        for child in self.node.children_with_tokens() {
+            match child {
+                NodeOrToken::Node(node) => builder.visit_node(&node),
+                NodeOrToken::Token(token) => {
+                    if !matches!(
+                        token.kind(),
+                        latex::L_BRACK | latex::R_BRACK | latex::L_CURLY | latex::R_CURLY
+                    ) {
+                        builder.visit_token(&token);
+                    }
+                }
+            };
+        }
+
+        let start = self.node.text_range().start();
+        let text = self.node.text();
+        let slice = |range: TextRange| text.slice(range - start).to_string();
+        builder
+            .finish()
+            .into_iter()
+            .map(|(key_range, value_range)| KeyValueEntry {
+                key: slice(key_range),
+                key_range,
+                value: value_range.map(|range| (slice(range), range)),
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Default)]
+struct EntryBuilder {
+    entries: Vec<(TextRange, Option<TextRange>)>,
+    key: Option<TextRange>,
+    equality_sign: Option<TextRange>,
+    value: Option<TextRange>,
+}
+
+impl EntryBuilder {
+    fn visit_node(&mut self, node: &latex::SyntaxNode) {
+        if latex::KeyValueBody::can_cast(node.kind())
+            || latex::KeyValuePair::can_cast(node.kind())
+            || latex::Key::can_cast(node.kind())
+            || latex::Value::can_cast(node.kind())
+            || node.kind() == latex::TEXT
+        {
+            for child in node.children_with_tokens() {
+                match child {
+                    NodeOrToken::Node(node) => self.visit_node(&node),
+                    NodeOrToken::Token(token) => self.visit_token(&token),
+                };
+            }
+        } else {
+            // Nested groups and commands are part of the current key or value.
+            self.push(node.text_range());
+        }
+    }
+
+    fn visit_token(&mut self, token: &latex::SyntaxToken) {
+        match token.kind() {
+            latex::COMMA => self.end_entry(),
+            latex::EQUALITY_SIGN if self.equality_sign.is_none() => {
+                self.equality_sign = Some(token.text_range());
+            }
+            latex::WHITESPACE | latex::LINE_BREAK | latex::COMMENT | latex::MISSING => {}
+            _ => self.push(token.text_range()),
+        };
+    }
+
+    fn push(&mut self, range: TextRange) {
+        let part = if self.equality_sign.is_some() {
+            &mut self.value
+        } else {
+            &mut self.key
+        };
+
+        *part = Some(part.map_or(range, |current| current.cover(range)));
+    }
+
+    fn end_entry(&mut self) {
+        if let Some(key) = self.key.take() {
+            let value = self.equality_sign.take().map(|equality_sign| {
+                self.value
+                    .take()
+                    .unwrap_or_else(|| TextRange::empty(equality_sign.end()))
+            });
+
+            self.entries.push((key, value));
+        }
+
+        self.equality_sign = None;
+        self.value = None;
+    }
+
+    fn finish(mut self) -> Vec<(TextRange, Option<TextRange>)> {
+        self.end_entry();
+        self.entries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(text: &str) -> Vec<(String, Option<String>)> {
+        let root = latex::SyntaxNode::new_root(latex::parse(text).green);
+        let group = root.descendants().find_map(KeyValueGroup::cast).unwrap();
+        group
+            .entries()
+            .into_iter()
+            .map(|entry| (entry.key, entry.value.map(|(value, _)| value)))
+            .collect()
+    }
+
+    #[test]
+    fn key_value_group() {
+        assert_eq!(
+            entries("\\includegraphics[width=0.5\\textwidth, keepaspectratio, angle=]{foo}"),
+            vec![
+                ("width".into(), Some("0.5\\textwidth".into())),
+                ("keepaspectratio".into(), None),
+                ("angle".into(), Some(String::new())),
+            ]
+        );
+    }
+
+    #[test]
+    fn generic_group() {
+        assert_eq!(
+            entries("\\begin{tikzpicture}[line width = 2pt, every node/.style={draw, red}]"),
+            vec![
+                ("line width".into(), Some("2pt".into())),
+                ("every node/.style".into(), Some("{draw, red}".into())),
+            ]
+        );
+    }
+}
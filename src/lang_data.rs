@@ -28,6 +28,114 @@ pub struct BibtexFieldDoc {
     pub documentation: String,
 }
 
+/// Describes the key-value options of commands like `\includegraphics[width=...]`
+/// or environments like `\begin{tikzpicture}[...]`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyValueSchema {
+    #[serde(default)]
+    pub commands: Vec<String>,
+    #[serde(default)]
+    pub environments: Vec<String>,
+    pub keys: Vec<KeyValueKeyDoc>,
+    /// The options are single-letter flags that are written without separators like `[htbp]`.
+    #[serde(default)]
+    pub is_compact: bool,
+    /// The keys are only a selection of the available keys, so unknown keys are not reported.
+    #[serde(default)]
+    pub allows_unknown_keys: bool,
+}
+
+impl KeyValueSchema {
+    #[must_use]
+    pub fn find_key(&self, name: &str) -> Option<&KeyValueKeyDoc> {
+        self.keys.iter().find(|key| key.name == name)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyValueKeyDoc {
+    pub name: String,
+    pub documentation: Option<String>,
+    #[serde(default)]
+    pub value_type: KeyValueType,
+    /// The allowed values of a `choice` key.
+    #[serde(default)]
+    pub values: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyValueType {
+    /// The key is a flag that does not take a value.
+    None,
+    Bool,
+    Number,
+    Length,
+    Color,
+    Choice,
+    Text,
+}
+
+impl Default for KeyValueType {
+    fn default() -> Self {
+        Self::Text
+    }
+}
+
+impl KeyValueType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "flag",
+            Self::Bool => "boolean",
+            Self::Number => "number",
+            Self::Length => "length",
+            Self::Color => "color",
+            Self::Choice => "choice",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// The documented options of packages or classes like `\usepackage[colorlinks]{hyperref}`.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentOptionsDoc {
+    pub file_names: Vec<String>,
+    pub options: Vec<ComponentOptionDoc>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ComponentOptionDoc {
+    pub name: String,
+    pub documentation: String,
+}
+
+/// A math symbol together with the name of its Unicode code point.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MathSymbol {
+    pub command: String,
+    pub glyph: String,
+    pub unicode_name: String,
+    pub package: Option<String>,
+}
+
+/// The structure that is inserted when completing the name of an environment.
+/// Both the arguments and the body lines use the snippet syntax of LSP,
+/// except that backslashes are inserted literally.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: String,
+    #[serde(default)]
+    pub body: Vec<String>,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LanguageData {
@@ -38,6 +146,10 @@ pub struct LanguageData {
     pub tikz_libraries: Vec<String>,
     pub math_environments: Vec<String>,
     pub enum_environments: Vec<String>,
+    pub key_value_schemas: Vec<KeyValueSchema>,
+    pub component_options: Vec<ComponentOptionsDoc>,
+    pub math_symbols: Vec<MathSymbol>,
+    pub environment_templates: Vec<EnvironmentTemplate>,
 }
 
 impl LanguageData {
@@ -62,6 +174,42 @@ impl LanguageData {
             .find(|field| field.name.to_lowercase() == name.to_lowercase())
             .map(|field| field.documentation.as_ref())
     }
+
+    #[must_use]
+    pub fn find_math_symbol_by_glyph(&self, glyph: &str) -> Option<&MathSymbol> {
+        self.math_symbols
+            .iter()
+            .find(|symbol| symbol.glyph == glyph)
+    }
+
+    #[must_use]
+    pub fn find_environment_template(&self, name: &str) -> Option<&EnvironmentTemplate> {
+        self.environment_templates
+            .iter()
+            .find(|template| template.name == name)
+    }
+
+    #[must_use]
+    pub fn find_command_schema(&self, name: &str) -> Option<&KeyValueSchema> {
+        self.key_value_schemas
+            .iter()
+            .find(|schema| schema.commands.iter().any(|command| command == name))
+    }
+
+    #[must_use]
+    pub fn find_component_options(&self, file_name: &str) -> &[ComponentOptionDoc] {
+        self.component_options
+            .iter()
+            .find(|doc| doc.file_names.iter().any(|name| name == file_name))
+            .map_or(&[], |doc| &doc.options)
+    }
+
+    #[must_use]
+    pub fn find_environment_schema(&self, name: &str) -> Option<&KeyValueSchema> {
+        self.key_value_schemas
+            .iter()
+            .find(|schema| schema.environments.iter().any(|env| env == name))
+    }
 }
 
 pub static LANGUAGE_DATA: Lazy<LanguageData> = Lazy::new(|| {
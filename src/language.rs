@@ -5,6 +5,7 @@ pub enum DocumentLanguage {
     Latex,
     Bibtex,
     BuildLog,
+    BibLog,
 }
 
 impl DocumentLanguage {
@@ -16,9 +17,10 @@ impl DocumentLanguage {
 
     pub fn by_extension(extension: &str) -> Option<Self> {
         match extension.to_lowercase().as_str() {
-            "tex" | "sty" | "cls" | "def" | "lco" | "aux" | "rnw" => Some(Self::Latex),
+            "tex" | "sty" | "cls" | "def" | "lco" | "aux" | "bbl" | "rnw" => Some(Self::Latex),
             "bib" | "bibtex" => Some(Self::Bibtex),
             "log" => Some(Self::BuildLog),
+            "blg" => Some(Self::BibLog),
             _ => None,
         }
     }
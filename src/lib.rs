@@ -2,6 +2,7 @@ mod capabilities;
 pub mod citation;
 mod client;
 pub mod component_db;
+mod crawler;
 mod debouncer;
 mod diagnostics;
 mod dispatch;
@@ -9,22 +10,26 @@ pub mod distro;
 mod document;
 mod environment;
 pub mod features;
+pub mod ipc;
+mod key_value;
 mod label;
 mod lang_data;
 mod language;
 mod line_index;
 mod line_index_ext;
 mod options;
+mod project_config;
 mod range;
 mod req_queue;
 mod server;
+pub mod synctex;
 pub mod syntax;
 mod workspace;
 
 pub use self::{
     capabilities::ClientCapabilitiesExt,
     document::*,
-    environment::Environment,
+    environment::{Environment, WorkspaceFolder},
     label::*,
     lang_data::*,
     language::DocumentLanguage,
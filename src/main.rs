@@ -1,10 +1,14 @@
 use std::{env, fs::OpenOptions, io, path::PathBuf};
 
-use anyhow::Result;
-use clap::{ArgAction, Parser};
+use anyhow::{anyhow, bail, Result};
+use clap::{ArgAction, Args, Parser, Subcommand};
 use log::LevelFilter;
 use lsp_server::Connection;
-use texlab::Server;
+use lsp_types::Url;
+use texlab::{
+    features::{find_inverse_search_target, InverseSearchTarget},
+    ipc, Server,
+};
 
 /// An implementation of the Language Server Protocol for LaTeX
 #[derive(Debug, Parser)]
@@ -25,12 +29,49 @@ struct Opts {
     /// Print version information and exit
     #[clap(short = 'V', long)]
     version: bool,
+
+    #[clap(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Reveal a location of a TeX file or a PDF file in the editor of a running server
+    InverseSearch(InverseSearchOpts),
+}
+
+#[derive(Debug, Args)]
+struct InverseSearchOpts {
+    /// The TeX file or the PDF file that contains the location
+    #[clap(short, long, value_parser)]
+    input: PathBuf,
+
+    /// The line number inside of the TeX file (1-based)
+    #[clap(short, long)]
+    line: Option<u32>,
+
+    /// The page number inside of the PDF file (1-based)
+    #[clap(long)]
+    page: Option<u32>,
+
+    /// The horizontal position on the page in PDF points measured from the left
+    #[clap(long)]
+    x: Option<f64>,
+
+    /// The vertical position on the page in PDF points measured from the top
+    #[clap(long)]
+    y: Option<f64>,
 }
 
 fn main() -> Result<()> {
-    let opts = Opts::parse();
+    let mut opts = Opts::parse();
+    let command = opts.command.take();
     setup_logger(opts);
 
+    if let Some(Command::InverseSearch(opts)) = command {
+        return inverse_search(opts);
+    }
+
     let (connection, threads) = Connection::stdio();
     Server::with_connection(connection, env::current_dir()?, true).run()?;
     threads.join()?;
@@ -38,6 +79,28 @@ fn main() -> Result<()> {
     Ok(())
 }
 
+fn inverse_search(opts: InverseSearchOpts) -> Result<()> {
+    let path = opts.input.canonicalize()?;
+    let target = match opts {
+        InverseSearchOpts {
+            line: Some(line), ..
+        } => InverseSearchTarget {
+            uri: Url::from_file_path(&path).map_err(|_| anyhow!("invalid path"))?,
+            line: line.saturating_sub(1),
+        },
+        InverseSearchOpts {
+            page: Some(page),
+            x: Some(x),
+            y: Some(y),
+            ..
+        } => find_inverse_search_target(&path, page, x, y)
+            .ok_or_else(|| anyhow!("No SyncTeX record found for {}", path.display()))?,
+        _ => bail!("Either --line or --page, --x and --y must be specified"),
+    };
+
+    ipc::send(&path, &target)
+}
+
 fn setup_logger(opts: Opts) {
     let verbosity_level = if !opts.quiet {
         match opts.verbosity {
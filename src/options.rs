@@ -3,6 +3,8 @@ use std::path::PathBuf;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
+use crate::{syntax::latex::SyntaxConfig, EnvironmentTemplate, LANGUAGE_DATA};
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Options {
@@ -38,6 +40,15 @@ pub struct Options {
 
     #[serde(default)]
     pub forward_search: ForwardSearchOptions,
+
+    #[serde(default)]
+    pub inlay_hints: InlayHintsOptions,
+
+    #[serde(default)]
+    pub completion: CompletionOptions,
+
+    #[serde(default)]
+    pub experimental: ExperimentalOptions,
 }
 
 fn default_diagnostics_delay() -> u64 {
@@ -132,7 +143,47 @@ pub struct ForwardSearchOptions {
     pub args: Option<Vec<String>>,
 }
 
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintsOptions {
+    #[serde(default = "default_true")]
+    pub label_references: bool,
+
+    #[serde(default = "default_true")]
+    pub citations: bool,
+}
+
+impl Default for InlayHintsOptions {
+    fn default() -> Self {
+        Self {
+            label_references: true,
+            citations: true,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompletionOptions {
+    #[serde(default)]
+    pub environment_templates: Vec<EnvironmentTemplate>,
+}
+
+impl CompletionOptions {
+    /// Finds the template of an environment, preferring the user-defined ones over the built-in ones.
+    pub fn find_environment_template(&self, name: &str) -> Option<&EnvironmentTemplate> {
+        self.environment_templates
+            .iter()
+            .find(|template| template.name == name)
+            .or_else(|| LANGUAGE_DATA.find_environment_template(name))
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct DiagnosticsOptions {
     #[serde(default)]
@@ -140,7 +191,66 @@ pub struct DiagnosticsOptions {
 
     #[serde(default)]
     pub ignored_patterns: Vec<DiagnosticsPattern>,
+
+    #[serde(default)]
+    pub unused_labels: bool,
+
+    #[serde(default)]
+    pub unused_entries: bool,
+
+    #[serde(default = "default_true")]
+    pub unknown_keys: bool,
+}
+
+impl Default for DiagnosticsOptions {
+    fn default() -> Self {
+        Self {
+            allowed_patterns: Vec::new(),
+            ignored_patterns: Vec::new(),
+            unused_labels: false,
+            unused_entries: false,
+            unknown_keys: true,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiagnosticsPattern(#[serde(with = "serde_regex")] pub Regex);
+
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExperimentalOptions {
+    #[serde(default)]
+    pub label_definition_commands: Vec<String>,
+
+    #[serde(default)]
+    pub label_reference_commands: Vec<String>,
+
+    #[serde(default)]
+    pub citation_commands: Vec<String>,
+
+    #[serde(default)]
+    pub include_commands: Vec<String>,
+
+    #[serde(default)]
+    pub section_commands: Vec<String>,
+}
+
+impl ExperimentalOptions {
+    pub fn syntax_config(&self) -> SyntaxConfig {
+        let commands = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| name.trim_start_matches('\\').to_string())
+                .collect()
+        };
+
+        SyntaxConfig {
+            label_definition_commands: commands(&self.label_definition_commands),
+            label_reference_commands: commands(&self.label_reference_commands),
+            citation_commands: commands(&self.citation_commands),
+            include_commands: commands(&self.include_commands),
+            section_commands: commands(&self.section_commands),
+        }
+    }
+}
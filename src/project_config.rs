@@ -0,0 +1,221 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Result;
+use serde_json::{Map, Value};
+
+/// The names of the project files in the order in which they are looked up inside of a directory.
+pub const PROJECT_CONFIG_FILE_NAMES: [&str; 3] = [".texlabroot", "texlabrc", ".texlabrc"];
+
+/// The settings that a project file may change.
+/// Project files come with the (possibly untrusted) sources,
+/// so they cannot change settings that run programs like `build.executable` or `chktex`.
+/// This includes `build.args` because `latexmk` runs arbitrary code with options like `-e`.
+const PROJECT_SETTINGS: [&[&str]; 12] = [
+    &["rootDirectory"],
+    &["auxDirectory"],
+    &["bibtexFormatter"],
+    &["latexFormatter"],
+    &["formatterLineLength"],
+    &["diagnostics", "allowedPatterns"],
+    &["diagnostics", "ignoredPatterns"],
+    &["experimental", "labelDefinitionCommands"],
+    &["experimental", "labelReferenceCommands"],
+    &["experimental", "citationCommands"],
+    &["experimental", "includeCommands"],
+    &["experimental", "sectionCommands"],
+];
+
+/// Settings that are shared by everyone working on a project.
+/// The file contains the same JSON object as the `texlab` section of the client settings.
+/// An empty file only marks the root directory of the project.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ProjectConfig {
+    pub path: PathBuf,
+    pub settings: Value,
+}
+
+impl ProjectConfig {
+    /// Finds the closest project file in the given directory or one of its ancestors.
+    pub fn find(directory: &Path) -> Option<PathBuf> {
+        directory.ancestors().find_map(|directory| {
+            PROJECT_CONFIG_FILE_NAMES
+                .iter()
+                .map(|name| directory.join(name))
+                .find(|path| path.is_file())
+        })
+    }
+
+    pub fn is_project_config(path: &Path) -> bool {
+        path.file_name()
+            .and_then(|name| name.to_str())
+            .map_or(false, |name| PROJECT_CONFIG_FILE_NAMES.contains(&name))
+    }
+
+    pub fn load(path: PathBuf) -> Result<Self> {
+        let text = fs::read_to_string(&path)?;
+        let mut settings = if text.trim().is_empty() {
+            Value::Object(Map::new())
+        } else {
+            serde_json::from_str(&text)?
+        };
+
+        let directory = path.parent().unwrap_or_else(|| Path::new("."));
+        if let Value::Object(object) = &mut settings {
+            for key in ["rootDirectory", "auxDirectory"] {
+                if let Some(Value::String(dir)) = object.get_mut(key) {
+                    *dir = directory.join(&dir).to_string_lossy().into_owned();
+                }
+            }
+
+            object
+                .entry("rootDirectory")
+                .or_insert_with(|| Value::String(directory.to_string_lossy().into_owned()));
+        }
+
+        Ok(Self { path, settings })
+    }
+
+    /// Merges the project settings into the settings of the client.
+    /// Values of the project file take precedence.
+    /// All other settings of the project file are ignored.
+    pub fn apply(&self, client_settings: &Value) -> Value {
+        let mut settings = match client_settings {
+            Value::Object(_) => client_settings.clone(),
+            _ => Value::Object(Map::new()),
+        };
+
+        for keys in PROJECT_SETTINGS {
+            let value = keys
+                .iter()
+                .try_fold(&self.settings, |value, key| value.get(key));
+
+            if let Some(value) = value {
+                insert(&mut settings, keys, value.clone());
+            }
+        }
+
+        settings
+    }
+}
+
+fn insert(mut target: &mut Value, keys: &[&str], value: Value) {
+    for key in keys {
+        if !target.is_object() {
+            *target = Value::Object(Map::new());
+        }
+
+        target = target
+            .as_object_mut()
+            .unwrap()
+            .entry(*key)
+            .or_insert(Value::Null);
+    }
+
+    *target = value;
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn apply_overrides_client_settings() {
+        let config = ProjectConfig {
+            path: PathBuf::from("texlabrc"),
+            settings: json!({
+                "diagnostics": { "ignoredPatterns": ["Overfull"] },
+                "latexFormatter": "texlab"
+            }),
+        };
+
+        let client_settings = json!({
+            "diagnostics": { "allowedPatterns": ["Warning"] },
+            "latexFormatter": "latexindent",
+            "diagnosticsDelay": 100
+        });
+
+        assert_eq!(
+            config.apply(&client_settings),
+            json!({
+                "diagnostics": { "allowedPatterns": ["Warning"], "ignoredPatterns": ["Overfull"] },
+                "latexFormatter": "texlab",
+                "diagnosticsDelay": 100
+            })
+        );
+    }
+
+    #[test]
+    fn apply_ignores_programs() {
+        let config = ProjectConfig {
+            path: PathBuf::from("texlabrc"),
+            settings: json!({
+                "build": { "executable": "evil", "args": ["%f"] },
+                "chktex": { "onOpenAndSave": true },
+                "forwardSearch": { "executable": "evil" }
+            }),
+        };
+
+        let client_settings = json!({
+            "build": { "executable": "latexmk" }
+        });
+
+        assert_eq!(
+            config.apply(&client_settings),
+            json!({
+                "build": { "executable": "latexmk" }
+            })
+        );
+    }
+
+    #[test]
+    fn apply_ignores_build_args() {
+        let config = ProjectConfig {
+            path: PathBuf::from("texlabrc"),
+            settings: json!({
+                "build": { "args": ["-e", "system('evil')", "-pdflatex=evil", "%f"] }
+            }),
+        };
+
+        let client_settings = json!({
+            "build": { "args": ["-pdf", "%f"] }
+        });
+
+        assert_eq!(config.apply(&client_settings), client_settings);
+    }
+
+    #[test]
+    fn load_resolves_directories() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let path = directory.path().join("texlabrc");
+        fs::write(&path, r#"{ "auxDirectory": "build" }"#)?;
+
+        let config = ProjectConfig::load(path)?;
+        assert_eq!(
+            config.settings,
+            json!({
+                "auxDirectory": directory.path().join("build").to_string_lossy(),
+                "rootDirectory": directory.path().to_string_lossy()
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_in_ancestors() -> Result<()> {
+        let directory = tempfile::tempdir()?;
+        let nested = directory.path().join("chapters").join("intro");
+        fs::create_dir_all(&nested)?;
+        fs::write(directory.path().join(".texlabroot"), "")?;
+
+        assert_eq!(
+            ProjectConfig::find(&nested),
+            Some(directory.path().join(".texlabroot"))
+        );
+        Ok(())
+    }
+}
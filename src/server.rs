@@ -1,5 +1,5 @@
 use std::{
-    path::PathBuf,
+    path::{Path, PathBuf},
     sync::{Arc, Mutex},
     time::Duration,
 };
@@ -9,31 +9,45 @@ use crossbeam_channel::{Receiver, Sender};
 use log::{error, info, warn};
 use lsp_server::{Connection, Message, RequestId};
 use lsp_types::{notification::*, request::*, *};
+use rustc_hash::{FxHashMap, FxHashSet};
 use serde::Serialize;
 use threadpool::ThreadPool;
+use uuid::Uuid;
 
 use crate::{
-    client::{send_notification, send_request},
-    debouncer,
+    client::{send_notification, send_request, ProgressReporter},
+    component_db::{self, ComponentDatabase, COMPONENT_DATABASE},
+    crawler, debouncer,
     diagnostics::DiagnosticManager,
     dispatch::{NotificationDispatcher, RequestDispatcher},
-    distro::Distribution,
+    distro::{Distribution, Resolver},
     features::{
-        execute_command, find_all_references, find_document_highlights, find_document_links,
-        find_document_symbols, find_foldings, find_hover, find_workspace_symbols,
-        format_source_code, goto_definition, prepare_rename_all, rename_all, BuildEngine,
-        BuildParams, BuildResult, BuildStatus, FeatureRequest, ForwardSearchResult,
-        ForwardSearchStatus,
+        execute_command, execute_inverse_search, find_all_references, find_code_actions,
+        find_document_highlights, find_document_links, find_document_symbols, find_foldings,
+        find_hover, find_include_tree, find_incoming_calls, find_inlay_hints, find_outgoing_calls,
+        find_pdf_location, find_project_root, find_semantic_tokens, find_semantic_tokens_range,
+        find_signature_help, find_workspace_symbols, format_on_type, format_source_code,
+        format_source_code_range, goto_definition, prepare_call_hierarchy, prepare_rename_all,
+        rename_all, semantic_tokens_legend, BuildEngine, BuildParams, BuildResult, BuildStatus,
+        FeatureRequest, ForwardSearchResult, ForwardSearchStatus, IncludeGraphParams, IncludeTree,
+        InverseSearchParams, InverseSearchTarget, PdfLocation,
     },
+    ipc,
+    project_config::{ProjectConfig, PROJECT_CONFIG_FILE_NAMES},
     req_queue::{IncomingData, ReqQueue},
     ClientCapabilitiesExt, Document, DocumentData, DocumentLanguage, Environment, LineIndex,
-    LineIndexExt, Options, Workspace, WorkspaceEvent,
+    LineIndexExt, Options, Workspace, WorkspaceEvent, WorkspaceFolder,
 };
 
 #[derive(Debug)]
 enum InternalMessage {
     SetDistro(Distribution),
-    SetOptions(Arc<Options>),
+    SetComponentDatabase(ComponentDatabase),
+    SetClientSettings(serde_json::Value, FxHashMap<PathBuf, serde_json::Value>),
+    IndexWorkspace,
+    IndexFolders(Vec<PathBuf>),
+    AddDocuments(Vec<Document>),
+    ShowDocument(InverseSearchTarget),
 }
 
 #[derive(Clone)]
@@ -43,11 +57,15 @@ pub struct Server {
     internal_rx: Receiver<InternalMessage>,
     req_queue: Arc<Mutex<ReqQueue>>,
     workspace: Workspace,
+    client_settings: Arc<serde_json::Value>,
+    folder_settings: Arc<FxHashMap<PathBuf, serde_json::Value>>,
+    project_configs: Arc<FxHashMap<PathBuf, Arc<ProjectConfig>>>,
     diagnostic_tx: debouncer::Sender<Workspace>,
     diagnostic_manager: DiagnosticManager,
     pool: Arc<Mutex<ThreadPool>>,
     load_resolver: bool,
     build_engine: Arc<BuildEngine>,
+    ipc_server: Option<Arc<ipc::IpcServer>>,
 }
 
 impl Server {
@@ -67,11 +85,15 @@ impl Server {
             internal_rx,
             req_queue,
             workspace,
+            client_settings: Arc::new(serde_json::json!({})),
+            folder_settings: Arc::default(),
+            project_configs: Arc::default(),
             diagnostic_tx,
             diagnostic_manager,
             pool: Arc::new(Mutex::new(threadpool::Builder::new().build())),
             load_resolver,
             build_engine: Arc::default(),
+            ipc_server: None,
         }
     }
 
@@ -101,6 +123,13 @@ impl Server {
             definition_provider: Some(OneOf::Left(true)),
             references_provider: Some(OneOf::Left(true)),
             hover_provider: Some(HoverProviderCapability::Simple(true)),
+            inlay_hint_provider: Some(OneOf::Left(true)),
+            call_hierarchy_provider: Some(CallHierarchyServerCapability::Simple(true)),
+            signature_help_provider: Some(SignatureHelpOptions {
+                trigger_characters: Some(vec!["{".into(), "[".into()]),
+                retrigger_characters: Some(vec!["}".into(), "]".into()]),
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            }),
             #[cfg(feature = "completion")]
             completion_provider: Some(CompletionOptions {
                 resolve_provider: Some(true),
@@ -115,6 +144,13 @@ impl Server {
                 ..CompletionOptions::default()
             }),
             document_symbol_provider: Some(OneOf::Left(true)),
+            workspace: Some(WorkspaceServerCapabilities {
+                workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                    supported: Some(true),
+                    change_notifications: Some(OneOf::Left(true)),
+                }),
+                file_operations: None,
+            }),
             workspace_symbol_provider: Some(OneOf::Left(true)),
             rename_provider: Some(OneOf::Right(RenameOptions {
                 prepare_provider: Some(true),
@@ -122,6 +158,16 @@ impl Server {
             })),
             document_highlight_provider: Some(OneOf::Left(true)),
             document_formatting_provider: Some(OneOf::Left(true)),
+            document_range_formatting_provider: Some(OneOf::Left(true)),
+            document_on_type_formatting_provider: Some(DocumentOnTypeFormattingOptions {
+                first_trigger_character: "}".to_string(),
+                more_trigger_character: None,
+            }),
+            code_action_provider: Some(CodeActionProviderCapability::Options(CodeActionOptions {
+                code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                resolve_provider: None,
+                work_done_progress_options: WorkDoneProgressOptions::default(),
+            })),
             execute_command_provider: Some(ExecuteCommandOptions {
                 commands: vec![
                     "texlab.cleanAuxiliary".into(),
@@ -129,6 +175,14 @@ impl Server {
                 ],
                 ..Default::default()
             }),
+            semantic_tokens_provider: Some(
+                SemanticTokensServerCapabilities::SemanticTokensOptions(SemanticTokensOptions {
+                    legend: semantic_tokens_legend(),
+                    range: Some(true),
+                    full: Some(SemanticTokensFullOptions::Bool(true)),
+                    work_done_progress_options: WorkDoneProgressOptions::default(),
+                }),
+            ),
             ..ServerCapabilities::default()
         }
     }
@@ -140,12 +194,29 @@ impl Server {
         self.workspace.environment.client_capabilities = Arc::new(params.capabilities);
         self.workspace.environment.client_info = params.client_info.map(Arc::new);
 
+        let folder_uris: Vec<Url> = match params.workspace_folders {
+            Some(folders) => folders.into_iter().map(|folder| folder.uri).collect(),
+            None => params.root_uri.into_iter().collect(),
+        };
+
+        self.workspace.environment.workspace_folders = Arc::new(
+            folder_uris
+                .iter()
+                .filter_map(|uri| uri.to_file_path().ok())
+                .map(|path| WorkspaceFolder {
+                    path,
+                    options: Arc::default(),
+                })
+                .collect(),
+        );
+
         let result = InitializeResult {
             capabilities: self.capabilities(),
             server_info: Some(ServerInfo {
                 name: "TexLab".to_owned(),
                 version: Some(env!("CARGO_PKG_VERSION").to_owned()),
             }),
+            offset_encoding: None,
         };
         self.connection
             .initialize_finish(id, serde_json::to_value(result)?)?;
@@ -155,19 +226,39 @@ impl Server {
                 let distro = Distribution::detect();
                 info!("Detected distribution: {}", distro.kind);
 
+                let resolver = distro.resolver.clone();
                 server
                     .internal_tx
                     .send(InternalMessage::SetDistro(distro))
                     .unwrap();
+
+                if let Err(why) = server.index_components(&resolver) {
+                    warn!("Failed to index the installed packages: {}", why);
+                }
+            });
+
+            let internal_tx = self.internal_tx.clone();
+            let root = &self.workspace.environment.current_directory;
+            let result = ipc::spawn_server(root, move |target| {
+                let _ = internal_tx.send(InternalMessage::ShowDocument(target));
             });
+
+            match result {
+                Ok(ipc_server) => self.ipc_server = Some(Arc::new(ipc_server)),
+                Err(why) => warn!("Unable to listen for inverse search requests: {}", why),
+            };
         }
 
         self.register_diagnostics_handler();
 
+        let current_dir = Arc::clone(&self.workspace.environment.current_directory);
+        self.discover_project_config(&current_dir)?;
+
         self.spawn(move |server| {
             server.register_config_capability();
             server.register_file_watching();
             let _ = server.pull_config();
+            let _ = server.internal_tx.send(InternalMessage::IndexWorkspace);
         });
 
         Ok(())
@@ -181,10 +272,16 @@ impl Server {
             .has_file_watching_support()
         {
             let options = DidChangeWatchedFilesRegistrationOptions {
-                watchers: vec![FileSystemWatcher {
-                    glob_pattern: "**/*.{aux,log}".into(),
-                    kind: Some(WatchKind::Create | WatchKind::Change | WatchKind::Delete),
-                }],
+                watchers: vec![
+                    FileSystemWatcher {
+                        glob_pattern: "**/*.{tex,bib,sty,cls,aux,bbl,log,blg}".into(),
+                        kind: Some(WatchKind::Create | WatchKind::Change | WatchKind::Delete),
+                    },
+                    FileSystemWatcher {
+                        glob_pattern: format!("**/{{{}}}", PROJECT_CONFIG_FILE_NAMES.join(",")),
+                        kind: Some(WatchKind::Create | WatchKind::Change | WatchKind::Delete),
+                    },
+                ],
             };
 
             let reg = Registration {
@@ -247,6 +344,7 @@ impl Server {
                 match event {
                     WorkspaceEvent::Changed(workspace, document) => {
                         diagnostic_manager.push_syntax(&workspace, &document.uri);
+                        diagnostic_manager.push_references(&workspace, &document.uri);
                         let delay = workspace.environment.options.diagnostics_delay;
                         diagnostic_tx
                             .send(workspace, Duration::from_millis(delay))
@@ -274,23 +372,37 @@ impl Server {
             return Ok(());
         }
 
-        let params = ConfigurationParams {
-            items: vec![ConfigurationItem {
-                section: Some("texlab".to_string()),
-                scope_uri: None,
-            }],
-        };
+        let folder_paths: Vec<_> = self
+            .workspace
+            .environment
+            .workspace_folders
+            .iter()
+            .map(|folder| folder.path.clone())
+            .collect();
+
+        let mut items = vec![ConfigurationItem {
+            section: Some("texlab".to_string()),
+            scope_uri: None,
+        }];
+
+        items.extend(folder_paths.iter().map(|path| ConfigurationItem {
+            section: Some("texlab".to_string()),
+            scope_uri: Url::from_directory_path(path).ok(),
+        }));
+
+        let params = ConfigurationParams { items };
 
         match send_request::<WorkspaceConfiguration>(
             &self.req_queue,
             &self.connection.sender,
             params,
         ) {
-            Ok(mut json) => {
-                let value = json.pop().expect("invalid configuration request");
-                let options = self.parse_options(value)?;
+            Ok(json) => {
+                let mut json = json.into_iter();
+                let value = json.next().expect("invalid configuration request");
+                let folder_settings = folder_paths.into_iter().zip(json).collect();
                 self.internal_tx
-                    .send(InternalMessage::SetOptions(Arc::new(options)))
+                    .send(InternalMessage::SetClientSettings(value, folder_settings))
                     .unwrap();
             }
             Err(why) => {
@@ -322,27 +434,283 @@ impl Server {
         Ok(options)
     }
 
+    /// Combines the settings of the client with the project configuration files.
+    /// Workspace folders without settings of their own use the global settings.
+    /// Every project directory uses the settings of the surrounding workspace folder
+    /// together with its own project file.
+    fn update_options(&mut self) -> Result<()> {
+        let options = self.parse_options((*self.client_settings).clone())?;
+        self.workspace.environment.options = Arc::new(options);
+
+        let mut folders = self.workspace.environment.workspace_folders.to_vec();
+        for folder in &mut folders {
+            folder.options = match self.folder_settings.get(&folder.path) {
+                Some(settings) => Arc::new(self.parse_options(settings.clone())?),
+                None => Arc::clone(&self.workspace.environment.options),
+            };
+        }
+
+        self.workspace.environment.workspace_folders = Arc::new(folders);
+
+        let mut projects = Vec::new();
+        for (directory, config) in self.project_configs.iter() {
+            let settings = config.apply(self.client_settings_in(directory));
+            projects.push(WorkspaceFolder {
+                path: directory.clone(),
+                options: Arc::new(self.parse_options(settings)?),
+            });
+        }
+
+        self.workspace.environment.project_folders = Arc::new(projects);
+        self.reparse_all()
+    }
+
+    fn client_settings_in(&self, directory: &Path) -> &serde_json::Value {
+        self.workspace
+            .environment
+            .workspace_folders
+            .iter()
+            .filter(|folder| directory.starts_with(&folder.path))
+            .max_by_key(|folder| folder.path.components().count())
+            .and_then(|folder| self.folder_settings.get(&folder.path))
+            .unwrap_or(&self.client_settings)
+    }
+
+    fn discover_project_config(&mut self, directory: &Path) -> Result<()> {
+        match ProjectConfig::find(directory) {
+            Some(path) if !self.is_project_config_loaded(&path) => self.load_project_config(path),
+            _ => Ok(()),
+        }
+    }
+
+    fn is_project_config_loaded(&self, path: &Path) -> bool {
+        path.parent()
+            .and_then(|directory| self.project_configs.get(directory))
+            .map_or(false, |config| config.path == path)
+    }
+
+    fn load_project_config(&mut self, path: PathBuf) -> Result<()> {
+        match ProjectConfig::load(path.clone()) {
+            Ok(config) => {
+                info!("Loaded project configuration: {}", path.display());
+                let directory = path.parent().unwrap_or(&path).to_path_buf();
+                Arc::make_mut(&mut self.project_configs).insert(directory, Arc::new(config));
+                self.update_options()?;
+            }
+            Err(why) => {
+                send_notification::<ShowMessage>(
+                    &self.connection.sender,
+                    ShowMessageParams {
+                        message: format!(
+                            "The project configuration {} is invalid.\nDetails: {why}",
+                            path.display()
+                        ),
+                        typ: MessageType::WARNING,
+                    },
+                )?;
+            }
+        };
+
+        Ok(())
+    }
+
+    /// Returns the given workspace folders as well as their root directories.
+    /// Without workspace folders, the global root directory is used instead.
+    fn find_index_directories(&self, folder_paths: Option<&[PathBuf]>) -> Vec<PathBuf> {
+        let environment = &self.workspace.environment;
+        let mut directories = Vec::new();
+        if environment.workspace_folders.is_empty() {
+            directories.extend(
+                environment
+                    .options
+                    .root_directory
+                    .as_ref()
+                    .map(|root_dir| environment.current_directory.join(root_dir)),
+            );
+        }
+
+        for folder in environment.workspace_folders.iter().filter(|folder| {
+            folder_paths.map_or(true, |folder_paths| folder_paths.contains(&folder.path))
+        }) {
+            directories.push(folder.path.clone());
+            directories.extend(
+                folder
+                    .options
+                    .root_directory
+                    .as_ref()
+                    .map(|root_dir| folder.path.join(root_dir)),
+            );
+        }
+
+        directories
+    }
+
+    fn spawn_indexer(&self, directories: Vec<PathBuf>) {
+        if directories.is_empty() {
+            return;
+        }
+
+        self.spawn(move |server| {
+            if let Err(why) = server.index_workspace(&directories) {
+                warn!("Failed to index the workspace: {}", why);
+            }
+        });
+    }
+
+    /// Parses all LaTeX and BibTeX files inside of the given directories
+    /// so that the workspace is complete before the documents get opened.
+    fn index_workspace(&self, directories: &[PathBuf]) -> Result<()> {
+        let environment = &self.workspace.environment;
+
+        let token = format!("texlab-indexing-{}", Uuid::new_v4());
+        let progress_reporter = ProgressReporter {
+            supports_progress: environment
+                .client_capabilities
+                .has_work_done_progress_support(),
+            req_queue: &self.req_queue,
+            lsp_sender: self.connection.sender.clone(),
+            token: &token,
+            title: "Indexing",
+            cancellable: false,
+        };
+        progress_reporter.start(None, Some(0))?;
+
+        let paths = crawler::find_source_files(directories);
+        let file_count = paths.len();
+
+        // The files are parsed on a separate pool so that large workspaces
+        // neither block the pool of the server nor take longer than necessary.
+        let pool = threadpool::Builder::new()
+            .thread_name("texlab-indexer".into())
+            .build();
+
+        let (document_tx, document_rx) = crossbeam_channel::unbounded();
+        for path in paths {
+            let environment = environment.clone();
+            let document_tx = document_tx.clone();
+            pool.execute(move || {
+                let _ = document_tx.send(parse_source_file(&environment, &path));
+            });
+        }
+
+        drop(document_tx);
+
+        let mut documents = Vec::new();
+        let mut last_percentage = None;
+        for (i, document) in document_rx.iter().enumerate() {
+            let count = i + 1;
+            let percentage = (count * 100 / file_count) as u32;
+            if last_percentage != Some(percentage) {
+                last_percentage = Some(percentage);
+                progress_reporter
+                    .report(format!("{}/{} files", count, file_count), Some(percentage))?;
+            }
+
+            documents.extend(document);
+        }
+
+        info!("Indexed {} files in the workspace", documents.len());
+        self.internal_tx
+            .send(InternalMessage::AddDocuments(documents))?;
+        Ok(())
+    }
+
+    /// Extracts the commands and environments of the installed and project-local packages
+    /// to complement the bundled component database.
+    fn index_components(&self, resolver: &Resolver) -> Result<()> {
+        let environment = &self.workspace.environment;
+        let token = format!("texlab-components-{}", Uuid::new_v4());
+        let progress_reporter = ProgressReporter {
+            supports_progress: environment
+                .client_capabilities
+                .has_work_done_progress_support(),
+            req_queue: &self.req_queue,
+            lsp_sender: self.connection.sender.clone(),
+            token: &token,
+            title: "Indexing packages",
+            cancellable: false,
+        };
+        progress_reporter.start(None, None)?;
+
+        let folder_paths: Vec<_> = environment
+            .workspace_folders
+            .iter()
+            .map(|folder| folder.path.clone())
+            .collect();
+
+        let local_files = crawler::find_source_files(&folder_paths);
+        let component_db = component_db::index_components(
+            &COMPONENT_DATABASE,
+            resolver,
+            &local_files,
+            &component_db::default_cache_path(),
+        );
+
+        info!("Indexed {} components", component_db.components.len());
+        self.internal_tx
+            .send(InternalMessage::SetComponentDatabase(component_db))?;
+        Ok(())
+    }
+
+    fn add_documents(&mut self, documents: Vec<Document>) -> Result<()> {
+        let documents = self.workspace.extend(documents);
+        for document in &documents {
+            self.diagnostic_manager
+                .push_syntax(&self.workspace, &document.uri);
+        }
+
+        let mut visited = FxHashSet::default();
+        for document in &documents {
+            if visited.insert(Arc::clone(&document.uri)) {
+                let slice = self.workspace.slice(&document.uri);
+                visited.extend(slice.documents_by_uri.into_keys());
+                self.diagnostic_manager
+                    .push_references(&self.workspace, &document.uri);
+            }
+        }
+
+        let delay = self.workspace.environment.options.diagnostics_delay;
+        self.diagnostic_tx
+            .send(self.workspace.clone(), Duration::from_millis(delay))?;
+        Ok(())
+    }
+
     fn cancel(&self, params: CancelParams) -> Result<()> {
         let id = match params.id {
             NumberOrString::Number(id) => RequestId::from(id),
             NumberOrString::String(id) => RequestId::from(id),
         };
 
+        self.build_engine.cancel_request(&id);
+
         let mut req_queue = self.req_queue.lock().unwrap();
         req_queue.incoming.complete(id);
 
         Ok(())
     }
 
+    fn cancel_progress(&self, params: WorkDoneProgressCancelParams) -> Result<()> {
+        self.build_engine.cancel_progress(&params.token);
+        Ok(())
+    }
+
     fn did_change_watched_files(&mut self, params: DidChangeWatchedFilesParams) -> Result<()> {
         for change in params.changes {
             if let Ok(path) = change.uri.to_file_path() {
+                if ProjectConfig::is_project_config(&path) {
+                    self.project_config_changed(path, change.typ)?;
+                    continue;
+                }
+
                 match change.typ {
                     FileChangeType::CREATED | FileChangeType::CHANGED => {
-                        self.workspace.reload(path)?;
+                        // The file may have been removed again, for example during `git checkout`.
+                        if let Err(why) = self.workspace.reload(path) {
+                            warn!("Unable to reload {}: {}", change.uri, why);
+                        }
                     }
                     FileChangeType::DELETED => {
-                        self.workspace.documents_by_uri.remove(&change.uri);
+                        self.remove_document(&change.uri)?;
                     }
                     _ => {}
                 }
@@ -352,6 +720,86 @@ impl Server {
         Ok(())
     }
 
+    /// Drops a closed document that has been deleted from disk and
+    /// updates the diagnostics of the documents that depended on it.
+    fn remove_document(&mut self, uri: &Url) -> Result<()> {
+        if self.workspace.is_open(uri) {
+            return Ok(());
+        }
+
+        let dependents: Vec<_> = self
+            .workspace
+            .slice(uri)
+            .documents_by_uri
+            .into_keys()
+            .filter(|dependent| dependent.as_ref() != uri)
+            .collect();
+
+        if self.workspace.documents_by_uri.remove(uri).is_none() {
+            return Ok(());
+        }
+
+        self.diagnostic_manager.remove(uri);
+        send_notification::<PublishDiagnostics>(
+            &self.connection.sender,
+            PublishDiagnosticsParams {
+                uri: uri.clone(),
+                version: None,
+                diagnostics: Vec::new(),
+            },
+        )?;
+
+        for dependent in &dependents {
+            self.diagnostic_manager
+                .push_references(&self.workspace, dependent);
+        }
+
+        let delay = self.workspace.environment.options.diagnostics_delay;
+        self.diagnostic_tx
+            .send(self.workspace.clone(), Duration::from_millis(delay))?;
+        Ok(())
+    }
+
+    fn project_config_changed(&mut self, path: PathBuf, typ: FileChangeType) -> Result<()> {
+        let directory = match path.parent() {
+            Some(directory) => directory.to_path_buf(),
+            None => return Ok(()),
+        };
+
+        match typ {
+            FileChangeType::CREATED | FileChangeType::CHANGED
+                if self.project_configs.contains_key(&directory)
+                    || self.is_project_config_used(&path) =>
+            {
+                self.load_project_config(path)?;
+            }
+            FileChangeType::DELETED if self.is_project_config_loaded(&path) => {
+                Arc::make_mut(&mut self.project_configs).remove(&directory);
+                self.update_options()?;
+                // Another project file might take over, for example in a parent directory.
+                self.discover_project_config(&directory)?;
+            }
+            _ => {}
+        };
+
+        Ok(())
+    }
+
+    /// Checks if the project file applies to the current directory or one of the open documents.
+    fn is_project_config_used(&self, path: &Path) -> bool {
+        let open_directories = self
+            .workspace
+            .documents_by_uri
+            .keys()
+            .filter(|uri| self.workspace.is_open(uri))
+            .filter_map(|uri| uri.to_file_path().ok())
+            .filter_map(|file| file.parent().map(Path::to_path_buf));
+
+        std::iter::once(self.workspace.environment.current_directory.to_path_buf())
+            .chain(open_directories)
+            .any(|directory| ProjectConfig::find(&directory).as_deref() == Some(path))
+    }
+
     fn did_change_configuration(&mut self, params: DidChangeConfigurationParams) -> Result<()> {
         if self
             .workspace
@@ -363,17 +811,94 @@ impl Server {
                 let _ = server.pull_config();
             });
         } else {
-            let options = self.parse_options(params.settings)?;
-            self.workspace.environment.options = Arc::new(options);
-            self.reparse_all()?;
+            self.client_settings = Arc::new(params.settings);
+            self.update_options()?;
+        }
+
+        Ok(())
+    }
+
+    fn did_change_workspace_folders(
+        &mut self,
+        params: DidChangeWorkspaceFoldersParams,
+    ) -> Result<()> {
+        let to_path = |folder: lsp_types::WorkspaceFolder| folder.uri.to_file_path().ok();
+        let removed: Vec<_> = params
+            .event
+            .removed
+            .into_iter()
+            .filter_map(to_path)
+            .collect();
+        let added: Vec<_> = params.event.added.into_iter().filter_map(to_path).collect();
+
+        let mut folders = self.workspace.environment.workspace_folders.to_vec();
+        folders.retain(|folder| !removed.contains(&folder.path) && !added.contains(&folder.path));
+        folders.extend(added.iter().map(|path| WorkspaceFolder {
+            path: path.clone(),
+            options: Arc::clone(&self.workspace.environment.options),
+        }));
+
+        self.workspace.environment.workspace_folders = Arc::new(folders);
+
+        let removed_uris: Vec<_> = self
+            .workspace
+            .documents_by_uri
+            .keys()
+            .filter(|uri| {
+                uri.to_file_path().map_or(false, |path| {
+                    removed.iter().any(|folder| path.starts_with(folder))
+                        && !self.is_inside_workspace_folder(&path)
+                })
+            })
+            .cloned()
+            .collect();
+
+        for uri in removed_uris {
+            self.remove_document(&uri)?;
+        }
+
+        if self
+            .workspace
+            .environment
+            .client_capabilities
+            .has_pull_configuration_support()
+        {
+            self.spawn(move |server| {
+                let _ = server.pull_config();
+                let _ = server
+                    .internal_tx
+                    .send(InternalMessage::IndexFolders(added));
+            });
+        } else {
+            self.update_options()?;
+            self.spawn_indexer(self.find_index_directories(Some(&added)));
         }
 
         Ok(())
     }
 
+    /// Returns the options of the workspace folder that contains the document.
+    fn options(&self, uri: &Url) -> Arc<Options> {
+        self.workspace.environment.scoped(uri).options
+    }
+
+    fn is_inside_workspace_folder(&self, path: &Path) -> bool {
+        self.workspace
+            .environment
+            .workspace_folders
+            .iter()
+            .any(|folder| path.starts_with(&folder.path))
+    }
+
     fn did_open(&mut self, params: DidOpenTextDocumentParams) -> Result<()> {
         let language_id = &params.text_document.language_id;
         let language = DocumentLanguage::by_language_id(language_id);
+        if let Ok(path) = params.text_document.uri.to_file_path() {
+            if let Some(directory) = path.parent() {
+                self.discover_project_config(directory)?;
+            }
+        }
+
         let document = self.workspace.open(
             Arc::new(params.text_document.uri),
             Arc::new(params.text_document.text),
@@ -382,7 +907,7 @@ impl Server {
 
         self.workspace.viewport.insert(Arc::clone(&document.uri));
 
-        if self.workspace.environment.options.chktex.on_open_and_save {
+        if self.options(&document.uri).chktex.on_open_and_save {
             self.run_chktex(document);
         }
 
@@ -416,7 +941,7 @@ impl Server {
                     ),
                 );
 
-                if self.workspace.environment.options.chktex.on_edit {
+                if self.options(&uri).chktex.on_edit {
                     self.run_chktex(new_document);
                 };
             }
@@ -438,7 +963,7 @@ impl Server {
             .workspace
             .documents_by_uri
             .get(&uri)
-            .filter(|_| self.workspace.environment.options.build.on_save)
+            .filter(|_| self.options(&uri).build.on_save)
             .map(|document| {
                 self.feature_request(
                     Arc::clone(&document.uri),
@@ -451,7 +976,7 @@ impl Server {
             self.spawn(move |server| {
                 server
                     .build_engine
-                    .build(request, &server.req_queue, &server.connection.sender)
+                    .build(request, None, &server.req_queue, &server.connection.sender)
                     .unwrap_or_else(|why| {
                         error!("Build failed: {}", why);
                         BuildResult {
@@ -465,7 +990,7 @@ impl Server {
             .workspace
             .documents_by_uri
             .get(&uri)
-            .filter(|_| self.workspace.environment.options.chktex.on_open_and_save)
+            .filter(|_| self.options(&uri).chktex.on_open_and_save)
             .cloned()
         {
             self.run_chktex(document);
@@ -573,15 +1098,15 @@ impl Server {
     fn completion_resolve(&self, id: RequestId, mut item: CompletionItem) -> Result<()> {
         use rowan::ast::AstNode;
 
-        use crate::{
-            citation, component_db::COMPONENT_DATABASE, features::CompletionItemData,
-            syntax::bibtex,
-        };
+        use crate::{citation, features::CompletionItemData, syntax::bibtex};
 
         self.spawn(move |server| {
             match serde_json::from_value(item.data.clone().unwrap()).unwrap() {
                 CompletionItemData::Package | CompletionItemData::Class => {
-                    item.documentation = COMPONENT_DATABASE
+                    item.documentation = server
+                        .workspace
+                        .environment
+                        .component_db
                         .documentation(&item.label)
                         .map(Documentation::MarkupContent);
                 }
@@ -642,6 +1167,60 @@ impl Server {
         Ok(())
     }
 
+    fn prepare_call_hierarchy(
+        &self,
+        id: RequestId,
+        params: CallHierarchyPrepareParams,
+    ) -> Result<()> {
+        let uri = Arc::new(
+            params
+                .text_document_position_params
+                .text_document
+                .uri
+                .clone(),
+        );
+        self.handle_feature_request(id, params, uri, prepare_call_hierarchy)?;
+        Ok(())
+    }
+
+    fn incoming_calls(
+        &self,
+        id: RequestId,
+        params: CallHierarchyIncomingCallsParams,
+    ) -> Result<()> {
+        let uri = Arc::new(params.item.uri.clone());
+        self.handle_feature_request(id, params, uri, find_incoming_calls)?;
+        Ok(())
+    }
+
+    fn outgoing_calls(
+        &self,
+        id: RequestId,
+        params: CallHierarchyOutgoingCallsParams,
+    ) -> Result<()> {
+        let uri = Arc::new(params.item.uri.clone());
+        self.handle_feature_request(id, params, uri, find_outgoing_calls)?;
+        Ok(())
+    }
+
+    fn inlay_hints(&self, id: RequestId, params: InlayHintParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_inlay_hints)?;
+        Ok(())
+    }
+
+    fn signature_help(&self, id: RequestId, params: SignatureHelpParams) -> Result<()> {
+        let uri = Arc::new(
+            params
+                .text_document_position_params
+                .text_document
+                .uri
+                .clone(),
+        );
+        self.handle_feature_request(id, params, uri, find_signature_help)?;
+        Ok(())
+    }
+
     fn goto_definition(&self, id: RequestId, params: GotoDefinitionParams) -> Result<()> {
         let uri = Arc::new(
             params
@@ -684,6 +1263,28 @@ impl Server {
         Ok(())
     }
 
+    fn range_formatting(&self, id: RequestId, params: DocumentRangeFormattingParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, format_source_code_range)?;
+        Ok(())
+    }
+
+    fn on_type_formatting(
+        &self,
+        id: RequestId,
+        params: DocumentOnTypeFormattingParams,
+    ) -> Result<()> {
+        let uri = Arc::new(params.text_document_position.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, format_on_type)?;
+        Ok(())
+    }
+
+    fn code_action(&self, id: RequestId, params: CodeActionParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_code_actions)?;
+        Ok(())
+    }
+
     fn execute_command(&self, id: RequestId, params: ExecuteCommandParams) -> Result<()> {
         self.spawn(move |server| {
             let result = execute_command(&server.workspace, &params.command, params.arguments);
@@ -702,11 +1303,19 @@ impl Server {
         Ok(())
     }
 
+    fn semantic_tokens_full(&self, id: RequestId, params: SemanticTokensParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_semantic_tokens)?;
+        Ok(())
+    }
+
     fn semantic_tokens_range(
         &self,
-        _id: RequestId,
-        _params: SemanticTokensRangeParams,
+        id: RequestId,
+        params: SemanticTokensRangeParams,
     ) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_semantic_tokens_range)?;
         Ok(())
     }
 
@@ -715,9 +1324,10 @@ impl Server {
         let lsp_sender = self.connection.sender.clone();
         let req_queue = Arc::clone(&self.req_queue);
         let build_engine = Arc::clone(&self.build_engine);
+        let request_id = id.clone();
         self.handle_feature_request(id, params, uri, move |request| {
             build_engine
-                .build(request, &req_queue, &lsp_sender)
+                .build(request, Some(request_id), &req_queue, &lsp_sender)
                 .unwrap_or_else(|why| {
                     error!("Build failed: {}", why);
                     BuildResult {
@@ -738,6 +1348,65 @@ impl Server {
         Ok(())
     }
 
+    fn project_root(&self, id: RequestId, params: IncludeGraphParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_project_root)?;
+        Ok(())
+    }
+
+    fn include_tree(&self, id: RequestId, params: IncludeGraphParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_include_tree)?;
+        Ok(())
+    }
+
+    fn pdf_location(&self, id: RequestId, params: TextDocumentPositionParams) -> Result<()> {
+        let uri = Arc::new(params.text_document.uri.clone());
+        self.handle_feature_request(id, params, uri, find_pdf_location)?;
+        Ok(())
+    }
+
+    fn inverse_search(&self, id: RequestId, params: InverseSearchParams) -> Result<()> {
+        self.spawn(move |server| {
+            let result = execute_inverse_search(&params);
+            server
+                .connection
+                .sender
+                .send(lsp_server::Response::new_ok(id, result).into())
+                .unwrap();
+        });
+
+        Ok(())
+    }
+
+    fn show_document(&self, target: InverseSearchTarget) {
+        if !self
+            .workspace
+            .environment
+            .client_capabilities
+            .has_show_document_support()
+        {
+            warn!("The client does not support revealing {}", target.uri);
+            return;
+        }
+
+        self.spawn(move |server| {
+            let location = target.location();
+            let params = ShowDocumentParams {
+                uri: location.uri,
+                external: Some(false),
+                take_focus: Some(true),
+                selection: Some(location.range),
+            };
+
+            if let Err(why) =
+                send_request::<ShowDocument>(&server.req_queue, &server.connection.sender, params)
+            {
+                error!("Failed to reveal {}: {}", target.uri, why);
+            }
+        });
+    }
+
     fn reparse_all(&mut self) -> Result<()> {
         for document in self
             .workspace
@@ -772,6 +1441,19 @@ impl Server {
                                 .on::<FoldingRangeRequest, _>(|id, params| self.folding_range(id, params))?
                                 .on::<References, _>(|id, params| self.references(id, params))?
                                 .on::<HoverRequest, _>(|id, params| self.hover(id, params))?
+                                .on::<InlayHintRequest, _>(|id, params| self.inlay_hints(id, params))?
+                                .on::<CallHierarchyPrepare, _>(|id, params| {
+                                    self.prepare_call_hierarchy(id, params)
+                                })?
+                                .on::<CallHierarchyIncomingCalls, _>(|id, params| {
+                                    self.incoming_calls(id, params)
+                                })?
+                                .on::<CallHierarchyOutgoingCalls, _>(|id, params| {
+                                    self.outgoing_calls(id, params)
+                                })?
+                                .on::<SignatureHelpRequest, _>(|id, params| {
+                                    self.signature_help(id, params)
+                                })?
                                 .on::<DocumentSymbolRequest, _>(|id, params| {
                                     self.document_symbols(id, params)
                                 })?
@@ -795,11 +1477,33 @@ impl Server {
                                     self.document_highlight(id, params)
                                 })?
                                 .on::<Formatting, _>(|id, params| self.formatting(id, params))?
+                                .on::<RangeFormatting, _>(|id, params| {
+                                    self.range_formatting(id, params)
+                                })?
+                                .on::<OnTypeFormatting, _>(|id, params| {
+                                    self.on_type_formatting(id, params)
+                                })?
+                                .on::<CodeActionRequest, _>(|id, params| self.code_action(id, params))?
                                 .on::<BuildRequest, _>(|id, params| self.build(id, params))?
                                 .on::<ForwardSearchRequest, _>(|id, params| {
                                     self.forward_search(id, params)
                                 })?
+                                .on::<PdfLocationRequest, _>(|id, params| {
+                                    self.pdf_location(id, params)
+                                })?
+                                .on::<InverseSearchRequest, _>(|id, params| {
+                                    self.inverse_search(id, params)
+                                })?
+                                .on::<ProjectRootRequest, _>(|id, params| {
+                                    self.project_root(id, params)
+                                })?
+                                .on::<IncludeTreeRequest, _>(|id, params| {
+                                    self.include_tree(id, params)
+                                })?
                                 .on::<ExecuteCommand,_>(|id, params| self.execute_command(id, params))?
+                                .on::<SemanticTokensFullRequest, _>(|id, params| {
+                                    self.semantic_tokens_full(id, params)
+                                })?
                                 .on::<SemanticTokensRangeRequest, _>(|id, params| {
                                     self.semantic_tokens_range(id, params)
                                 })?
@@ -811,12 +1515,18 @@ impl Server {
                         Message::Notification(notification) => {
                             NotificationDispatcher::new(notification)
                                 .on::<Cancel, _>(|params| self.cancel(params))?
+                                .on::<WorkDoneProgressCancel, _>(|params| {
+                                    self.cancel_progress(params)
+                                })?
                                 .on::<DidChangeConfiguration, _>(|params| {
                                     self.did_change_configuration(params)
                                 })?
                                 .on::<DidChangeWatchedFiles, _>(|params| {
                                     self.did_change_watched_files(params)
                                 })?
+                                .on::<DidChangeWorkspaceFolders, _>(|params| {
+                                    self.did_change_workspace_folders(params)
+                                })?
                                 .on::<DidOpenTextDocument, _>(|params| self.did_open(params))?
                                 .on::<DidChangeTextDocument, _>(|params| self.did_change(params))?
                                 .on::<DidSaveTextDocument, _>(|params| self.did_save(params))?
@@ -841,9 +1551,25 @@ impl Server {
                             self.workspace.environment.resolver = Arc::new(distro.resolver);
                             self.reparse_all()?;
                         }
-                        InternalMessage::SetOptions(options) => {
-                            self.workspace.environment.options = options;
-                            self.reparse_all()?;
+                        InternalMessage::SetComponentDatabase(component_db) => {
+                            self.workspace.environment.component_db = Arc::new(component_db);
+                        }
+                        InternalMessage::SetClientSettings(settings, folder_settings) => {
+                            self.client_settings = Arc::new(settings);
+                            self.folder_settings = Arc::new(folder_settings);
+                            self.update_options()?;
+                        }
+                        InternalMessage::IndexWorkspace => {
+                            self.spawn_indexer(self.find_index_directories(None));
+                        }
+                        InternalMessage::IndexFolders(folder_paths) => {
+                            self.spawn_indexer(self.find_index_directories(Some(&folder_paths)));
+                        }
+                        InternalMessage::AddDocuments(documents) => {
+                            self.add_documents(documents)?;
+                        }
+                        InternalMessage::ShowDocument(target) => {
+                            self.show_document(target);
                         }
                     };
                 }
@@ -881,7 +1607,10 @@ fn publish_diagnostics(
     workspace: &Workspace,
 ) -> Result<()> {
     for document in workspace.documents_by_uri.values() {
-        if matches!(document.data, DocumentData::BuildLog(_)) {
+        if matches!(
+            document.data,
+            DocumentData::BuildLog(_) | DocumentData::BibLog(_)
+        ) {
             continue;
         }
 
@@ -899,6 +1628,15 @@ fn publish_diagnostics(
     Ok(())
 }
 
+fn parse_source_file(environment: &Environment, path: &Path) -> Option<Document> {
+    let uri = Arc::new(Url::from_file_path(path).ok()?);
+    let language = DocumentLanguage::by_path(path).unwrap_or(DocumentLanguage::Latex);
+    let data = std::fs::read(path).ok()?;
+    let text = Arc::new(String::from_utf8_lossy(&data).into_owned());
+    let environment = environment.scoped(&uri);
+    Some(Document::parse(&environment, uri, text, language))
+}
+
 fn apply_document_edit(old_text: &mut String, changes: Vec<TextDocumentContentChangeEvent>) {
     for change in changes {
         let line_index = LineIndex::new(old_text);
@@ -933,3 +1671,43 @@ impl lsp_types::request::Request for ForwardSearchRequest {
 
     const METHOD: &'static str = "textDocument/forwardSearch";
 }
+
+struct PdfLocationRequest;
+
+impl lsp_types::request::Request for PdfLocationRequest {
+    type Params = TextDocumentPositionParams;
+
+    type Result = Option<PdfLocation>;
+
+    const METHOD: &'static str = "textDocument/pdfLocation";
+}
+
+struct InverseSearchRequest;
+
+impl lsp_types::request::Request for InverseSearchRequest {
+    type Params = InverseSearchParams;
+
+    type Result = Option<Location>;
+
+    const METHOD: &'static str = "textDocument/inverseSearch";
+}
+
+struct ProjectRootRequest;
+
+impl lsp_types::request::Request for ProjectRootRequest {
+    type Params = IncludeGraphParams;
+
+    type Result = Option<Url>;
+
+    const METHOD: &'static str = "textDocument/projectRoot";
+}
+
+struct IncludeTreeRequest;
+
+impl lsp_types::request::Request for IncludeTreeRequest {
+    type Params = IncludeGraphParams;
+
+    type Result = Option<IncludeTree>;
+
+    const METHOD: &'static str = "textDocument/includeTree";
+}
@@ -0,0 +1,385 @@
+use std::{
+    cmp::Ordering,
+    fs::File,
+    io::{self, BufReader, Read},
+    path::{Path, PathBuf},
+};
+
+use flate2::read::GzDecoder;
+use rustc_hash::FxHashMap;
+
+/// The number of scaled points per big point.
+const SP_PER_BP: f64 = 65781.76;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum SyncTexNodeKind {
+    VerticalBox,
+    HorizontalBox,
+    VoidBox,
+    Point,
+}
+
+impl SyncTexNodeKind {
+    fn is_box(self) -> bool {
+        matches!(self, Self::VerticalBox | Self::HorizontalBox)
+    }
+}
+
+/// A rectangle on a PDF page in big points measured from the top left corner.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct SyncTexRect {
+    pub left: f64,
+    pub top: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl SyncTexRect {
+    fn distance(&self, x: f64, y: f64) -> f64 {
+        let dx = (self.left - x).max(x - (self.left + self.width)).max(0.0);
+        let dy = (self.top - y).max(y - (self.top + self.height)).max(0.0);
+        (dx * dx + dy * dy).sqrt()
+    }
+
+    fn contains(&self, other: &Self) -> bool {
+        self.left <= other.left
+            && self.top <= other.top
+            && other.left + other.width <= self.left + self.width
+            && other.top + other.height <= self.top + self.height
+    }
+
+    fn area(&self) -> f64 {
+        self.width * self.height
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let left = self.left.min(other.left);
+        let top = self.top.min(other.top);
+        let right = (self.left + self.width).max(other.left + other.width);
+        let bottom = (self.top + self.height).max(other.top + other.height);
+        Self {
+            left,
+            top,
+            width: right - left,
+            height: bottom - top,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct SyncTexNode {
+    pub kind: SyncTexNodeKind,
+    pub page: u32,
+    pub tag: u32,
+    pub line: u32,
+    pub rect: SyncTexRect,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct SyncTexLocation<'a> {
+    pub path: &'a Path,
+    pub line: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct SyncTexPdfLocation {
+    pub page: u32,
+    pub rect: SyncTexRect,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct SyncTexFile {
+    pub inputs: FxHashMap<u32, PathBuf>,
+    pub nodes: Vec<SyncTexNode>,
+}
+
+impl SyncTexFile {
+    /// Reads the SyncTeX file that belongs to the given PDF file.
+    /// Relative input paths are resolved against the directory of the PDF file.
+    pub fn read(pdf_path: &Path) -> io::Result<Self> {
+        let mut text = String::new();
+        let gz_path = pdf_path.with_extension("synctex.gz");
+        if gz_path.exists() {
+            GzDecoder::new(BufReader::new(File::open(gz_path)?)).read_to_string(&mut text)?;
+        } else {
+            File::open(pdf_path.with_extension("synctex"))?.read_to_string(&mut text)?;
+        }
+
+        let mut file = Self::parse(&text);
+        if let Some(base_dir) = pdf_path.parent() {
+            for path in file.inputs.values_mut() {
+                if path.is_relative() {
+                    *path = base_dir.join(&path);
+                }
+            }
+        }
+
+        Ok(file)
+    }
+
+    pub fn parse(text: &str) -> Self {
+        Parser::default().parse(text)
+    }
+
+    /// Finds the source location of the point `(x, y)` on the given page (1-based).
+    pub fn inverse_search(&self, page: u32, x: f64, y: f64) -> Option<SyncTexLocation<'_>> {
+        let nodes: Vec<_> = self.nodes.iter().filter(|node| node.page == page).collect();
+
+        // Prefer the innermost box around the point and look for the closest record inside of it.
+        let container = nodes
+            .iter()
+            .filter(|node| node.kind.is_box() && node.rect.distance(x, y) == 0.0)
+            .min_by(|a, b| compare(a.rect.area(), b.rect.area()));
+
+        let node = nodes
+            .iter()
+            .filter(|node| !node.kind.is_box())
+            .filter(|node| container.map_or(true, |container| container.rect.contains(&node.rect)))
+            .min_by(|a, b| compare(a.rect.distance(x, y), b.rect.distance(x, y)))
+            .or(container)?;
+
+        let path = self.inputs.get(&node.tag)?;
+        Some(SyncTexLocation {
+            path,
+            line: node.line.saturating_sub(1),
+        })
+    }
+
+    /// Finds the area inside of the PDF file that belongs to the given (0-based) line.
+    /// If the line does not produce any output, the closest line after it is used instead.
+    pub fn forward_search(&self, path: &Path, line: u32) -> Option<SyncTexPdfLocation> {
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let tags: Vec<_> = self
+            .inputs
+            .iter()
+            .filter(|(_, input)| {
+                input.as_path() == path || input.canonicalize().map_or(false, |input| input == path)
+            })
+            .map(|(tag, _)| *tag)
+            .collect();
+
+        let line = line + 1;
+        let nodes: Vec<_> = self
+            .nodes
+            .iter()
+            .filter(|node| tags.contains(&node.tag) && node.page > 0)
+            .collect();
+
+        let best_line = nodes.iter().map(|node| node.line).min_by_key(|node_line| {
+            (
+                *node_line < line,
+                (i64::from(*node_line) - i64::from(line)).abs(),
+            )
+        })?;
+
+        let nodes: Vec<_> = nodes
+            .into_iter()
+            .filter(|node| node.line == best_line)
+            .collect();
+
+        let page = nodes.iter().map(|node| node.page).min()?;
+        let nodes = nodes.into_iter().filter(|node| node.page == page);
+        let rect = [
+            SyncTexNodeKind::HorizontalBox,
+            SyncTexNodeKind::VoidBox,
+            SyncTexNodeKind::Point,
+            SyncTexNodeKind::VerticalBox,
+        ]
+        .into_iter()
+        .find_map(|kind| {
+            nodes
+                .clone()
+                .filter(|node| node.kind == kind)
+                .map(|node| node.rect)
+                .reduce(|a, b| a.union(&b))
+        })?;
+
+        Some(SyncTexPdfLocation { page, rect })
+    }
+}
+
+fn compare(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b).unwrap_or(Ordering::Equal)
+}
+
+#[derive(Debug)]
+struct Parser {
+    file: SyncTexFile,
+    page: u32,
+    unit: f64,
+    magnification: f64,
+    x_offset: f64,
+    y_offset: f64,
+    previous: [f64; 5],
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Self {
+            file: SyncTexFile::default(),
+            page: 0,
+            unit: 1.0,
+            magnification: 1000.0,
+            x_offset: 0.0,
+            y_offset: 0.0,
+            previous: [0.0; 5],
+        }
+    }
+}
+
+impl Parser {
+    fn parse(mut self, text: &str) -> SyncTexFile {
+        for line in text.lines() {
+            if let Some(input) = line.strip_prefix("Input:") {
+                if let Some((tag, path)) = input.split_once(':') {
+                    if let Ok(tag) = tag.parse() {
+                        self.file.inputs.insert(tag, PathBuf::from(path));
+                    }
+                }
+            } else if let Some(value) = line.strip_prefix("Unit:") {
+                self.unit = value.trim().parse().unwrap_or(1.0);
+            } else if let Some(value) = line.strip_prefix("Magnification:") {
+                self.magnification = value.trim().parse().unwrap_or(1000.0);
+            } else if let Some(value) = line.strip_prefix("X Offset:") {
+                self.x_offset = value.trim().parse().unwrap_or(0.0);
+            } else if let Some(value) = line.strip_prefix("Y Offset:") {
+                self.y_offset = value.trim().parse().unwrap_or(0.0);
+            } else if let Some(page) = line.strip_prefix('{') {
+                self.page = page.parse().unwrap_or(self.page + 1);
+            } else if let Some(record) = line.strip_prefix('[') {
+                self.node(SyncTexNodeKind::VerticalBox, record);
+            } else if let Some(record) = line.strip_prefix('(') {
+                self.node(SyncTexNodeKind::HorizontalBox, record);
+            } else if let Some(record) = line.strip_prefix(['v', 'h']) {
+                self.node(SyncTexNodeKind::VoidBox, record);
+            } else if let Some(record) = line.strip_prefix(['x', 'k', 'g', '$']) {
+                self.node(SyncTexNodeKind::Point, record);
+            }
+        }
+
+        self.file
+    }
+
+    /// Parses a record of the form `tag,line(,column):x,y(:width(,height,depth))`.
+    fn node(&mut self, kind: SyncTexNodeKind, record: &str) -> Option<()> {
+        let mut parts = record.split(':');
+        let mut location = parts.next()?.split(',');
+        let tag = location.next()?.parse().ok()?;
+        let line = location.next()?.parse().ok()?;
+
+        let mut values = [0.0; 5];
+        for (i, value) in parts.flat_map(|part| part.split(',')).take(5).enumerate() {
+            values[i] = match value {
+                "=" => self.previous[i],
+                _ => value.parse().ok()?,
+            };
+        }
+
+        self.previous = values;
+        let scale = self.unit * self.magnification / 1000.0 / SP_PER_BP;
+        let [x, y, width, height, depth] = values;
+        let left = (x + self.x_offset) * scale;
+        let baseline = (y + self.y_offset) * scale;
+        let (left, width) = if width < 0.0 {
+            (left + width * scale, -width * scale)
+        } else {
+            (left, width * scale)
+        };
+
+        let rect = SyncTexRect {
+            left,
+            top: baseline - height * scale,
+            width,
+            height: (height + depth) * scale,
+        };
+
+        self.file.nodes.push(SyncTexNode {
+            kind,
+            page: self.page,
+            tag,
+            line,
+            rect,
+        });
+
+        Some(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    static SYNCTEX: &str = r#"SyncTeX Version:1
+Input:1:./main.tex
+Input:2:./chapter.tex
+Output:pdf
+Magnification:1000
+Unit:1
+X Offset:0
+Y Offset:0
+Content:
+!151
+{1
+[1,5:4736286,5799936:25137278,38630989,0
+(1,5:4736286,5799936:25137278,655360,0
+x1,5:4736286,5799936
+g1,5:6500000,5799936
+)
+(2,3:4736286,7499936:25137278,655360,0
+x2,3:4736286,7499936
+k2,4:9000000,7499936:0
+)
+]
+}1
+{2
+[1,9:4736286,5799936:25137278,38630989,0
+(1,9:4736286,5799936:25137278,655360,0
+x1,9:4736286,5799936
+)
+]
+}2
+Postamble:
+Count:12
+"#;
+
+    #[test]
+    fn inverse_search_first_line() {
+        let file = SyncTexFile::parse(SYNCTEX);
+        let location = file.inverse_search(1, 75.0, 85.0).unwrap();
+        assert_eq!(location.path, Path::new("./main.tex"));
+        assert_eq!(location.line, 4);
+    }
+
+    #[test]
+    fn inverse_search_nearest_record() {
+        let file = SyncTexFile::parse(SYNCTEX);
+        let location = file.inverse_search(1, 140.0, 110.0).unwrap();
+        assert_eq!(location.path, Path::new("./chapter.tex"));
+        assert_eq!(location.line, 3);
+    }
+
+    #[test]
+    fn forward_search_line() {
+        let file = SyncTexFile::parse(SYNCTEX);
+        let location = file.forward_search(Path::new("./chapter.tex"), 2).unwrap();
+        assert_eq!(location.page, 1);
+        assert_eq!(location.rect.left.round(), 72.0);
+        assert_eq!(location.rect.top.round(), 104.0);
+        assert_eq!(location.rect.height.round(), 10.0);
+    }
+
+    #[test]
+    fn forward_search_next_line() {
+        let file = SyncTexFile::parse(SYNCTEX);
+        let location = file.forward_search(Path::new("./main.tex"), 6).unwrap();
+        assert_eq!(location.page, 2);
+    }
+
+    #[test]
+    fn inverse_search_other_page() {
+        let file = SyncTexFile::parse(SYNCTEX);
+        let location = file.inverse_search(2, 75.0, 85.0).unwrap();
+        assert_eq!(location.path, Path::new("./main.tex"));
+        assert_eq!(location.line, 8);
+    }
+}
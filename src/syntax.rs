@@ -1,3 +1,4 @@
+pub mod bib_log;
 pub mod bibtex;
 pub mod build_log;
 pub mod latex;
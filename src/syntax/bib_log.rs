@@ -0,0 +1,261 @@
+use std::path::PathBuf;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::build_log::BuildErrorLevel;
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum BibLogMessageKind {
+    Generic,
+    MissingEntry(String),
+    DuplicateEntry(String),
+    Entry(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BibLogMessage {
+    pub level: BuildErrorLevel,
+    pub kind: BibLogMessageKind,
+    pub message: String,
+    pub file: Option<PathBuf>,
+    pub line: Option<u32>,
+    pub log_line: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Parse {
+    pub messages: Vec<BibLogMessage>,
+}
+
+static BIBER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^\[\d+\] [^>]*> (?P<level>WARN|ERROR) - (?P<msg>.*)$").unwrap());
+
+static BIBER_MISSING_ENTRY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^I didn't find a database entry for '(?P<key>[^']+)'").unwrap());
+
+static BIBER_DUPLICATE_ENTRY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^Duplicate entry key '(?P<key>[^']+)' in file '(?P<file>[^']+)'").unwrap()
+});
+
+static BIBER_SUBSYSTEM_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^BibTeX subsystem: (?P<file>.+?)(_\d+\.utf8)?, line (?P<line>\d+), (?P<msg>.*)$")
+        .unwrap()
+});
+
+static BIBER_ENTRY_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^(Datamodel: )?Entry '(?P<key>[^']+)' \((?P<file>[^)]+)\): (?P<msg>.*)$").unwrap()
+});
+
+static BIBTEX_WARNING_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^Warning--(?P<msg>.*)$").unwrap());
+
+static BIBTEX_MISSING_ENTRY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"^I didn't find a database entry for "(?P<key>[^"]+)""#).unwrap());
+
+static BIBTEX_ENTRY_WARNING_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<msg>.*) in (?P<key>\S+)$").unwrap());
+
+static BIBTEX_ERROR_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(?P<msg>.*)---line (?P<line>\d+) of file (?P<file>.*)$").unwrap());
+
+static BIBTEX_FILE_ERROR_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^I couldn't open (database|style|auxiliary) file").unwrap());
+
+static BIBTEX_CONTEXT_KEY_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^ : @\w+\s*[{(]\s*(?P<key>[^,\s]+)").unwrap());
+
+/// Parses the log file of either BibTeX or Biber.
+pub fn parse(log: &str) -> Parse {
+    let lines: Vec<_> = log.lines().collect();
+    let is_biber = lines.first().map_or(false, |line| {
+        BIBER_REGEX.is_match(line) || line.contains("INFO - ")
+    });
+
+    let messages = lines
+        .iter()
+        .enumerate()
+        .filter_map(|(index, line)| {
+            if is_biber {
+                parse_biber_line(line)
+            } else {
+                parse_bibtex_line(line, lines.get(index + 1).copied())
+            }
+            .map(|mut message| {
+                message.log_line = index as u32;
+                message
+            })
+        })
+        .collect();
+
+    Parse { messages }
+}
+
+fn parse_biber_line(line: &str) -> Option<BibLogMessage> {
+    let captures = BIBER_REGEX.captures(line)?;
+    let level = match &captures["level"] {
+        "ERROR" => BuildErrorLevel::Error,
+        _ => BuildErrorLevel::Warning,
+    };
+
+    let text = &captures["msg"];
+    let mut message = BibLogMessage {
+        level,
+        kind: BibLogMessageKind::Generic,
+        message: text.to_string(),
+        file: None,
+        line: None,
+        log_line: 0,
+    };
+
+    if let Some(captures) = BIBER_MISSING_ENTRY_REGEX.captures(text) {
+        message.kind = BibLogMessageKind::MissingEntry(captures["key"].to_string());
+    } else if let Some(captures) = BIBER_DUPLICATE_ENTRY_REGEX.captures(text) {
+        message.kind = BibLogMessageKind::DuplicateEntry(captures["key"].to_string());
+        message.file = Some(file_name(&captures["file"]));
+    } else if let Some(captures) = BIBER_SUBSYSTEM_REGEX.captures(text) {
+        message.message = captures["msg"].to_string();
+        message.file = Some(file_name(&captures["file"]));
+        message.line = captures["line"]
+            .parse::<u32>()
+            .ok()
+            .and_then(|line| line.checked_sub(1));
+    } else if let Some(captures) = BIBER_ENTRY_REGEX.captures(text) {
+        message.kind = BibLogMessageKind::Entry(captures["key"].to_string());
+        message.message = captures["msg"].to_string();
+        message.file = Some(file_name(&captures["file"]));
+    }
+
+    Some(message)
+}
+
+fn parse_bibtex_line(line: &str, next_line: Option<&str>) -> Option<BibLogMessage> {
+    if let Some(captures) = BIBTEX_WARNING_REGEX.captures(line) {
+        let text = &captures["msg"];
+        let kind = if let Some(captures) = BIBTEX_MISSING_ENTRY_REGEX.captures(text) {
+            BibLogMessageKind::MissingEntry(captures["key"].to_string())
+        } else if let Some(captures) = BIBTEX_ENTRY_WARNING_REGEX.captures(text) {
+            BibLogMessageKind::Entry(captures["key"].to_string())
+        } else {
+            BibLogMessageKind::Generic
+        };
+
+        Some(BibLogMessage {
+            level: BuildErrorLevel::Warning,
+            kind,
+            message: text.to_string(),
+            file: None,
+            line: None,
+            log_line: 0,
+        })
+    } else if let Some(captures) = BIBTEX_ERROR_REGEX.captures(line) {
+        let text = &captures["msg"];
+        let kind = next_line
+            .filter(|_| text == "Repeated entry")
+            .and_then(|line| BIBTEX_CONTEXT_KEY_REGEX.captures(line))
+            .map_or(BibLogMessageKind::Generic, |captures| {
+                BibLogMessageKind::DuplicateEntry(captures["key"].to_string())
+            });
+
+        Some(BibLogMessage {
+            level: BuildErrorLevel::Error,
+            kind,
+            message: text.to_string(),
+            file: Some(file_name(&captures["file"])),
+            line: captures["line"]
+                .parse::<u32>()
+                .ok()
+                .and_then(|line| line.checked_sub(1)),
+            log_line: 0,
+        })
+    } else if BIBTEX_FILE_ERROR_REGEX.is_match(line) {
+        Some(BibLogMessage {
+            level: BuildErrorLevel::Error,
+            kind: BibLogMessageKind::Generic,
+            message: line.to_string(),
+            file: None,
+            line: None,
+            log_line: 0,
+        })
+    } else {
+        None
+    }
+}
+
+/// Biber reports errors of the BibTeX subsystem using temporary copies of the database files,
+/// so only the file name can be used to find the original file.
+fn file_name(path: &str) -> PathBuf {
+    let path = PathBuf::from(path);
+    path.file_name().map_or(path.clone(), PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use insta::assert_debug_snapshot;
+
+    use super::*;
+
+    #[test]
+    fn bibtex() {
+        let log = r#"This is BibTeX, Version 0.99d (TeX Live 2022)
+Capacity: max_strings=200000, hash_size=200000, hash_prime=170003
+The top-level auxiliary file: main.aux
+The style file: plain.bst
+Database file #1: main.bib
+I was expecting a `,' or a `}'---line 3 of file main.bib
+ :
+ :   title = {Bar}
+I'm skipping whatever remains of this entry
+Repeated entry---line 7 of file main.bib
+ : @article{foo
+ :             ,
+I'm skipping whatever remains of this entry
+Warning--I didn't find a database entry for "bar"
+Warning--empty journal in foo
+You've used 1 entry,
+(There were 2 error messages)
+"#;
+
+        assert_debug_snapshot!(parse(log).messages);
+    }
+
+    #[test]
+    fn biber() {
+        let log = r#"[0] Config.pm:307> INFO - This is Biber 2.17
+[0] Config.pm:310> INFO - Logfile is 'main.blg'
+[58] Biber.pm:415> INFO - Reading 'main.bcf'
+[103] Biber.pm:1000> INFO - Found 2 citekeys in bib section 0
+[130] Biber.pm:4610> INFO - Looking for bibtex file 'main.bib' for section 0
+[140] bibtex.pm:1519> INFO - Found BibTeX data source 'main.bib'
+[150] Utils.pm:411> WARN - Duplicate entry key 'foo' in file 'main.bib', skipping ...
+[160] Utils.pm:411> WARN - I didn't find a database entry for 'bar' (section 0)
+[175] Utils.pm:395> ERROR - BibTeX subsystem: /tmp/biber_tmp_abc/main.bib_12345.utf8, line 8, syntax error: found "}", expecting "="
+[180] Utils.pm:411> WARN - Datamodel: Entry 'baz' (main.bib): Invalid field 'foo' for entrytype 'article'
+[190] Biber.pm:131> INFO - WARNINGS: 3
+[190] Biber.pm:135> INFO - ERRORS: 1
+"#;
+
+        assert_debug_snapshot!(parse(log).messages);
+    }
+
+    #[test]
+    fn line_zero() {
+        let lines = |log: &str| -> Vec<_> {
+            parse(log)
+                .messages
+                .into_iter()
+                .map(|message| message.line)
+                .collect()
+        };
+
+        assert_eq!(
+            lines("I was expecting a `,' or a `}'---line 0 of file main.bib"),
+            vec![None]
+        );
+
+        assert_eq!(
+            lines("[175] Utils.pm:395> ERROR - BibTeX subsystem: main.bib, line 0, syntax error"),
+            vec![None]
+        );
+    }
+}
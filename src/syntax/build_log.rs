@@ -1,7 +1,7 @@
-use std::{cmp::Ordering, path::PathBuf};
+use std::path::PathBuf;
 
 use once_cell::sync::Lazy;
-use regex::{Match, Regex};
+use regex::Regex;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
 pub enum BuildErrorLevel {
@@ -9,12 +9,45 @@ pub enum BuildErrorLevel {
     Warning,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum BuildErrorKind {
+    Generic,
+    UndefinedReference(String),
+    UndefinedCitation(String),
+    Rerun,
+    Font,
+    BadBox(BadBox),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum BadBoxKind {
+    Overfull,
+    Underfull,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BadBox {
+    pub kind: BadBoxKind,
+    pub detail: String,
+    pub end_line: Option<u32>,
+}
+
+/// The source line around the error location as printed by TeX after `l.<n>`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct BuildErrorContext {
+    pub before: String,
+    pub after: String,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct BuildError {
     pub relative_path: PathBuf,
     pub level: BuildErrorLevel,
+    pub kind: BuildErrorKind,
     pub message: String,
     pub line: Option<u32>,
+    pub context: Option<BuildErrorContext>,
+    pub log_line: u32,
 }
 
 const MAX_LINE_LENGTH: usize = 79;
@@ -23,17 +56,26 @@ pub static PACKAGE_MESSAGE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new("^\\([a-zA-Z_\\-]+\\)\\s*(?P<msg>.*)$").unwrap());
 
 pub static FILE_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new("\\((?P<file>[^\r\n()]+\\.(tex|sty|cls))").unwrap());
+    Lazy::new(|| Regex::new("^\\((?P<file>[^\r\n()]*?\\.(tex|sty|cls))(\\s|\\(|\\)|$)").unwrap());
 
-pub static TEX_ERROR_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new("(?m)^! ((?P<msg1>(.|\r|\n)*?)\r?\nl\\.(?P<line>\\d+)|(?P<msg2>[^\r\n]*))").unwrap()
+pub static TEX_CONTEXT_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new("^l\\.(?P<line>\\d+) ?(?P<before>.*)$").unwrap());
+
+pub static WARNING_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new("(?P<font>LaTeX Font|LaTeX|Package [a-zA-Z_\\-]+|Class [a-zA-Z_\\-]+) Warning: (?P<msg>.*)$")
+        .unwrap()
 });
 
-pub static WARNING_REGEX: Lazy<Regex> =
-    Lazy::new(|| Regex::new("(LaTeX|Package [a-zA-Z_\\-]+) Warning: (?P<msg>[^\r\n]*)").unwrap());
+pub static INPUT_LINE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new("on input line (?P<line>\\d+)").unwrap());
+
+pub static REFERENCE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new("^(?P<kind>Reference|Citation) `(?P<key>[^']+)' (on page \\S+ )?undefined").unwrap()
+});
 
 pub static BAD_BOX_REGEX: Lazy<Regex> = Lazy::new(|| {
-    Regex::new("(?P<msg>(Ov|Und)erfull \\\\[hv]box[^\r\n]*lines? (?P<line>\\d+)[^\r\n]*)").unwrap()
+    Regex::new("(?P<kind>Ov|Und)erfull \\\\[hv]box \\((?P<detail>[^)]*)\\)[^\r\n]*lines? (?P<line>\\d+)(--(?P<end>\\d+))?")
+        .unwrap()
 });
 
 #[derive(Debug, PartialEq, Eq, Clone, Hash)]
@@ -42,140 +84,259 @@ pub struct Parse {
 }
 
 pub fn parse(log: &str) -> Parse {
-    let log = prepare_log(log);
-    let mut ranges: Vec<FileRange> = FILE_REGEX
-        .find_iter(&log)
-        .map(|result| FileRange::create(&log, result))
-        .collect();
-    ranges.sort();
-
-    let tex_errors = extract_matches(&log, &ranges, &TEX_ERROR_REGEX, BuildErrorLevel::Error);
-    let warnings = extract_matches(&log, &ranges, &WARNING_REGEX, BuildErrorLevel::Warning);
-    let bad_boxes = extract_matches(&log, &ranges, &BAD_BOX_REGEX, BuildErrorLevel::Warning);
+    let mut parser = Parser {
+        lines: prepare_log(log),
+        files: Vec::new(),
+        errors: Vec::new(),
+    };
 
+    parser.parse();
     Parse {
-        errors: vec![tex_errors, warnings, bad_boxes].concat(),
+        errors: parser.errors,
     }
 }
 
-fn extract_matches(
-    log: &str,
-    ranges: &[FileRange],
-    regex: &Regex,
-    level: BuildErrorLevel,
-) -> Vec<BuildError> {
-    let mut errors = Vec::new();
-    for result in regex.find_iter(log) {
-        let captures = regex.captures(&log[result.start()..result.end()]).unwrap();
-        let message = captures
-            .name("msg")
-            .or_else(|| captures.name("msg1"))
-            .or_else(|| captures.name("msg2"))
-            .unwrap()
-            .as_str()
-            .lines()
-            .next()
-            .unwrap_or_default()
-            .to_owned();
-
-        if let Some(range) = ranges.iter().find(|range| range.contains(result.start())) {
-            let line = captures
-                .name("line")
-                .map(|result| result.as_str().parse::<u32>().unwrap() - 1);
-
-            errors.push(BuildError {
-                relative_path: range.path.clone(),
-                level,
-                message,
-                line,
-            });
-        }
-    }
-    errors
+/// A logical line of the log after undoing the line wrapping of TeX.
+#[derive(Debug)]
+struct LogLine {
+    text: String,
+    number: u32,
 }
 
-fn prepare_log(log: &str) -> String {
-    let mut old_lines = log.lines();
-    let mut new_lines: Vec<String> = Vec::new();
-    while let Some(line) = old_lines.next() {
-        if PACKAGE_MESSAGE_REGEX.is_match(line) {
-            let captures = PACKAGE_MESSAGE_REGEX.captures(line).unwrap();
+fn prepare_log(log: &str) -> Vec<LogLine> {
+    let mut old_lines = log.lines().enumerate();
+    let mut new_lines: Vec<LogLine> = Vec::new();
+    while let Some((number, line)) = old_lines.next() {
+        if let Some(captures) = PACKAGE_MESSAGE_REGEX.captures(line) {
             if let Some(last_line) = new_lines.last_mut() {
-                last_line.push(' ');
-                last_line.push_str(captures.name("msg").unwrap().as_str());
-            }
-        } else if line.ends_with("...") {
-            let mut new_line = line[line.len() - 3..].to_owned();
-            if let Some(old_line) = old_lines.next() {
-                new_line.push_str(old_line);
+                last_line.text.push(' ');
+                last_line.text.push_str(&captures["msg"]);
+                continue;
             }
-            new_lines.push(new_line);
-        } else if line.chars().count() == MAX_LINE_LENGTH {
-            let mut new_line = String::new();
-            new_line.push_str(line);
-            if let Some(old_line) = old_lines.next() {
-                new_line.push_str(old_line);
-            }
-            new_lines.push(new_line);
-        } else {
-            new_lines.push(line.to_owned());
         }
+
+        let mut text = line.to_string();
+        let mut length = line.chars().count();
+        while length == MAX_LINE_LENGTH {
+            match old_lines.next() {
+                Some((_, next_line)) => {
+                    text.push_str(next_line);
+                    length = next_line.chars().count();
+                }
+                None => break,
+            };
+        }
+
+        new_lines.push(LogLine {
+            text,
+            number: number as u32,
+        });
     }
-    new_lines.join("\n")
+
+    new_lines
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
-struct FileRange {
-    pub path: PathBuf,
-    pub start: usize,
-    pub end: usize,
+struct Parser {
+    lines: Vec<LogLine>,
+    files: Vec<Option<PathBuf>>,
+    errors: Vec<BuildError>,
 }
 
-impl FileRange {
-    fn create(log: &str, result: Match) -> Self {
-        let mut balance = 1;
-        let mut end = result.start() + 1;
-        let chars = (&log[result.start() + 1..]).chars();
-        for c in chars {
-            if balance <= 0 {
+impl Parser {
+    fn parse(&mut self) {
+        let mut index = 0;
+        while index < self.lines.len() {
+            index = self
+                .tex_error(index)
+                .or_else(|| self.bad_box(index))
+                .or_else(|| self.warning(index))
+                .unwrap_or_else(|| {
+                    let text = self.lines[index].text.clone();
+                    self.scan_files(&text);
+                    index + 1
+                });
+        }
+    }
+
+    fn tex_error(&mut self, start: usize) -> Option<usize> {
+        let first_line = self.lines[start].text.strip_prefix("! ")?;
+        let mut message = first_line.to_string();
+        let mut end = start + 1;
+        while let Some(line) = self.lines.get(end).map(|line| line.text.as_str()) {
+            if line.is_empty()
+                || line.starts_with("! ")
+                || line.starts_with('<')
+                || TEX_CONTEXT_REGEX.is_match(line)
+            {
                 break;
             }
 
-            if c == '(' {
-                balance += 1;
-            } else if c == ')' {
-                balance -= 1;
-            }
-            end += c.len_utf8();
+            message.push('\n');
+            message.push_str(line);
+            end += 1;
         }
 
-        let captures = FILE_REGEX.captures(result.as_str()).unwrap();
-        let path = PathBuf::from(captures.name("file").unwrap().as_str());
-        Self {
-            path,
-            start: result.start(),
-            end,
+        let mut line = None;
+        let mut context = None;
+        for index in end..self.lines.len() {
+            let text = &self.lines[index].text;
+            if text.starts_with("! ") {
+                break;
+            }
+
+            if let Some(captures) = TEX_CONTEXT_REGEX.captures(text) {
+                line = captures["line"]
+                    .parse::<u32>()
+                    .ok()
+                    .and_then(|line| line.checked_sub(1));
+                let before = captures["before"].to_string();
+                let prefix_length = text.chars().count() - before.chars().count();
+                let after = self
+                    .lines
+                    .get(index + 1)
+                    .map(|line| {
+                        let skipped = line
+                            .text
+                            .chars()
+                            .take(prefix_length + before.chars().count());
+                        if skipped.clone().all(char::is_whitespace) {
+                            line.text.chars().skip(skipped.count()).collect()
+                        } else {
+                            line.text.trim_start().to_string()
+                        }
+                    })
+                    .unwrap_or_default();
+
+                context = Some(BuildErrorContext { before, after });
+                end = index + 2;
+                break;
+            }
         }
+
+        self.push(
+            start,
+            BuildErrorLevel::Error,
+            BuildErrorKind::Generic,
+            message,
+            line,
+            context,
+        );
+        Some(end.min(self.lines.len()))
     }
 
-    fn len(&self) -> usize {
-        self.end - self.start + 1
+    fn bad_box(&mut self, start: usize) -> Option<usize> {
+        let text = self.lines[start].text.clone();
+        let captures = BAD_BOX_REGEX.captures(&text)?;
+        let range = captures.get(0).unwrap().range();
+        self.scan_files(&text[..range.start]);
+
+        let kind = if &captures["kind"] == "Ov" {
+            BadBoxKind::Overfull
+        } else {
+            BadBoxKind::Underfull
+        };
+
+        let line = captures["line"].parse::<u32>().ok();
+        let end_line = captures
+            .name("end")
+            .and_then(|end| end.as_str().parse::<u32>().ok())
+            .and_then(|end| end.checked_sub(1));
+
+        let bad_box = BadBox {
+            kind,
+            detail: captures["detail"].to_string(),
+            end_line,
+        };
+
+        self.push(
+            start,
+            BuildErrorLevel::Warning,
+            BuildErrorKind::BadBox(bad_box),
+            text[range].to_string(),
+            line.and_then(|line| line.checked_sub(1)),
+            None,
+        );
+
+        // The following lines contain a dump of the box which may have unbalanced parentheses.
+        let mut end = start + 1;
+        while self
+            .lines
+            .get(end)
+            .map_or(false, |line| !line.text.is_empty())
+        {
+            end += 1;
+        }
+
+        Some(end)
     }
 
-    fn contains(&self, index: usize) -> bool {
-        index >= self.start && index <= self.end
+    fn warning(&mut self, start: usize) -> Option<usize> {
+        let text = self.lines[start].text.clone();
+        let captures = WARNING_REGEX.captures(&text)?;
+        self.scan_files(&text[..captures.get(0).unwrap().start()]);
+
+        let message = captures["msg"].to_string();
+        let line = INPUT_LINE_REGEX
+            .captures(&message)
+            .and_then(|captures| captures["line"].parse::<u32>().ok())
+            .and_then(|line| line.checked_sub(1));
+
+        let kind = if &captures["font"] == "LaTeX Font" {
+            BuildErrorKind::Font
+        } else if let Some(captures) = REFERENCE_REGEX.captures(&message) {
+            let key = captures["key"].to_string();
+            if &captures["kind"] == "Reference" {
+                BuildErrorKind::UndefinedReference(key)
+            } else {
+                BuildErrorKind::UndefinedCitation(key)
+            }
+        } else if message.contains("Rerun")
+            || message.contains("rerun")
+            || message.contains("(re)run")
+        {
+            BuildErrorKind::Rerun
+        } else {
+            BuildErrorKind::Generic
+        };
+
+        self.push(start, BuildErrorLevel::Warning, kind, message, line, None);
+        Some(start + 1)
     }
-}
 
-impl Ord for FileRange {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.len().cmp(&other.len())
+    fn push(
+        &mut self,
+        start: usize,
+        level: BuildErrorLevel,
+        kind: BuildErrorKind,
+        message: String,
+        line: Option<u32>,
+        context: Option<BuildErrorContext>,
+    ) {
+        if let Some(path) = self.files.iter().rev().find_map(Option::as_ref) {
+            self.errors.push(BuildError {
+                relative_path: path.clone(),
+                level,
+                kind,
+                message,
+                line,
+                context,
+                log_line: self.lines[start].number,
+            });
+        }
     }
-}
 
-impl PartialOrd for FileRange {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+    fn scan_files(&mut self, text: &str) {
+        for (index, c) in text.char_indices() {
+            if c == '(' {
+                let path = FILE_REGEX
+                    .captures(&text[index..])
+                    .map(|captures| PathBuf::from(&captures["file"]));
+
+                self.files.push(path);
+            } else if c == ')' {
+                self.files.pop();
+            }
+        }
     }
 }
 
@@ -184,6 +345,13 @@ mod tests {
     use super::*;
     use insta::assert_debug_snapshot;
 
+    fn context(before: &str, after: &str) -> BuildErrorContext {
+        BuildErrorContext {
+            before: before.into(),
+            after: after.into(),
+        }
+    }
+
     #[test]
     fn parse_001() {
         let log = r#"
@@ -277,16 +445,30 @@ PDF statistics:
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::BadBox(BadBox {
+                        kind: BadBoxKind::Overfull,
+                        detail: "200.00162pt too wide".into(),
+                        end_line: Some(8),
+                    }),
                     message: "Overfull \\hbox (200.00162pt too wide) in paragraph at lines 8--9"
                         .into(),
                     line: Some(7),
+                    context: None,
+                    log_line: 51,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::BadBox(BadBox {
+                        kind: BadBoxKind::Overfull,
+                        detail: "3.19998pt too high".into(),
+                        end_line: None,
+                    }),
                     message: "Overfull \\vbox (3.19998pt too high) detected at line 23".into(),
                     line: Some(22),
-                }
+                    context: None,
+                    log_line: 61,
+                },
             ]
         );
     }
@@ -364,9 +546,12 @@ PDF statistics:
             vec![BuildError {
                 relative_path: "./child.tex".into(),
                 level: BuildErrorLevel::Error,
+                kind: BuildErrorKind::Generic,
                 message: "Undefined control sequence.".into(),
-                line: Some(0)
-            }]
+                line: Some(0),
+                context: Some(context("\\foo", "")),
+                log_line: 41,
+            },]
         );
     }
 
@@ -445,15 +630,21 @@ PDF statistics:
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::UndefinedCitation("foo".into()),
                     message: "Citation `foo' on page 1 undefined on input line 6.".into(),
-                    line: None
+                    line: Some(5),
+                    context: None,
+                    log_line: 41,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::Generic,
                     message: "There were undefined references.".into(),
                     line: None,
-                }
+                    context: None,
+                    log_line: 47,
+                },
             ]
         );
     }
@@ -551,15 +742,21 @@ PDF statistics:
                 BuildError {
                     relative_path: "/TexLive/texmf-dist/tex/generic/babel/babel.sty".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Package babel Error: Unknown option `foo'. Either you misspelled it or the language definition file foo.ldf was not found.".into(),
-                    line: Some(392)
+                    line: Some(392),
+                    context: Some(context("\\ProcessOptions*", "")),
+                    log_line: 29,
                 },
                 BuildError {
                     relative_path: "/TexLive/texmf-dist/tex/generic/babel/babel.sty".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Package babel Error: You haven't specified a language option.".into(),
-                    line: Some(425)
-                }
+                    line: Some(425),
+                    context: Some(context("...ry to proceed from here, type x to quit.}", "")),
+                    log_line: 42,
+                },
             ]
         );
     }
@@ -959,21 +1156,30 @@ PDF statistics:
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::Generic,
                     message: "'babel/polyglossia' detected but 'csquotes' missing. Loading 'csquotes' recommended.".into(),
-                    line: None
+                    line: None,
+                    context: None,
+                    log_line: 325,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::Generic,
                     message: "There were undefined references.".into(),
                     line: None,
+                    context: None,
+                    log_line: 362,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Warning,
+                    kind: BuildErrorKind::Rerun,
                     message: "Please (re)run Biber on the file: parent and rerun LaTeX afterwards.".into(),
-                    line: None
-                }
+                    line: None,
+                    context: None,
+                    log_line: 364,
+                },
             ]
         );
     }
@@ -1110,38 +1316,56 @@ PDF statistics:
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Undefined control sequence.".into(),
-                    line: Some(6)
+                    line: Some(6),
+                    context: Some(context("\\foo", "")),
+                    log_line: 44,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Missing $ inserted.".into(),
-                    line: Some(7)
+                    line: Some(7),
+                    context: Some(context("\\bar", "")),
+                    log_line: 53,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Undefined control sequence.".into(),
-                    line: Some(8)
+                    line: Some(8),
+                    context: Some(context("\\baz", "")),
+                    log_line: 65,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Missing { inserted.".into(),
-                    line: Some(9)
+                    line: Some(9),
+                    context: Some(context("", "")),
+                    log_line: 74,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Missing $ inserted.".into(),
-                    line: Some(9)
+                    line: Some(9),
+                    context: Some(context("", "")),
+                    log_line: 84,
                 },
                 BuildError {
                     relative_path: "./parent.tex".into(),
                     level: BuildErrorLevel::Error,
+                    kind: BuildErrorKind::Generic,
                     message: "Missing } inserted.".into(),
-                    line: Some(9)
+                    line: Some(9),
+                    context: Some(context("", "")),
+                    log_line: 92,
                 },
             ]
         );
@@ -1329,4 +1553,26 @@ PDF statistics:
 
         assert_debug_snapshot!(parse(log).errors);
     }
+
+    #[test]
+    fn line_zero() {
+        let log = r#"
+(./main.tex
+! Undefined control sequence.
+l.0 \foo
+
+Overfull \hbox (1.0pt too wide) in paragraph at lines 0--0
+[]
+
+)"#;
+
+        let errors = parse(log).errors;
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].line, None);
+        assert_eq!(errors[1].line, None);
+        assert!(matches!(
+            &errors[1].kind,
+            BuildErrorKind::BadBox(BadBox { end_line: None, .. })
+        ));
+    }
 }
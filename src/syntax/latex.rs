@@ -1,4 +1,5 @@
 mod analysis;
+mod config;
 mod cst;
 mod kind;
 mod lexer;
@@ -6,9 +7,10 @@ mod parser;
 
 pub use self::{
     analysis::*,
+    config::SyntaxConfig,
     cst::*,
     kind::SyntaxKind::{self, *},
-    parser::{parse, Parse},
+    parser::{parse, parse_with_config, Parse},
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
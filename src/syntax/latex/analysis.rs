@@ -6,6 +6,8 @@ mod graphics_path;
 mod implicit_link;
 mod label_name;
 mod label_number;
+mod magic_comment;
+mod option;
 mod theorem;
 mod types;
 
@@ -14,21 +16,26 @@ use crate::syntax::latex;
 pub use self::types::*;
 use self::{
     command::{analyze_command, analyze_command_definition},
-    environment::analyze_begin,
+    environment::{analyze_begin, analyze_environment_definition},
     explicit_link::{analyze_import, analyze_include},
     graphics_path::analyze_graphics_path,
     implicit_link::analyze_implicit_links,
     label_name::analyze_label_name,
     label_number::analyze_label_number,
+    magic_comment::analyze_magic_comments,
+    option::analyze_option_declaration,
     theorem::analyze_theorem_definition,
 };
 
 pub fn analyze(context: &mut LatexAnalyzerContext, root: &latex::SyntaxNode) {
     analyze_implicit_links(context);
+    analyze_magic_comments(context, root);
     for node in root.descendants() {
+        analyze_option_declaration(context, node.clone());
         analyze_command(context, node.clone())
             .or_else(|| analyze_command_definition(context, node.clone()))
             .or_else(|| analyze_begin(context, node.clone()))
+            .or_else(|| analyze_environment_definition(context, node.clone()))
             .or_else(|| analyze_include(context, node.clone()))
             .or_else(|| analyze_import(context, node.clone()))
             .or_else(|| analyze_label_name(context, node.clone()))
@@ -36,5 +43,4 @@ pub fn analyze(context: &mut LatexAnalyzerContext, root: &latex::SyntaxNode) {
             .or_else(|| analyze_theorem_definition(context, node.clone()))
             .or_else(|| analyze_graphics_path(context, node));
     }
-    context.extras.has_document_environment = context.extras.environment_names.contains("document");
 }
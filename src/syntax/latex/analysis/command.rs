@@ -1,8 +1,10 @@
+use std::{iter::Peekable, str::Chars};
+
 use rowan::ast::AstNode;
 
-use crate::syntax::latex;
+use crate::syntax::latex::{self, HasBrack, HasCurly};
 
-use super::LatexAnalyzerContext;
+use super::{CommandParameter, LatexAnalyzerContext};
 
 pub fn analyze_command(context: &mut LatexAnalyzerContext, node: latex::SyntaxNode) -> Option<()> {
     let command = latex::GenericCommand::cast(node)?;
@@ -18,9 +20,216 @@ pub fn analyze_command_definition(
     node: latex::SyntaxNode,
 ) -> Option<()> {
     let definition = latex::CommandDefinition::cast(node)?;
-    context
-        .extras
-        .command_names
-        .insert(definition.name()?.command()?.text().into());
+    let name = definition.name()?;
+    let parameters = match definition.command()?.kind() {
+        latex::DOCUMENT_COMMAND_DEFINITION_NAME => {
+            parse_argument_spec(&definition.argument_spec()?.content_text()?)
+        }
+        latex::TEX_DEFINITION_NAME => parse_parameter_text(&definition),
+        latex::COMMAND_DEFINITION_NAME => {
+            parse_argument_count(definition.argument_count(), definition.default_argument())
+        }
+        _ => Vec::new(),
+    };
+
+    let extras = &mut context.extras;
+    extras.command_names.insert(name.text().into());
+    extras
+        .command_definitions
+        .insert(name.text().into(), parameters);
     Some(())
 }
+
+/// Builds the parameters of a `\newcommand`-style definition like `\newcommand{\foo}[2][bar]`.
+/// The default value turns the first parameter into an optional one.
+pub(super) fn parse_argument_count(
+    count: Option<latex::BrackGroupWord>,
+    default: Option<latex::BrackGroup>,
+) -> Vec<CommandParameter> {
+    let count: usize = count
+        .and_then(|count| count.content_text())
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(0);
+
+    let mut parameters = vec![CommandParameter::mandatory(); count];
+    if let Some(default) = default.and_then(|default| default.content_text()) {
+        if let Some(first) = parameters.first_mut() {
+            *first = CommandParameter::optional(Some(default));
+        }
+    }
+
+    parameters
+}
+
+/// Parses the parameter text of a plain TeX definition like `\def\foo[#1]#2{...}`.
+/// Undelimited parameters are treated like mandatory arguments.
+fn parse_parameter_text(definition: &latex::CommandDefinition) -> Vec<CommandParameter> {
+    let text: String = definition
+        .syntax()
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| !token.kind().is_command_name() && token.kind() != latex::WHITESPACE)
+        .map(|token| token.text().to_string())
+        .collect();
+
+    let mut segments = text.split('#');
+    let prefix = segments.next().unwrap_or_default();
+    segments
+        .enumerate()
+        .filter_map(|(i, segment)| {
+            let close = segment.strip_prefix(|c: char| c.is_ascii_digit())?;
+            let open = if i == 0 { prefix } else { "" };
+            Some(if open.is_empty() && close.is_empty() {
+                CommandParameter::mandatory()
+            } else {
+                CommandParameter {
+                    open: open.into(),
+                    close: close.into(),
+                    is_optional: false,
+                    default: None,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Parses an `xparse` argument specification like `s m O{default}`.
+pub(super) fn parse_argument_spec(spec: &str) -> Vec<CommandParameter> {
+    let mut chars = spec.chars().peekable();
+    let mut parameters = Vec::new();
+    while let Some(c) = chars.next() {
+        let parameter = match c {
+            'm' | 'v' => CommandParameter::mandatory(),
+            'o' => CommandParameter::optional(None),
+            'O' => CommandParameter::optional(read_group(&mut chars)),
+            's' => CommandParameter {
+                open: "*".into(),
+                close: "".into(),
+                is_optional: true,
+                default: None,
+            },
+            't' => CommandParameter {
+                open: read_token(&mut chars).into(),
+                close: "".into(),
+                is_optional: true,
+                default: None,
+            },
+            'r' | 'R' | 'd' | 'D' => {
+                let open = read_token(&mut chars);
+                let close = read_token(&mut chars);
+                let default = if c.is_uppercase() {
+                    read_group(&mut chars)
+                } else {
+                    None
+                };
+
+                CommandParameter {
+                    open: open.into(),
+                    close: close.into(),
+                    is_optional: matches!(c, 'd' | 'D'),
+                    default,
+                }
+            }
+            'e' | '>' => {
+                read_group(&mut chars);
+                continue;
+            }
+            'E' => {
+                read_group(&mut chars);
+                read_group(&mut chars);
+                continue;
+            }
+            _ => continue,
+        };
+
+        parameters.push(parameter);
+    }
+
+    parameters
+}
+
+fn read_token(chars: &mut Peekable<Chars>) -> String {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    let mut token = String::new();
+    match chars.next() {
+        Some('\\') => {
+            token.push('\\');
+            while let Some(c) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+                token.push(c);
+            }
+
+            if token.len() == 1 {
+                token.extend(chars.next());
+            }
+        }
+        Some(c) => token.push(c),
+        None => {}
+    };
+
+    token
+}
+
+fn read_group(chars: &mut Peekable<Chars>) -> Option<String> {
+    while chars.next_if(|c| c.is_whitespace()).is_some() {}
+    chars.next_if_eq(&'{')?;
+    let mut depth = 1;
+    let mut text = String::new();
+    for c in chars.by_ref() {
+        match c {
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+            _ => {}
+        };
+
+        text.push(c);
+    }
+
+    Some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delimited(open: &str, close: &str, is_optional: bool) -> CommandParameter {
+        CommandParameter {
+            open: open.into(),
+            close: close.into(),
+            is_optional,
+            default: None,
+        }
+    }
+
+    #[test]
+    fn argument_spec() {
+        assert_eq!(
+            parse_argument_spec("s m O{foo {bar}} +r() >{\\SplitList{;}} d<>"),
+            vec![
+                delimited("*", "", true),
+                CommandParameter::mandatory(),
+                CommandParameter::optional(Some("foo {bar}".into())),
+                delimited("(", ")", false),
+                delimited("<", ">", true),
+            ]
+        );
+    }
+
+    #[test]
+    fn parameter_text() {
+        let root = latex::SyntaxNode::new_root(latex::parse(r"\def\foo[#1]#2{}").green);
+        let definition = root
+            .descendants()
+            .find_map(latex::CommandDefinition::cast)
+            .unwrap();
+
+        assert_eq!(
+            parse_parameter_text(&definition),
+            vec![delimited("[", "]", false), CommandParameter::mandatory()]
+        );
+    }
+}
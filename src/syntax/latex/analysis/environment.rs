@@ -1,13 +1,36 @@
 use rowan::ast::AstNode;
 
-use crate::syntax::latex;
+use crate::syntax::latex::{self, HasCurly};
 
-use super::LatexAnalyzerContext;
+use super::{
+    command::{parse_argument_count, parse_argument_spec},
+    LatexAnalyzerContext,
+};
 
 pub fn analyze_begin(context: &mut LatexAnalyzerContext, node: latex::SyntaxNode) -> Option<()> {
     let begin = latex::Begin::cast(node)?;
     let name = begin.name()?.key()?.to_string();
     let extras = &mut context.extras;
+    extras.has_document_environment |= name == "document";
     extras.environment_names.insert(name);
     Some(())
 }
+
+pub fn analyze_environment_definition(
+    context: &mut LatexAnalyzerContext,
+    node: latex::SyntaxNode,
+) -> Option<()> {
+    let definition = latex::EnvironmentDefinition::cast(node)?;
+    let name = definition.name()?.key()?.to_string();
+    let parameters = match definition.command()?.kind() {
+        latex::DOCUMENT_ENVIRONMENT_DEFINITION_NAME => {
+            parse_argument_spec(&definition.argument_spec()?.content_text()?)
+        }
+        _ => parse_argument_count(definition.argument_count(), definition.default_argument()),
+    };
+
+    let extras = &mut context.extras;
+    extras.environment_names.insert(name.clone());
+    extras.environment_definitions.insert(name, parameters);
+    Some(())
+}
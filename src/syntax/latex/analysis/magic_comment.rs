@@ -0,0 +1,98 @@
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use crate::syntax::latex;
+
+use super::LatexAnalyzerContext;
+
+static MAGIC_COMMENT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)^%\s*!\s*(TEX|BIB)\s+(root|program|TS-program)\s*=\s*(.*?)\s*$").unwrap()
+});
+
+/// Reads magic comments like `% !TEX root = main.tex` or `% !TeX program = xelatex`.
+/// The first occurrence of each comment wins.
+pub fn analyze_magic_comments(context: &mut LatexAnalyzerContext, root: &latex::SyntaxNode) {
+    for token in root
+        .descendants_with_tokens()
+        .filter_map(|element| element.into_token())
+        .filter(|token| token.kind() == latex::COMMENT)
+    {
+        let captures = match MAGIC_COMMENT_REGEX.captures(token.text()) {
+            Some(captures) => captures,
+            None => continue,
+        };
+
+        let value = &captures[3];
+        if value.is_empty() {
+            continue;
+        }
+
+        let magic = &mut context.extras.magic_comments;
+        match (
+            captures[1].to_ascii_lowercase().as_str(),
+            captures[2].to_ascii_lowercase().as_str(),
+        ) {
+            ("tex", "root") if magic.root.is_none() => {
+                magic.root = context.document_uri.join(value).ok().map(Arc::new);
+            }
+            ("tex", "program" | "ts-program") if magic.tex_program.is_none() => {
+                magic.tex_program = Some(value.to_string());
+            }
+            ("bib", "program" | "ts-program") if magic.bib_program.is_none() => {
+                magic.bib_program = Some(value.to_string());
+            }
+            _ => {}
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use lsp_types::Url;
+
+    use crate::{Document, DocumentLanguage, Environment};
+
+    use super::*;
+
+    fn analyze(text: &str) -> super::super::MagicComments {
+        let uri = Arc::new(Url::parse("http://example.com/chapters/foo.tex").unwrap());
+        let document = Document::parse(
+            &Environment::default(),
+            uri,
+            Arc::new(text.to_string()),
+            DocumentLanguage::Latex,
+        );
+
+        document
+            .data
+            .as_latex()
+            .unwrap()
+            .extras
+            .magic_comments
+            .clone()
+    }
+
+    #[test]
+    fn root_and_programs() {
+        let magic_comments = analyze(
+            "% !TEX root = ../main.tex\n%!TeX program=xelatex\n% !BIB TS-program = biber\n",
+        );
+
+        assert_eq!(
+            magic_comments.root.as_deref(),
+            Some(&Url::parse("http://example.com/main.tex").unwrap())
+        );
+        assert_eq!(magic_comments.tex_program.as_deref(), Some("xelatex"));
+        assert_eq!(magic_comments.bib_program.as_deref(), Some("biber"));
+    }
+
+    #[test]
+    fn first_occurrence() {
+        let magic_comments =
+            analyze("% !TeX program = lualatex\n% !TeX program = pdflatex\n% TeX root = foo.tex");
+        assert_eq!(magic_comments.root, None);
+        assert_eq!(magic_comments.tex_program.as_deref(), Some("lualatex"));
+    }
+}
@@ -0,0 +1,108 @@
+use rowan::ast::AstNode;
+
+use crate::syntax::latex::{self, HasCurly};
+
+use super::LatexAnalyzerContext;
+
+/// Collects the options of a package or class that are declared with
+/// `\DeclareOption{name}{...}` or `\DeclareKeys{name .code = ..., ...}`.
+pub fn analyze_option_declaration(
+    context: &mut LatexAnalyzerContext,
+    node: latex::SyntaxNode,
+) -> Option<()> {
+    let command = latex::GenericCommand::cast(node)?;
+    let body = command
+        .syntax()
+        .children()
+        .find_map(latex::CurlyGroup::cast)?
+        .content_text()?;
+
+    let names = match command.name()?.text() {
+        "\\DeclareOption" => vec![body.as_str()],
+        "\\DeclareKeys" => split_key_declarations(&body),
+        _ => return None,
+    };
+
+    for name in names.into_iter().filter(|name| !name.is_empty()) {
+        context.extras.option_names.insert(name.into());
+    }
+
+    Some(())
+}
+
+/// Extracts the key names of a declaration list like `margin .store = \margin, draft .code = {...}`.
+fn split_key_declarations(text: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in text.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                names.push(key_name(&text[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        };
+    }
+
+    names.push(key_name(&text[start..]));
+    names
+}
+
+fn key_name(declaration: &str) -> &str {
+    declaration
+        .split(['.', '='])
+        .next()
+        .unwrap_or_default()
+        .trim()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use lsp_types::Url;
+
+    use crate::{Document, DocumentLanguage, Environment};
+
+    fn analyze(text: &str) -> Vec<String> {
+        let uri = Arc::new(Url::parse("http://example.com/foo.sty").unwrap());
+        let document = Document::parse(
+            &Environment::default(),
+            uri,
+            Arc::new(text.to_string()),
+            DocumentLanguage::Latex,
+        );
+
+        let mut names: Vec<_> = document
+            .data
+            .as_latex()
+            .unwrap()
+            .extras
+            .option_names
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn declare_option() {
+        assert_eq!(
+            analyze("\\DeclareOption{draft}{\\drafttrue}\n\\DeclareOption*{\\PassOptionsToClass{\\CurrentOption}{article}}"),
+            vec!["draft"]
+        );
+    }
+
+    #[test]
+    fn declare_keys() {
+        assert_eq!(
+            analyze("\\DeclareKeys[foo]{\n  margin .store = \\foo@margin,\n  draft .code = {\\a, \\b},\n  final .if = foo@final\n}"),
+            vec!["draft", "final", "margin"]
+        );
+    }
+}
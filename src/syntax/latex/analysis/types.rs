@@ -21,17 +21,30 @@ pub struct Extras {
     pub explicit_links: Vec<ExplicitLink>,
     pub has_document_environment: bool,
     pub command_names: FxHashSet<SmolStr>,
+    pub command_definitions: FxHashMap<SmolStr, Vec<CommandParameter>>,
     pub environment_names: FxHashSet<String>,
+    pub environment_definitions: FxHashMap<String, Vec<CommandParameter>>,
     pub label_names: Vec<LabelName>,
     pub label_numbers_by_name: FxHashMap<String, String>,
     pub theorem_environments: Vec<TheoremEnvironment>,
+    pub option_names: FxHashSet<SmolStr>,
     pub graphics_paths: FxHashSet<String>,
+    pub magic_comments: MagicComments,
+}
+
+/// The settings of `% !TEX root`, `% !TeX program` and `% !BIB program` magic comments.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
+pub struct MagicComments {
+    pub root: Option<Arc<Url>>,
+    pub tex_program: Option<String>,
+    pub bib_program: Option<String>,
 }
 
 #[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
 pub struct ImplicitLinks {
     pub aux: Vec<Arc<Url>>,
     pub log: Vec<Arc<Url>>,
+    pub blg: Vec<Arc<Url>>,
     pub pdf: Vec<Arc<Url>>,
 }
 
@@ -61,6 +74,36 @@ impl ExplicitLink {
     }
 }
 
+/// A parameter of a user-defined command or environment.
+/// Optional parameters include flags like the `*` of `\NewDocumentCommand{\foo}{s}`.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct CommandParameter {
+    pub open: SmolStr,
+    pub close: SmolStr,
+    pub is_optional: bool,
+    pub default: Option<String>,
+}
+
+impl CommandParameter {
+    pub fn mandatory() -> Self {
+        Self {
+            open: "{".into(),
+            close: "}".into(),
+            is_optional: false,
+            default: None,
+        }
+    }
+
+    pub fn optional(default: Option<String>) -> Self {
+        Self {
+            open: "[".into(),
+            close: "]".into(),
+            is_optional: true,
+            default,
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Clone, Default, Hash)]
 pub struct TheoremEnvironment {
     pub name: String,
@@ -0,0 +1,33 @@
+use rustc_hash::FxHashSet;
+
+use super::SyntaxKind::{self, *};
+
+/// Additional commands that the lexer classifies like their built-in counterparts.
+/// The command names are stored without the leading backslash.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct SyntaxConfig {
+    pub label_definition_commands: FxHashSet<String>,
+    pub label_reference_commands: FxHashSet<String>,
+    pub citation_commands: FxHashSet<String>,
+    pub include_commands: FxHashSet<String>,
+    pub section_commands: FxHashSet<String>,
+}
+
+impl SyntaxConfig {
+    /// Returns the kind of the given command name (including the backslash)
+    /// if it has been configured by the user.
+    pub fn classify(&self, text: &str) -> Option<SyntaxKind> {
+        let name = text.strip_prefix('\\')?;
+        let name = name.strip_suffix('*').unwrap_or(name);
+        [
+            (&self.label_definition_commands, LABEL_DEFINITION_NAME),
+            (&self.label_reference_commands, LABEL_REFERENCE_NAME),
+            (&self.citation_commands, CITATION_NAME),
+            (&self.include_commands, LATEX_INCLUDE_NAME),
+            (&self.section_commands, SECTION_NAME),
+        ]
+        .into_iter()
+        .find(|(commands, _)| commands.contains(name))
+        .map(|(_, kind)| kind)
+    }
+}
@@ -193,6 +193,10 @@ impl CurlyGroupWord {
     }
 }
 
+cst_node!(CurlyGroupArgumentSpec, CURLY_GROUP_ARGUMENT_SPEC);
+
+impl HasCurly for CurlyGroupArgumentSpec {}
+
 cst_node!(BrackGroupWord, BRACK_GROUP_WORD);
 
 impl HasBrack for BrackGroupWord {}
@@ -445,6 +449,10 @@ impl Include {
         self.syntax().first_token()
     }
 
+    pub fn options(&self) -> Option<BrackGroupKeyValue> {
+        self.syntax().children().find_map(BrackGroupKeyValue::cast)
+    }
+
     pub fn path_list(&self) -> Option<CurlyGroupWordList> {
         self.syntax().children().find_map(CurlyGroupWordList::cast)
     }
@@ -551,8 +559,38 @@ impl CommandDefinition {
         self.syntax().first_token()
     }
 
-    pub fn name(&self) -> Option<CurlyGroupCommand> {
-        self.syntax().children().find_map(CurlyGroupCommand::cast)
+    /// Returns the defined command, which is either wrapped in braces (`\newcommand{\foo}`)
+    /// or follows the definition command directly (`\def\foo`).
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.syntax()
+            .children_with_tokens()
+            .skip(1)
+            .find_map(|element| match element {
+                rowan::NodeOrToken::Node(node) => CurlyGroupCommand::cast(node)?
+                    .syntax()
+                    .children_with_tokens()
+                    .filter_map(|element| element.into_token())
+                    .find(|token| token.kind().is_command_name()),
+                rowan::NodeOrToken::Token(token) => {
+                    Some(token).filter(|token| token.kind().is_command_name())
+                }
+            })
+    }
+
+    /// Returns the number of parameters of a `\newcommand` definition like `\newcommand{\foo}[2]`.
+    pub fn argument_count(&self) -> Option<BrackGroupWord> {
+        self.syntax().children().find_map(BrackGroupWord::cast)
+    }
+
+    /// Returns the default value of the first parameter of a `\newcommand` definition.
+    pub fn default_argument(&self) -> Option<BrackGroup> {
+        self.syntax().children().find_map(BrackGroup::cast)
+    }
+
+    pub fn argument_spec(&self) -> Option<CurlyGroupArgumentSpec> {
+        self.syntax()
+            .children()
+            .find_map(CurlyGroupArgumentSpec::cast)
     }
 
     pub fn implementation(&self) -> Option<CurlyGroup> {
@@ -560,6 +598,32 @@ impl CommandDefinition {
     }
 }
 
+cst_node!(EnvironmentDefinition, ENVIRONMENT_DEFINITION);
+
+impl EnvironmentDefinition {
+    pub fn command(&self) -> Option<SyntaxToken> {
+        self.syntax().first_token()
+    }
+
+    pub fn name(&self) -> Option<CurlyGroupWord> {
+        self.syntax().children().find_map(CurlyGroupWord::cast)
+    }
+
+    pub fn argument_count(&self) -> Option<BrackGroupWord> {
+        self.syntax().children().find_map(BrackGroupWord::cast)
+    }
+
+    pub fn default_argument(&self) -> Option<BrackGroup> {
+        self.syntax().children().find_map(BrackGroup::cast)
+    }
+
+    pub fn argument_spec(&self) -> Option<CurlyGroupArgumentSpec> {
+        self.syntax()
+            .children()
+            .find_map(CurlyGroupArgumentSpec::cast)
+    }
+}
+
 cst_node!(AcronymReference, ACRONYM_REFERENCE);
 
 impl AcronymReference {
@@ -64,6 +64,10 @@ pub enum SyntaxKind {
     BEGIN_BLOCK_COMMENT_NAME,
     END_BLOCK_COMMENT_NAME,
     GRAPHICS_PATH_NAME,
+    DOCUMENT_COMMAND_DEFINITION_NAME,
+    DOCUMENT_ENVIRONMENT_DEFINITION_NAME,
+    TEX_DEFINITION_NAME,
+    LET_DEFINITION_NAME,
 
     PREAMBLE,
     TEXT,
@@ -75,6 +79,7 @@ pub enum SyntaxKind {
     CURLY_GROUP_WORD,
     CURLY_GROUP_WORD_LIST,
     CURLY_GROUP_COMMAND,
+    CURLY_GROUP_ARGUMENT_SPEC,
     CURLY_GROUP_KEY_VALUE,
     BRACK_GROUP,
     BRACK_GROUP_WORD,
@@ -164,6 +169,9 @@ impl SyntaxKind {
                 | LABEL_REFERENCE_RANGE_NAME
                 | LABEL_NUMBER_NAME
                 | COMMAND_DEFINITION_NAME
+                | DOCUMENT_COMMAND_DEFINITION_NAME
+                | TEX_DEFINITION_NAME
+                | LET_DEFINITION_NAME
                 | MATH_OPERATOR_NAME
                 | GLOSSARY_ENTRY_DEFINITION_NAME
                 | GLOSSARY_ENTRY_REFERENCE_NAME
@@ -1,6 +1,6 @@
 use logos::Logos;
 
-use super::kind::SyntaxKind;
+use super::{config::SyntaxConfig, kind::SyntaxKind};
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord, Logos)]
 #[allow(non_camel_case_types)]
@@ -246,6 +246,25 @@ enum CommandNameToken {
     #[token("\\DeclareRobustCommand*")]
     CommandDefinition,
 
+    #[token("\\NewDocumentCommand")]
+    #[token("\\RenewDocumentCommand")]
+    #[token("\\ProvideDocumentCommand")]
+    #[token("\\DeclareDocumentCommand")]
+    #[token("\\NewExpandableDocumentCommand")]
+    #[token("\\RenewExpandableDocumentCommand")]
+    #[token("\\ProvideExpandableDocumentCommand")]
+    #[token("\\DeclareExpandableDocumentCommand")]
+    DocumentCommandDefinition,
+
+    #[token("\\def")]
+    #[token("\\gdef")]
+    #[token("\\edef")]
+    #[token("\\xdef")]
+    TexDefinition,
+
+    #[token("\\let")]
+    LetDefinition,
+
     #[token("\\DeclareMathOperator")]
     #[token("\\DeclareMathOperator*")]
     MathOperator,
@@ -377,6 +396,12 @@ enum CommandNameToken {
     #[token("\\renewenvironment*")]
     EnvironmentDefinition,
 
+    #[token("\\NewDocumentEnvironment")]
+    #[token("\\RenewDocumentEnvironment")]
+    #[token("\\ProvideDocumentEnvironment")]
+    #[token("\\DeclareDocumentEnvironment")]
+    DocumentEnvironmentDefinition,
+
     #[token("\\graphicspath")]
     GraphicsPath,
 
@@ -420,9 +445,9 @@ pub struct Lexer<'a> {
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &'a str) -> Self {
+    pub fn new(input: &'a str, config: &SyntaxConfig) -> Self {
         let mut tokens = Vec::new();
-        tokenize(input, &mut tokens);
+        tokenize(input, config, &mut tokens);
         tokens.reverse();
         Self { tokens }
     }
@@ -436,7 +461,7 @@ impl<'a> Lexer<'a> {
     }
 }
 
-fn tokenize<'a>(input: &'a str, tokens: &mut Vec<(SyntaxKind, &'a str)>) {
+fn tokenize<'a>(input: &'a str, config: &SyntaxConfig, tokens: &mut Vec<(SyntaxKind, &'a str)>) {
     let mut lexer = RootToken::lexer(input);
     while let Some(kind) = lexer.next() {
         let text = lexer.slice();
@@ -481,7 +506,9 @@ fn tokenize<'a>(input: &'a str, tokens: &mut Vec<(SyntaxKind, &'a str)>) {
                 tokens.push((SyntaxKind::DOLLAR, text));
             }
             RootToken::CommandName => {
-                let kind = tokenize_command_name(text);
+                let kind = config
+                    .classify(text)
+                    .unwrap_or_else(|| tokenize_command_name(text));
                 tokens.push((kind, text));
             }
             RootToken::BeginBlockComment => {
@@ -536,6 +563,9 @@ fn tokenize_command_name(text: &str) -> SyntaxKind {
         CommandNameToken::LabelReferenceRange => SyntaxKind::LABEL_REFERENCE_RANGE_NAME,
         CommandNameToken::LabelNumber => SyntaxKind::LABEL_NUMBER_NAME,
         CommandNameToken::CommandDefinition => SyntaxKind::COMMAND_DEFINITION_NAME,
+        CommandNameToken::DocumentCommandDefinition => SyntaxKind::DOCUMENT_COMMAND_DEFINITION_NAME,
+        CommandNameToken::TexDefinition => SyntaxKind::TEX_DEFINITION_NAME,
+        CommandNameToken::LetDefinition => SyntaxKind::LET_DEFINITION_NAME,
         CommandNameToken::MathOperator => SyntaxKind::MATH_OPERATOR_NAME,
         CommandNameToken::GlossaryEntryDefinition => SyntaxKind::GLOSSARY_ENTRY_DEFINITION_NAME,
         CommandNameToken::GlossaryEntryReference => SyntaxKind::GLOSSARY_ENTRY_REFERENCE_NAME,
@@ -548,6 +578,9 @@ fn tokenize_command_name(text: &str) -> SyntaxKind {
         CommandNameToken::ColorSetDefinition => SyntaxKind::COLOR_SET_DEFINITION_NAME,
         CommandNameToken::TikzLibraryImport => SyntaxKind::TIKZ_LIBRARY_IMPORT_NAME,
         CommandNameToken::EnvironmentDefinition => SyntaxKind::ENVIRONMENT_DEFINITION_NAME,
+        CommandNameToken::DocumentEnvironmentDefinition => {
+            SyntaxKind::DOCUMENT_ENVIRONMENT_DEFINITION_NAME
+        }
         CommandNameToken::EndBlockComment => SyntaxKind::END_BLOCK_COMMENT_NAME,
         CommandNameToken::GraphicsPath => SyntaxKind::GRAPHICS_PATH_NAME,
         CommandNameToken::Generic => SyntaxKind::GENERIC_COMMAND_NAME,
@@ -608,7 +641,7 @@ mod tests {
     use super::*;
 
     fn verify(text: &str) -> Vec<(SyntaxKind, &str)> {
-        let mut tokens = Lexer::new(text).tokens;
+        let mut tokens = Lexer::new(text, &SyntaxConfig::default()).tokens;
         tokens.reverse();
         tokens
     }
@@ -671,4 +704,17 @@ mod tests {
 \end{asy}"#
         ));
     }
+
+    #[test]
+    fn test_custom_commands() {
+        let config = SyntaxConfig {
+            citation_commands: ["mycite".to_string()].into_iter().collect(),
+            label_reference_commands: ["figref".to_string()].into_iter().collect(),
+            ..SyntaxConfig::default()
+        };
+
+        let mut tokens = Lexer::new(r#"\mycite*{foo} \figref{bar} \cite{baz}"#, &config).tokens;
+        tokens.reverse();
+        assert_debug_snapshot!(tokens);
+    }
 }
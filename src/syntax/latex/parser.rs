@@ -1,6 +1,7 @@
 use rowan::{GreenNode, GreenNodeBuilder};
 
 use super::{
+    config::SyntaxConfig,
     lexer::Lexer,
     SyntaxKind::{self, *},
 };
@@ -32,9 +33,9 @@ struct Parser<'a> {
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(text: &'a str) -> Self {
+    pub fn new(text: &'a str, config: &SyntaxConfig) -> Self {
         Self {
-            lexer: Lexer::new(text),
+            lexer: Lexer::new(text, config),
             builder: GreenNodeBuilder::new(),
         }
     }
@@ -137,6 +138,9 @@ impl<'a> Parser<'a> {
             LABEL_REFERENCE_RANGE_NAME => self.label_reference_range(),
             LABEL_NUMBER_NAME => self.label_number(),
             COMMAND_DEFINITION_NAME => self.command_definition(),
+            DOCUMENT_COMMAND_DEFINITION_NAME => self.document_command_definition(),
+            TEX_DEFINITION_NAME => self.tex_definition(),
+            LET_DEFINITION_NAME => self.let_definition(),
             MATH_OPERATOR_NAME => self.math_operator(),
             GLOSSARY_ENTRY_DEFINITION_NAME => self.glossary_entry_definition(),
             GLOSSARY_ENTRY_REFERENCE_NAME => self.glossary_entry_reference(),
@@ -149,6 +153,7 @@ impl<'a> Parser<'a> {
             COLOR_SET_DEFINITION_NAME => self.color_set_definition(),
             TIKZ_LIBRARY_IMPORT_NAME => self.tikz_library_import(),
             ENVIRONMENT_DEFINITION_NAME => self.environment_definition(),
+            DOCUMENT_ENVIRONMENT_DEFINITION_NAME => self.document_environment_definition(),
             BEGIN_BLOCK_COMMENT_NAME => self.block_comment(),
             END_BLOCK_COMMENT_NAME => self.generic_command(),
             GRAPHICS_PATH_NAME => self.graphics_path(),
@@ -274,6 +279,23 @@ impl<'a> Parser<'a> {
         self.builder.finish_node();
     }
 
+    fn curly_group_argument_spec(&mut self) {
+        self.builder.start_node(CURLY_GROUP_ARGUMENT_SPEC.into());
+        self.eat();
+        while self
+            .peek()
+            .filter(|&kind| !matches!(kind, R_CURLY))
+            .is_some()
+        {
+            self.content(ParserContext {
+                allow_environment: false,
+                allow_comma: true,
+            });
+        }
+        self.expect(R_CURLY);
+        self.builder.finish_node();
+    }
+
     fn brack_group(&mut self) {
         self.builder.start_node(BRACK_GROUP.into());
         self.eat();
@@ -1057,6 +1079,90 @@ impl<'a> Parser<'a> {
         self.builder.finish_node();
     }
 
+    fn document_command_definition(&mut self) {
+        self.builder.start_node(COMMAND_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        match self.lexer.peek() {
+            Some(L_CURLY) => self.curly_group_command(),
+            Some(kind) if kind.is_command_name() => {
+                self.eat();
+                self.trivia();
+            }
+            Some(_) | None => self.builder.token(MISSING.into(), ""),
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_argument_spec();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_impl();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        self.builder.finish_node();
+    }
+
+    fn tex_definition(&mut self) {
+        self.builder.start_node(COMMAND_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        match self.lexer.peek() {
+            Some(kind) if kind.is_command_name() => {
+                self.eat();
+                self.trivia();
+            }
+            Some(_) | None => self.builder.token(MISSING.into(), ""),
+        }
+
+        while matches!(
+            self.lexer.peek(),
+            Some(WORD | WHITESPACE | L_BRACK | R_BRACK | L_PAREN | R_PAREN | COMMA | EQUALITY_SIGN)
+        ) {
+            self.eat();
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_impl();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        self.builder.finish_node();
+    }
+
+    fn let_definition(&mut self) {
+        self.builder.start_node(COMMAND_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        match self.lexer.peek() {
+            Some(kind) if kind.is_command_name() => {
+                self.eat();
+                self.trivia();
+            }
+            Some(_) | None => self.builder.token(MISSING.into(), ""),
+        }
+
+        if self.lexer.peek() == Some(EQUALITY_SIGN) {
+            self.eat();
+            self.trivia();
+        }
+
+        match self.lexer.peek() {
+            Some(kind) if kind.is_command_name() => self.eat(),
+            Some(_) | None => self.builder.token(MISSING.into(), ""),
+        }
+
+        self.builder.finish_node();
+    }
+
     fn math_operator(&mut self) {
         self.builder.start_node(MATH_OPERATOR.into());
         self.eat();
@@ -1316,6 +1422,34 @@ impl<'a> Parser<'a> {
         self.builder.finish_node();
     }
 
+    fn document_environment_definition(&mut self) {
+        self.builder.start_node(ENVIRONMENT_DEFINITION.into());
+        self.eat();
+        self.trivia();
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_word();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        if self.lexer.peek() == Some(L_CURLY) {
+            self.curly_group_argument_spec();
+        } else {
+            self.builder.token(MISSING.into(), "");
+        }
+
+        for _ in 0..2 {
+            if self.lexer.peek() == Some(L_CURLY) {
+                self.curly_group_without_environments();
+            } else {
+                self.builder.token(MISSING.into(), "");
+            }
+        }
+
+        self.builder.finish_node();
+    }
+
     fn graphics_path(&mut self) {
         self.builder.start_node(GRAPHICS_PATH.into());
         self.eat();
@@ -1338,7 +1472,11 @@ impl<'a> Parser<'a> {
 }
 
 pub fn parse(text: &str) -> Parse {
-    Parser::new(text).parse()
+    parse_with_config(text, &SyntaxConfig::default())
+}
+
+pub fn parse_with_config(text: &str, config: &SyntaxConfig) -> Parse {
+    Parser::new(text, config).parse()
 }
 
 #[cfg(test)]
@@ -1753,6 +1891,36 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_document_command_definition() {
+        assert_debug_snapshot!(setup(r#"\NewDocumentCommand{\foo}{s m O{bar}}{#2}"#));
+    }
+
+    #[test]
+    fn test_document_command_definition_without_braces() {
+        assert_debug_snapshot!(setup(r#"\RenewDocumentCommand\foo{m}{#1}"#));
+    }
+
+    #[test]
+    fn test_document_environment_definition() {
+        assert_debug_snapshot!(setup(r#"\NewDocumentEnvironment{foo}{o m}{begin}{end}"#));
+    }
+
+    #[test]
+    fn test_tex_definition() {
+        assert_debug_snapshot!(setup(r#"\def\foo#1#2{#1 and #2}"#));
+    }
+
+    #[test]
+    fn test_tex_definition_delimited() {
+        assert_debug_snapshot!(setup(r#"\gdef\foo[#1]{#1}"#));
+    }
+
+    #[test]
+    fn test_let_definition() {
+        assert_debug_snapshot!(setup(r#"\let\foo=\bar \baz"#));
+    }
+
     #[test]
     fn test_math_operator_simple() {
         assert_debug_snapshot!(setup(r#"\DeclareMathOperator{\foo}{foo}"#));
@@ -10,10 +10,7 @@ use lsp_types::Url;
 use petgraph::{graphmap::UnGraphMap, visit::Dfs};
 use rustc_hash::{FxHashMap, FxHashSet};
 
-use crate::{
-    component_db::COMPONENT_DATABASE, syntax::latex::ExplicitLink, Document, DocumentLanguage,
-    Environment,
-};
+use crate::{syntax::latex::ExplicitLink, Document, DocumentLanguage, Environment};
 
 #[derive(Debug, Clone)]
 pub enum WorkspaceEvent {
@@ -44,7 +41,8 @@ impl Workspace {
         language: DocumentLanguage,
     ) -> Result<Document> {
         log::debug!("(Re)Loading document: {}", uri);
-        let document = Document::parse(&self.environment, Arc::clone(&uri), text, language);
+        let environment = self.environment.scoped(&uri);
+        let document = Document::parse(&environment, Arc::clone(&uri), text, language);
 
         self.documents_by_uri
             .insert(Arc::clone(&uri), document.clone());
@@ -61,7 +59,8 @@ impl Workspace {
     pub fn reload(&mut self, path: PathBuf) -> Result<Option<Document>> {
         let uri = Arc::new(Url::from_file_path(path.clone()).unwrap());
 
-        if self.is_open(&uri) && !uri.as_str().ends_with(".log") {
+        if self.is_open(&uri) && !uri.as_str().ends_with(".log") && !uri.as_str().ends_with(".blg")
+        {
             return Ok(self.documents_by_uri.get(&uri).cloned());
         }
 
@@ -90,6 +89,21 @@ impl Workspace {
         }
     }
 
+    /// Adds documents that have been loaded in the background and returns the added documents.
+    /// Documents that are already part of the workspace are kept since they might be more recent.
+    pub fn extend(&mut self, documents: Vec<Document>) -> Vec<Document> {
+        let mut added = Vec::new();
+        for document in documents {
+            if !self.documents_by_uri.contains_key(&document.uri) {
+                self.documents_by_uri
+                    .insert(Arc::clone(&document.uri), document.clone());
+                added.push(document);
+            }
+        }
+
+        added
+    }
+
     pub fn close(&mut self, uri: &Url) {
         self.viewport.remove(uri);
     }
@@ -113,8 +127,14 @@ impl Workspace {
                         .and_then(|document| document.data.as_latex())
                     {
                         let extras = &data.extras;
-                        let mut all_targets =
-                            vec![&extras.implicit_links.aux, &extras.implicit_links.log];
+                        let magic_root: Vec<_> =
+                            extras.magic_comments.root.iter().cloned().collect();
+                        let mut all_targets = vec![
+                            &extras.implicit_links.aux,
+                            &extras.implicit_links.log,
+                            &extras.implicit_links.blg,
+                            &magic_root,
+                        ];
                         for link in &extras.explicit_links {
                             all_targets.push(&link.targets);
                         }
@@ -131,6 +151,7 @@ impl Workspace {
                 }
 
                 let mut slice = self.clone();
+                slice.environment = self.environment.scoped(uri);
                 slice.documents_by_uri = FxHashMap::default();
                 let graph = UnGraphMap::from_edges(edges);
                 let mut dfs = Dfs::new(&graph, start);
@@ -145,8 +166,29 @@ impl Workspace {
             .unwrap_or_default()
     }
 
+    /// Returns the root document that has been declared with a `% !TEX root` magic comment
+    /// inside of the given document.
+    #[must_use]
+    pub fn find_declared_root(&self, uri: &Url) -> Option<Document> {
+        let root = self
+            .documents_by_uri
+            .get(uri)?
+            .data
+            .as_latex()?
+            .extras
+            .magic_comments
+            .root
+            .as_ref()?;
+
+        self.documents_by_uri.get(root).cloned()
+    }
+
     #[must_use]
     pub fn find_parent(&self, uri: &Url) -> Option<Document> {
+        if let Some(root) = self.find_declared_root(uri) {
+            return Some(root);
+        }
+
         self.slice(uri)
             .documents_by_uri
             .values()
@@ -198,11 +240,17 @@ impl Workspace {
     fn expand_children(&mut self, document: &Document) {
         if let Some(data) = document.data.as_latex() {
             let extras = &data.extras;
-            let mut all_targets = vec![&extras.implicit_links.aux, &extras.implicit_links.log];
+            let magic_root: Vec<_> = extras.magic_comments.root.iter().cloned().collect();
+            let mut all_targets = vec![
+                &extras.implicit_links.aux,
+                &extras.implicit_links.log,
+                &extras.implicit_links.blg,
+                &magic_root,
+            ];
             for link in &extras.explicit_links {
                 if link
                     .as_component_name()
-                    .and_then(|name| COMPONENT_DATABASE.find(&name))
+                    .and_then(|name| self.environment.component_db.find(&name))
                     .is_none()
                 {
                     all_targets.push(&link.targets);
@@ -1,11 +1,13 @@
+use std::time::Duration;
+
 use anyhow::{bail, Result};
 use crossbeam_channel::{Receiver, Sender};
 use lsp_server::{Connection, Message, Notification, Request, Response};
 use lsp_types::{
-    notification::{Exit, Initialized},
+    notification::{Exit, Initialized, PublishDiagnostics},
     request::{Initialize, Shutdown},
-    ClientCapabilities, ClientInfo, DidOpenTextDocumentParams, InitializeParams, InitializeResult,
-    InitializedParams, Url,
+    ClientCapabilities, ClientInfo, Diagnostic, DidOpenTextDocumentParams, InitializeParams,
+    InitializeResult, InitializedParams, PublishDiagnosticsParams, Url,
 };
 use tempfile::{tempdir, TempDir};
 use texlab::Server;
@@ -149,6 +151,24 @@ impl Client {
         Ok(())
     }
 
+    pub fn wait_for_diagnostics(&self, uri: &Url) -> Result<Vec<Diagnostic>> {
+        use lsp_types::notification::Notification;
+
+        loop {
+            let notification = self
+                .incoming
+                .notifications
+                .recv_timeout(Duration::from_secs(10))?;
+
+            if notification.method == PublishDiagnostics::METHOD {
+                let params: PublishDiagnosticsParams = serde_json::from_value(notification.params)?;
+                if &params.uri == uri && !params.diagnostics.is_empty() {
+                    return Ok(params.diagnostics);
+                }
+            }
+        }
+    }
+
     pub fn shutdown(mut self) -> Result<ClientResult> {
         self.request::<Shutdown>(())?;
         self.notify::<Exit>(())?;
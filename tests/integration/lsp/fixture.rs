@@ -22,6 +22,8 @@ fn parse_line(line: &str) -> Line {
         Line::File(name, "bibtex")
     } else if let Some(name) = line.strip_prefix("%LOG ") {
         Line::File(name, "log")
+    } else if let Some(name) = line.strip_prefix("%BLG ") {
+        Line::File(name, "blg")
     } else if let Some(text) = line.strip_prefix("%SRC ") {
         Line::Plain(text)
     } else if let Some(text) = line.strip_prefix("%CUR ") {
@@ -1,3 +1,5 @@
+mod call_hierarchy;
+mod code_action;
 #[cfg(feature = "completion")]
 mod completion;
 mod definition;
@@ -8,6 +10,9 @@ mod document_symbol;
 mod folding_range;
 mod formatting;
 mod hover;
+mod inlay_hint;
 mod publish_diagnostics;
 mod references;
 mod rename;
+mod semantic_tokens;
+mod signature_help;
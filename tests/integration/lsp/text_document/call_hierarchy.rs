@@ -0,0 +1,270 @@
+use anyhow::Result;
+use lsp_types::{
+    request::{CallHierarchyIncomingCalls, CallHierarchyOutgoingCalls, CallHierarchyPrepare},
+    CallHierarchyIncomingCallsParams, CallHierarchyItem, CallHierarchyOutgoingCallsParams,
+    CallHierarchyPrepareParams, ClientCapabilities, Position, Range, TextDocumentIdentifier,
+    TextDocumentPositionParams, Url,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::lsp::{client::Client, fixture};
+
+const FIXTURE: &str = r#"
+%TEX main.tex
+%SRC \documentclass{article}
+%SRC \begin{document}
+%SRC \input{child}
+%SRC \include{missing}
+%SRC \bibliography{main}
+%SRC \end{document}
+
+%TEX child.tex
+%SRC \input{grandchild}
+%SRC \input{grandchild.tex}
+
+%TEX grandchild.tex
+%SRC Foo
+
+%BIB main.bib
+%SRC @article{foo,}
+"#;
+
+fn open_fixture(client: &mut Client) -> Result<()> {
+    client.initialize(ClientCapabilities::default(), None)?;
+    for file in fixture::parse(FIXTURE).files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    Ok(())
+}
+
+fn prepare(client: &mut Client, name: &str, position: Position) -> Result<CallHierarchyItem> {
+    let items = client.request::<CallHierarchyPrepare>(CallHierarchyPrepareParams {
+        text_document_position_params: TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(client.uri(name)?),
+            position,
+        ),
+        work_done_progress_params: Default::default(),
+    })?;
+
+    Ok(items.unwrap().remove(0))
+}
+
+#[test]
+fn prepare_include() -> Result<()> {
+    let mut client = Client::spawn()?;
+    open_fixture(&mut client)?;
+    let item = prepare(&mut client, "main.tex", Position::new(2, 9))?;
+    let current = prepare(&mut client, "main.tex", Position::new(0, 0))?;
+    let expected_uri = client.uri("child.tex")?;
+    let main_uri = client.uri("main.tex")?;
+    client.shutdown()?;
+
+    assert_eq!(item.name, "child.tex");
+    assert_eq!(item.uri, expected_uri);
+    assert_eq!(current.uri, main_uri);
+    Ok(())
+}
+
+#[test]
+fn incoming_calls() -> Result<()> {
+    let mut client = Client::spawn()?;
+    open_fixture(&mut client)?;
+    let item = prepare(&mut client, "child.tex", Position::new(0, 0))?;
+    let calls = client.request::<CallHierarchyIncomingCalls>(CallHierarchyIncomingCallsParams {
+        item,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    })?;
+
+    let main_uri = client.uri("main.tex")?;
+    client.shutdown()?;
+
+    let calls: Vec<_> = calls
+        .unwrap()
+        .into_iter()
+        .map(|call| (call.from.uri, call.from_ranges))
+        .collect();
+
+    assert_eq!(
+        calls,
+        vec![(
+            main_uri,
+            vec![Range::new(Position::new(2, 7), Position::new(2, 12))]
+        )]
+    );
+    Ok(())
+}
+
+#[test]
+fn outgoing_calls() -> Result<()> {
+    let mut client = Client::spawn()?;
+    open_fixture(&mut client)?;
+    let item = prepare(&mut client, "child.tex", Position::new(0, 0))?;
+    let calls = client.request::<CallHierarchyOutgoingCalls>(CallHierarchyOutgoingCallsParams {
+        item,
+        work_done_progress_params: Default::default(),
+        partial_result_params: Default::default(),
+    })?;
+
+    let grandchild_uri = client.uri("grandchild.tex")?;
+    client.shutdown()?;
+
+    let calls: Vec<_> = calls
+        .unwrap()
+        .into_iter()
+        .map(|call| (call.to.uri, call.from_ranges))
+        .collect();
+
+    assert_eq!(
+        calls,
+        vec![(
+            grandchild_uri,
+            vec![
+                Range::new(Position::new(0, 7), Position::new(0, 17)),
+                Range::new(Position::new(1, 7), Position::new(1, 21)),
+            ]
+        )]
+    );
+    Ok(())
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncludeGraphParams {
+    text_document: TextDocumentIdentifier,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IncludeTree {
+    uri: Url,
+    includes: Vec<Include>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Include {
+    path: String,
+    kind: String,
+    range: Range,
+    target: Option<IncludeTree>,
+}
+
+struct ProjectRootRequest;
+
+impl lsp_types::request::Request for ProjectRootRequest {
+    type Params = IncludeGraphParams;
+
+    type Result = Option<Url>;
+
+    const METHOD: &'static str = "textDocument/projectRoot";
+}
+
+struct IncludeTreeRequest;
+
+impl lsp_types::request::Request for IncludeTreeRequest {
+    type Params = IncludeGraphParams;
+
+    type Result = Option<IncludeTree>;
+
+    const METHOD: &'static str = "textDocument/includeTree";
+}
+
+#[test]
+fn project_root() -> Result<()> {
+    let mut client = Client::spawn()?;
+    open_fixture(&mut client)?;
+    let root = client.request::<ProjectRootRequest>(IncludeGraphParams {
+        text_document: TextDocumentIdentifier::new(client.uri("grandchild.tex")?),
+    })?;
+
+    let main_uri = client.uri("main.tex")?;
+    client.shutdown()?;
+
+    assert_eq!(root, Some(main_uri));
+    Ok(())
+}
+
+#[test]
+fn project_root_magic_comment() -> Result<()> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+    let fixture = fixture::parse(
+        r#"
+%TEX thesis.tex
+%SRC \documentclass{article}
+%SRC \begin{document}
+%SRC \input{figure}
+%SRC \end{document}
+
+%TEX figure.tex
+%SRC % !TEX root = standalone.tex
+%SRC Foo
+
+%TEX standalone.tex
+%SRC \documentclass{standalone}
+%SRC \begin{document}
+%SRC \input{figure}
+%SRC \end{document}
+"#,
+    );
+
+    for file in fixture.files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    let root = client.request::<ProjectRootRequest>(IncludeGraphParams {
+        text_document: TextDocumentIdentifier::new(client.uri("figure.tex")?),
+    })?;
+
+    let standalone_uri = client.uri("standalone.tex")?;
+    client.shutdown()?;
+
+    assert_eq!(root, Some(standalone_uri));
+    Ok(())
+}
+
+#[test]
+fn include_tree() -> Result<()> {
+    let mut client = Client::spawn()?;
+    open_fixture(&mut client)?;
+    let tree = client.request::<IncludeTreeRequest>(IncludeGraphParams {
+        text_document: TextDocumentIdentifier::new(client.uri("child.tex")?),
+    })?;
+
+    let leaf = |uri| IncludeTree {
+        uri,
+        includes: Vec::new(),
+    };
+
+    let include = |path: &str, kind: &str, (line, start, end), target| Include {
+        path: path.into(),
+        kind: kind.into(),
+        range: Range::new(Position::new(line, start), Position::new(line, end)),
+        target,
+    };
+
+    let grandchild = leaf(client.uri("grandchild.tex")?);
+    let child = IncludeTree {
+        uri: client.uri("child.tex")?,
+        includes: vec![
+            include("grandchild", "latex", (0, 7, 17), Some(grandchild.clone())),
+            include("grandchild.tex", "latex", (1, 7, 21), Some(grandchild)),
+        ],
+    };
+
+    let bibliography = leaf(client.uri("main.bib")?);
+    let expected = IncludeTree {
+        uri: client.uri("main.tex")?,
+        includes: vec![
+            include("child", "latex", (2, 7, 12), Some(child)),
+            include("missing", "latex", (3, 9, 16), None),
+            include("main", "bibtex", (4, 14, 18), Some(bibliography)),
+        ],
+    };
+
+    client.shutdown()?;
+    assert_eq!(tree, Some(expected));
+    Ok(())
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use insta::assert_snapshot;
+use lsp_types::{
+    request::CodeActionRequest, ClientCapabilities, CodeActionContext, CodeActionOrCommand,
+    CodeActionParams, Range, TextDocumentIdentifier,
+};
+
+use crate::lsp::{client::Client, fixture};
+
+fn find_actions(fixture: &str, with_diagnostics: bool) -> Result<String> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+
+    let fixture = fixture::parse(fixture);
+    for file in fixture.files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    let cursor = fixture.cursor.unwrap();
+    let uri = client.uri(cursor.name)?;
+    let diagnostics = if with_diagnostics {
+        client.wait_for_diagnostics(&uri)?
+    } else {
+        Vec::new()
+    };
+
+    let actions = client
+        .request::<CodeActionRequest>(CodeActionParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            range: Range::new(cursor.position, cursor.position),
+            context: CodeActionContext {
+                diagnostics,
+                only: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })?
+        .unwrap_or_default();
+
+    let base_uri = client.uri("")?;
+    client.shutdown()?;
+
+    let mut output = String::new();
+    for action in actions {
+        if let CodeActionOrCommand::CodeAction(action) = action {
+            output.push_str(&format!("{}\n", action.title));
+            let mut changes: Vec<_> = action.edit.unwrap().changes.unwrap().into_iter().collect();
+            changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+            for (uri, edits) in changes {
+                for edit in edits {
+                    output.push_str(&format!(
+                        "  {} {}:{}-{}:{} {:?}\n",
+                        uri.as_str().replace(base_uri.as_str(), ""),
+                        edit.range.start.line,
+                        edit.range.start.character,
+                        edit.range.end.line,
+                        edit.range.end.character,
+                        edit.new_text
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(output)
+}
+
+#[test]
+fn latex_mismatched_environment() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%TEX main.tex
+%SRC \begin{foo}
+%CUR        ^
+%SRC \end{bar}
+"#,
+        true
+    )?);
+    Ok(())
+}
+
+#[test]
+fn latex_rcurly_inserted() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%TEX main.tex
+%SRC \section{foo
+%CUR             ^
+"#,
+        true
+    )?);
+    Ok(())
+}
+
+#[test]
+fn bibtex_expecting_eq() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%BIB main.bib
+%SRC @article{foo, author {bar}}
+%CUR               ^
+"#,
+        true
+    )?);
+    Ok(())
+}
+
+#[test]
+fn bibtex_expecting_field_value() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%BIB main.bib
+%SRC @article{foo, author = , title = {bar}}
+%CUR               ^
+"#,
+        true
+    )?);
+    Ok(())
+}
+
+#[test]
+fn package_import() -> Result<()> {
+    let output = find_actions(
+        r#"
+%TEX main.tex
+%SRC \documentclass{article}
+%SRC \begin{document}
+%SRC \eqref{foo}
+%CUR    ^
+%SRC \end{document}
+"#,
+        false,
+    )?;
+
+    assert!(output.contains(
+        "Add \\usepackage{amsmath}\n  /main.tex 0:23-0:23 \"\\n\\\\usepackage{amsmath}\"\n"
+    ));
+    Ok(())
+}
+
+#[test]
+fn package_import_user_defined() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%TEX main.tex
+%SRC \newcommand{\eqref}{foo}
+%SRC \eqref{foo}
+%CUR    ^
+"#,
+        false,
+    )?);
+    Ok(())
+}
+
+#[test]
+fn undefined_label() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%TEX main.tex
+%SRC \begin{document}
+%SRC See \ref{sec:foo} for details.
+%CUR           ^
+%SRC \end{document}
+"#,
+        true
+    )?);
+    Ok(())
+}
+
+#[test]
+fn undefined_label_without_diagnostic() -> Result<()> {
+    assert_eq!(
+        find_actions(
+            r#"
+%TEX main.tex
+%SRC See \ref{sec:foo} for details.
+%CUR           ^
+"#,
+            false
+        )?,
+        ""
+    );
+    Ok(())
+}
+
+#[test]
+fn undefined_citation() -> Result<()> {
+    assert_snapshot!(find_actions(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{main.bib}
+%SRC \cite{bar}
+%CUR        ^
+
+%BIB main.bib
+%SRC @article{foo, author = {Foo}}
+"#,
+        true
+    )?);
+    Ok(())
+}
@@ -1,21 +1,42 @@
 use anyhow::Result;
 use insta::assert_json_snapshot;
 use lsp_types::{
+    notification::DidChangeConfiguration,
     request::{Completion, ResolveCompletionItem},
-    ClientCapabilities, CompletionItem, CompletionList, CompletionParams, CompletionResponse,
-    CompletionTextEdit, Range,
+    ClientCapabilities, CompletionClientCapabilities, CompletionItem, CompletionItemCapability,
+    CompletionList, CompletionParams, CompletionResponse, CompletionTextEdit,
+    DidChangeConfigurationParams, InsertTextFormat, Range, TextDocumentClientCapabilities,
 };
 
 use crate::lsp::{client::Client, fixture};
 
 fn complete(fixture: &str) -> Result<Vec<CompletionItem>, anyhow::Error> {
+    complete_with_capabilities(fixture, ClientCapabilities::default())
+}
+
+fn complete_with_capabilities(
+    fixture: &str,
+    client_capabilities: ClientCapabilities,
+) -> Result<Vec<CompletionItem>, anyhow::Error> {
+    complete_with_settings(fixture, client_capabilities, None)
+}
+
+fn complete_with_settings(
+    fixture: &str,
+    client_capabilities: ClientCapabilities,
+    settings: Option<serde_json::Value>,
+) -> Result<Vec<CompletionItem>, anyhow::Error> {
     let mut client = Client::spawn()?;
-    client.initialize(ClientCapabilities::default(), None)?;
+    client.initialize(client_capabilities, None)?;
     let fixture = fixture::parse(fixture);
     for file in fixture.files {
         client.open(file.name, file.lang, file.text)?;
     }
 
+    if let Some(settings) = settings {
+        client.notify::<DidChangeConfiguration>(DidChangeConfigurationParams { settings })?;
+    }
+
     let range = fixture
         .ranges
         .values()
@@ -662,6 +683,119 @@ fn field_entry_two_fields_name_closed() -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn component_option_package() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \usepackage[fleqn, le]{amsmath}
+%CUR                     ^
+%1.1                    ^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn component_option_package_value() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \usepackage[mar]{geometry}
+%CUR                ^
+%1.1             ^^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn component_option_class() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \documentclass[a4]{article}
+%CUR                  ^
+%1.1                ^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn component_option_local_package() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \usepackage[draft, fi]{foo}
+%CUR                     ^
+%1.1                    ^^
+
+%TEX foo.sty
+%SRC \DeclareOption{draft}{}
+%SRC \DeclareOption{final}{}
+%SRC \DeclareOption{print}{}"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn component_option_local_class() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \documentclass[]{foo}
+%CUR                ^
+
+%TEX foo.cls
+%SRC \DeclareOption{draft}{}
+%SRC \DeclareKeys{fontsize .store = \foo@size}"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn key_value_key() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \includegraphics[width=3cm, he]{foo}
+%CUR                               ^
+%1.1                             ^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn key_value_choice() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \hypersetup{linktoc=pa}
+%CUR                      ^
+%1.1                     ^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn key_value_compact() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \begin{figure}[ht]
+%CUR                  ^
+%1.1                ^^
+%SRC \end{figure}"#
+    )?);
+
+    Ok(())
+}
+
 #[test]
 fn import_package_open_brace() -> Result<()> {
     assert_items!(complete(
@@ -851,6 +985,67 @@ fn test_user_command() -> Result<()> {
     Ok(())
 }
 
+fn find_text_edit(items: &[CompletionItem], label: &str) -> (String, Option<InsertTextFormat>) {
+    let item = items.iter().find(|item| item.label == label).unwrap();
+    match item.text_edit.as_ref() {
+        Some(CompletionTextEdit::Edit(edit)) => (edit.new_text.clone(), item.insert_text_format),
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn user_command_snippet() -> Result<()> {
+    let capabilities = ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    snippet_support: Some(true),
+                    ..CompletionItemCapability::default()
+                }),
+                ..CompletionClientCapabilities::default()
+            }),
+            ..TextDocumentClientCapabilities::default()
+        }),
+        ..ClientCapabilities::default()
+    };
+
+    let items = complete_with_capabilities(
+        r#"
+%TEX main.tex
+%SRC \NewDocumentCommand{\myfoo}{s m O{x} r()}{}
+%SRC \myfo
+%CUR     ^
+%1.1  ^^^^"#,
+        capabilities,
+    )?;
+
+    assert_eq!(
+        find_text_edit(&items, "myfoo"),
+        (
+            "myfoo{${1}\\}(${2})".to_string(),
+            Some(InsertTextFormat::SNIPPET)
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn user_command_without_snippet_support() -> Result<()> {
+    let items = complete(
+        r#"
+%TEX main.tex
+%SRC \def\myfoo#1{#1}
+%SRC \myfo
+%CUR     ^
+%1.1  ^^^^"#,
+    )?;
+
+    assert_eq!(find_text_edit(&items, "myfoo"), ("myfoo".to_string(), None));
+
+    Ok(())
+}
+
 #[test]
 fn test_user_environment() -> Result<()> {
     assert_items!(complete(
@@ -868,3 +1063,137 @@ fn test_user_environment() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn math_symbol_name() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC $\ph$
+%CUR     ^
+%1.1   ^^"#
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn math_symbol_unicode_name() -> Result<()> {
+    assert_items!(complete(
+        r#"
+%TEX main.tex
+%SRC \[\rightwards\]
+%CUR              ^
+%1.1    ^^^^^^^^^^"#
+    )?);
+
+    Ok(())
+}
+
+fn snippet_capabilities() -> ClientCapabilities {
+    ClientCapabilities {
+        text_document: Some(TextDocumentClientCapabilities {
+            completion: Some(CompletionClientCapabilities {
+                completion_item: Some(CompletionItemCapability {
+                    snippet_support: Some(true),
+                    ..CompletionItemCapability::default()
+                }),
+                ..CompletionClientCapabilities::default()
+            }),
+            ..TextDocumentClientCapabilities::default()
+        }),
+        ..ClientCapabilities::default()
+    }
+}
+
+#[test]
+fn environment_snippet_template() -> Result<()> {
+    let items = complete_with_capabilities(
+        r#"
+%TEX main.tex
+%SRC \begin{fig}
+%CUR           ^
+%1.1        ^^^^"#,
+        snippet_capabilities(),
+    )?;
+
+    assert_eq!(
+        find_text_edit(&items, "figure"),
+        (
+            "figure}[${1:htbp}]\n\t\\\\centering\n\t\\\\includegraphics[width=${2:\\\\linewidth}]{${3:file}}\n\t\\\\caption{${4:caption}}\n\t\\\\label{fig:${5:name}}\n\\\\end{figure}".to_string(),
+            Some(InsertTextFormat::SNIPPET)
+        )
+    );
+
+    Ok(())
+}
+
+#[test]
+fn environment_snippet_existing_end() -> Result<()> {
+    let items = complete_with_capabilities(
+        r#"
+%TEX main.tex
+%SRC \begin{ite}
+%CUR           ^
+%1.1        ^^^
+%SRC \end{ite}"#,
+        snippet_capabilities(),
+    )?;
+
+    assert_eq!(
+        find_text_edit(&items, "itemize"),
+        ("itemize".to_string(), None)
+    );
+    Ok(())
+}
+
+#[test]
+fn environment_snippet_user_template() -> Result<()> {
+    let items = complete_with_settings(
+        r#"
+%TEX main.tex
+%SRC \begin{document}
+%SRC \begin{fra}
+%CUR           ^
+%1.1        ^^^^
+%SRC \end{document}"#,
+        snippet_capabilities(),
+        Some(serde_json::json!({
+            "completion": {
+                "environmentTemplates": [
+                    { "name": "frame", "arguments": "{${1:title}}", "body": ["$0"] }
+                ]
+            }
+        })),
+    )?;
+
+    assert_eq!(
+        find_text_edit(&items, "frame"),
+        (
+            "frame}{${1:title}}\n\t$0\n\\\\end{frame}".to_string(),
+            Some(InsertTextFormat::SNIPPET)
+        )
+    );
+
+    let item = items.iter().find(|item| item.label == "frame").unwrap();
+    assert_eq!(item.detail.as_deref(), Some("user-defined template"));
+
+    Ok(())
+}
+
+#[test]
+fn environment_without_snippet_support() -> Result<()> {
+    let items = complete(
+        r#"
+%TEX main.tex
+%SRC \begin{ite}
+%CUR           ^
+%1.1        ^^^"#,
+    )?;
+
+    assert_eq!(
+        find_text_edit(&items, "itemize"),
+        ("itemize".to_string(), None)
+    );
+    Ok(())
+}
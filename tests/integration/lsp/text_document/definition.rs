@@ -58,6 +58,51 @@ fn command_definition() -> Result<()> {
     )
 }
 
+#[test]
+fn command_definition_xparse() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \NewDocumentCommand{\foo}{m O{bar}}{#1}
+%1.3                     ^^^^
+%1.2 ^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^^
+%SRC \foo{baz}
+%CUR   ^
+%1.1 ^^^^
+"#,
+    )
+}
+
+#[test]
+fn command_definition_tex() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \def\foo#1{#1}
+%1.3     ^^^^
+%1.2 ^^^^^^^^^^^^^^
+%SRC \foo{bar}
+%CUR   ^
+%1.1 ^^^^
+"#,
+    )
+}
+
+#[test]
+fn command_definition_let() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \let\foo\relax
+%1.3     ^^^^
+%1.2 ^^^^^^^^^^^^^^
+%SRC \foo
+%CUR   ^
+%1.1 ^^^^
+"#,
+    )
+}
+
 #[test]
 fn document() -> Result<()> {
     check(
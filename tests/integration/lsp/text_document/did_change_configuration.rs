@@ -1,11 +1,14 @@
 use anyhow::Result;
 use insta::assert_snapshot;
 use lsp_types::{
-    notification::{DidChangeConfiguration, Notification, ShowMessage},
-    ClientCapabilities, DidChangeConfigurationParams, ShowMessageParams,
+    notification::{DidChangeConfiguration, DidChangeWatchedFiles, Notification, ShowMessage},
+    request::GotoDefinition,
+    ClientCapabilities, DidChangeConfigurationParams, DidChangeWatchedFilesParams, FileChangeType,
+    FileEvent, GotoDefinitionParams, GotoDefinitionResponse, LocationLink, Position,
+    ShowMessageParams, TextDocumentIdentifier, TextDocumentPositionParams,
 };
 
-use crate::lsp::client::Client;
+use crate::lsp::{client::Client, fixture};
 
 #[test]
 fn invalid_configuration() -> Result<()> {
@@ -37,3 +40,137 @@ fn invalid_configuration() -> Result<()> {
     assert_snapshot!(message);
     Ok(())
 }
+
+#[test]
+fn custom_label_reference_commands() -> Result<()> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+
+    let fixture = fixture::parse(
+        r#"
+%TEX main.tex
+%SRC \label{foo}
+%1.3        ^^^
+%1.2        ^^^
+%SRC \figref{foo}
+%CUR          ^
+%1.1         ^^^
+"#,
+    );
+
+    for file in fixture.files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    client.notify::<DidChangeConfiguration>(DidChangeConfigurationParams {
+        settings: serde_json::json!({
+            "experimental": {
+                "labelReferenceCommands": ["figref"]
+            }
+        }),
+    })?;
+
+    let ranges = &fixture.ranges[&1];
+    let expected_links = vec![LocationLink {
+        origin_selection_range: Some(ranges[&1].range),
+        target_uri: client.uri(ranges[&2].name)?,
+        target_range: ranges[&2].range,
+        target_selection_range: ranges[&3].range,
+    }];
+
+    let actual_links = client
+        .request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: fixture.cursor.unwrap().into_params(&client)?,
+            partial_result_params: Default::default(),
+            work_done_progress_params: Default::default(),
+        })?
+        .map_or(Vec::new(), |actual| match actual {
+            GotoDefinitionResponse::Link(links) => links,
+            GotoDefinitionResponse::Array(_) | GotoDefinitionResponse::Scalar(_) => unreachable!(),
+        });
+
+    client.shutdown()?;
+
+    assert_eq!(actual_links, expected_links);
+    Ok(())
+}
+
+fn setup_project(client: &mut Client) -> Result<()> {
+    client.initialize(ClientCapabilities::default(), None)?;
+    client.store_on_disk(
+        "project/texlabrc",
+        r#"{ "experimental": { "labelReferenceCommands": ["figref"] } }"#,
+    )?;
+
+    for name in ["project/main.tex", "other/main.tex"] {
+        client.open(name, "latex", String::from("\\label{foo}\n\\figref{foo}"))?;
+    }
+
+    Ok(())
+}
+
+fn notify_project_config(client: &mut Client, typ: FileChangeType) -> Result<()> {
+    client.notify::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(client.uri("project/texlabrc")?, typ)],
+    })
+}
+
+fn find_definitions(client: &mut Client, name: &str) -> Result<usize> {
+    let count = client
+        .request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(client.uri(name)?),
+                Position::new(1, 9),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })?
+        .map_or(0, |response| match response {
+            GotoDefinitionResponse::Link(links) => links.len(),
+            GotoDefinitionResponse::Array(_) | GotoDefinitionResponse::Scalar(_) => unreachable!(),
+        });
+
+    Ok(count)
+}
+
+#[test]
+fn project_config() -> Result<()> {
+    let mut client = Client::spawn()?;
+    setup_project(&mut client)?;
+    assert_eq!(find_definitions(&mut client, "project/main.tex")?, 1);
+    assert_eq!(find_definitions(&mut client, "other/main.tex")?, 0);
+
+    client.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn project_config_changed() -> Result<()> {
+    let mut client = Client::spawn()?;
+    setup_project(&mut client)?;
+    assert_eq!(find_definitions(&mut client, "project/main.tex")?, 1);
+
+    client.store_on_disk(
+        "project/texlabrc",
+        r#"{ "experimental": { "labelReferenceCommands": [] } }"#,
+    )?;
+    notify_project_config(&mut client, FileChangeType::CHANGED)?;
+    assert_eq!(find_definitions(&mut client, "project/main.tex")?, 0);
+
+    client.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn project_config_deleted() -> Result<()> {
+    let mut client = Client::spawn()?;
+    setup_project(&mut client)?;
+    assert_eq!(find_definitions(&mut client, "project/main.tex")?, 1);
+
+    std::fs::remove_file(client.uri("project/texlabrc")?.to_file_path().unwrap())?;
+    notify_project_config(&mut client, FileChangeType::DELETED)?;
+    assert_eq!(find_definitions(&mut client, "project/main.tex")?, 0);
+
+    client.shutdown()?;
+    Ok(())
+}
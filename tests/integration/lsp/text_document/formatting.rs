@@ -1,14 +1,32 @@
 use anyhow::Result;
 use insta::assert_snapshot;
 use lsp_types::{
-    request::Formatting, ClientCapabilities, DocumentFormattingParams, FormattingOptions,
-    TextDocumentIdentifier,
+    notification::DidChangeConfiguration,
+    request::{Formatting, OnTypeFormatting, RangeFormatting},
+    ClientCapabilities, DidChangeConfigurationParams, DocumentFormattingParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, FormattingOptions,
+    TextDocumentIdentifier, TextDocumentPositionParams,
 };
 use texlab::{LineIndex, LineIndexExt};
 
 use crate::lsp::{client::Client, fixture};
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum FormattingKind {
+    Document,
+    Range,
+    OnType,
+}
+
 fn format(fixture: &str) -> Result<String> {
+    format_with_settings(fixture, serde_json::json!({}), FormattingKind::Document)
+}
+
+fn format_with_settings(
+    fixture: &str,
+    settings: serde_json::Value,
+    kind: FormattingKind,
+) -> Result<String> {
     let mut client = Client::spawn()?;
     client.initialize(ClientCapabilities::default(), None)?;
 
@@ -16,18 +34,41 @@ fn format(fixture: &str) -> Result<String> {
     let file = fixture.files.into_iter().next().unwrap();
     let old_text = file.text.clone();
     client.open(file.name, file.lang, file.text)?;
+    client.notify::<DidChangeConfiguration>(DidChangeConfigurationParams { settings })?;
+
+    let text_document = TextDocumentIdentifier::new(client.uri(file.name)?);
+    let options = FormattingOptions {
+        insert_spaces: true,
+        tab_size: 4,
+        ..Default::default()
+    };
 
-    let actual_edits = client
-        .request::<Formatting>(DocumentFormattingParams {
-            text_document: TextDocumentIdentifier::new(client.uri(file.name)?),
+    let actual_edits = match kind {
+        FormattingKind::Document => client.request::<Formatting>(DocumentFormattingParams {
+            text_document,
             work_done_progress_params: Default::default(),
-            options: FormattingOptions {
-                insert_spaces: true,
-                tab_size: 4,
-                ..Default::default()
-            },
-        })?
-        .unwrap_or_default();
+            options,
+        })?,
+        FormattingKind::Range => {
+            client.request::<RangeFormatting>(DocumentRangeFormattingParams {
+                text_document,
+                range: fixture.ranges[&1][&1].range,
+                work_done_progress_params: Default::default(),
+                options,
+            })?
+        }
+        FormattingKind::OnType => {
+            client.request::<OnTypeFormatting>(DocumentOnTypeFormattingParams {
+                text_document_position: TextDocumentPositionParams::new(
+                    text_document,
+                    fixture.cursor.unwrap().position,
+                ),
+                ch: "}".to_string(),
+                options,
+            })?
+        }
+    }
+    .unwrap_or_default();
 
     client.shutdown()?;
 
@@ -146,3 +187,158 @@ fn bibtex_internal_preamble() -> Result<()> {
 
     Ok(())
 }
+
+fn format_latex_internal(fixture: &str, kind: FormattingKind) -> Result<String> {
+    format_with_settings(
+        fixture,
+        serde_json::json!({
+            "latexFormatter": "texlab",
+            "formatterLineLength": 40
+        }),
+        kind,
+    )
+}
+
+#[test]
+fn latex_internal_environment() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{document}
+%SRC \begin{itemize}
+%SRC \item foo
+%SRC       \item bar
+%SRC \end{itemize}
+%SRC \end{document}"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_nested_environments() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{figure}[h] % placement
+%SRC % first comment
+%SRC \begin{center}
+%SRC foo
+%SRC 
+%SRC \end{center}
+%SRC \end{figure}"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_begin_end_placement() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC foo \begin{center} bar \end{center} baz
+%SRC \begin{tabular} {cc}a & b\end{tabular}
+%SRC \begin{tabular}{cc} c & d \end{tabular}
+%SRC \begin{center}foo\end{center}"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_display_math() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \[
+%SRC a + b
+%SRC \]
+%SRC $\begin{array}{c} x \end{array}$"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_verbatim() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{center}
+%SRC \begin{verbatim}
+%SRC   foo    bar
+%SRC \end{verbatim}
+%SRC \end{center}"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_wrap_long_lines() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{center}
+%SRC Lorem ipsum dolor sit amet, consectetur adipiscing elit. % a comment that stays
+%SRC \verb|Lorem ipsum dolor sit amet, consectetur adipiscing elit.|
+%SRC \end{center}"#,
+        FormattingKind::Document,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn bibtex_internal_range() -> Result<()> {
+    assert_snapshot!(format_with_settings(
+        r#"
+%BIB main.bib
+%SRC @article{foo, bar = baz}
+%SRC @article{qux, bar = baz}
+%1.1           ^^^
+%SRC @article{quux, bar = baz}"#,
+        serde_json::json!({}),
+        FormattingKind::Range,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_range() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{itemize}
+%SRC \item foo
+%SRC \item bar
+%1.1  ^
+%SRC \item baz
+%SRC \end{itemize}"#,
+        FormattingKind::Range,
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn latex_internal_on_type() -> Result<()> {
+    assert_snapshot!(format_latex_internal(
+        r#"
+%TEX main.tex
+%SRC \begin{itemize}
+%SRC \item foo
+%SRC     \end{itemize}
+%CUR                 ^"#,
+        FormattingKind::OnType,
+    )?);
+
+    Ok(())
+}
@@ -36,6 +36,23 @@ fn check(fixture: &str, contents: Option<HoverContents>) -> Result<()> {
     Ok(())
 }
 
+#[test]
+fn command_user_defined() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \NewDocumentCommand{\foo}{m}{#1}
+%SRC \foo{bar}
+%CUR   ^
+%1.1 ^^^^
+"#,
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "```latex\n\\NewDocumentCommand{\\foo}{m}{#1}\n```".into(),
+        })),
+    )
+}
+
 #[test]
 fn empty_latex_document() -> Result<()> {
     check(
@@ -115,6 +132,73 @@ fn component_known_package() -> Result<()> {
     )
 }
 
+#[test]
+fn component_option_known_package() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \usepackage[leqno]{amsmath}
+%CUR               ^
+%1.1             ^^^^^
+"#,
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "`leqno` (`amsmath.sty`)\n\nPlaces equation numbers on the left.".into(),
+        })),
+    )
+}
+
+#[test]
+fn component_option_known_package_value() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \usepackage[colorlinks, urlcolor=blue]{hyperref}
+%CUR                  ^
+%1.1             ^^^^^^^^^^
+"#,
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "`colorlinks` (`hyperref.sty`)\n\nColors the text of links instead of drawing boxes around them.".into(),
+        })),
+    )
+}
+
+#[test]
+fn component_option_local_package() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \usepackage[draft]{foo}
+%CUR               ^
+%1.1             ^^^^^
+
+%TEX foo.sty
+%SRC \DeclareOption{draft}{}
+"#,
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "`draft` (`foo.sty`)".into(),
+        })),
+    )
+}
+
+#[test]
+fn key_value_known_key() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \lstset{numbers=left}
+%CUR           ^
+%1.1         ^^^^^^^
+"#,
+        Some(HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value: "`numbers` (choice)\n\nSets the position of line numbers.\n\nValues: `none`, `left`, `right`".into(),
+        })),
+    )
+}
+
 #[test]
 fn component_unknown_class() -> Result<()> {
     check(
@@ -0,0 +1,135 @@
+use anyhow::Result;
+use lsp_types::{
+    notification::DidChangeConfiguration, request::InlayHintRequest, ClientCapabilities,
+    DidChangeConfigurationParams, InlayHintLabel, InlayHintParams, Position, Range,
+    TextDocumentIdentifier,
+};
+
+use crate::lsp::{client::Client, fixture};
+
+fn check(
+    fixture: &str,
+    settings: Option<serde_json::Value>,
+    expected: Vec<(Position, &str)>,
+) -> Result<()> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+
+    let fixture = fixture::parse(fixture);
+    for file in &fixture.files {
+        client.open(file.name, file.lang, file.text.clone())?;
+    }
+
+    if let Some(settings) = settings {
+        client.notify::<DidChangeConfiguration>(DidChangeConfigurationParams { settings })?;
+    }
+
+    let main_file = &fixture.files[0];
+    let line_count = main_file.text.lines().count() as u32;
+    let actual_hints = client.request::<InlayHintRequest>(InlayHintParams {
+        text_document: TextDocumentIdentifier::new(client.uri(main_file.name)?),
+        range: Range::new(Position::new(0, 0), Position::new(line_count - 1, 0)),
+        work_done_progress_params: Default::default(),
+    })?;
+
+    client.shutdown()?;
+
+    let actual: Vec<_> = actual_hints
+        .unwrap_or_default()
+        .into_iter()
+        .map(|hint| match hint.label {
+            InlayHintLabel::String(text) => (hint.position, text),
+            InlayHintLabel::LabelParts(_) => unreachable!(),
+        })
+        .collect();
+
+    let expected: Vec<_> = expected
+        .into_iter()
+        .map(|(position, text)| (position, text.to_string()))
+        .collect();
+
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[test]
+fn label_reference() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \section{Foo}
+%SRC \label{sec:foo}
+%SRC \ref{sec:foo}
+%SRC 
+
+%TEX main.aux
+%SRC \newlabel{sec:foo}{{1}{1}}
+"#,
+        None,
+        vec![(Position::new(2, 12), "Section 1 (Foo)")],
+    )
+}
+
+#[test]
+fn label_reference_multiple() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \begin{equation}\label{eq:foo}\end{equation}
+%SRC \cref{eq:foo,eq:bar}
+%SRC 
+
+%TEX main.aux
+%SRC \newlabel{eq:foo}{{1}{1}}
+%SRC \newlabel{eq:bar}{{2}{1}}
+"#,
+        None,
+        vec![
+            (Position::new(1, 12), "Equation (1)"),
+            (Position::new(1, 19), "2"),
+        ],
+    )
+}
+
+#[test]
+fn citation() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{main.bib}
+%SRC \cite{foo}
+%SRC 
+
+%BIB main.bib
+%SRC @article{foo, author = {Rivest, R. L. and Shamir, A. and Adleman, L.}, year = {1978}}
+"#,
+        None,
+        vec![(Position::new(1, 9), "Rivest et al. 1978")],
+    )
+}
+
+#[test]
+fn disabled() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{main.bib}
+%SRC \cite{foo}
+%SRC \ref{sec:foo}
+%SRC 
+
+%BIB main.bib
+%SRC @article{foo, author = {Rivest, R. L.}, year = {1978}}
+
+%TEX main.aux
+%SRC \newlabel{sec:foo}{{1}{1}}
+"#,
+        Some(serde_json::json!({
+            "inlayHints": {
+                "labelReferences": false,
+                "citations": false
+            }
+        })),
+        Vec::new(),
+    )
+}
@@ -1,3 +1,5 @@
+use std::collections::BTreeMap;
+
 use anyhow::Result;
 use insta::{assert_json_snapshot, internals::Redaction};
 use lsp_types::{
@@ -7,13 +9,22 @@ use lsp_types::{
     ClientCapabilities, Diagnostic, DidChangeConfigurationParams, DidChangeWatchedFilesParams,
     FileChangeType, FileEvent, PublishDiagnosticsParams, Url,
 };
-use rustc_hash::FxHashMap;
 
 use crate::lsp::{client::Client, fixture};
 
 struct DiagnosticResult {
-    all_diagnostics: FxHashMap<Url, Vec<Diagnostic>>,
-    uri_redaction: Redaction,
+    all_diagnostics: BTreeMap<Url, Vec<Diagnostic>>,
+    directory_uri: Url,
+}
+
+fn uri_redaction(directory_uri: &Url) -> Redaction {
+    let directory_uri = directory_uri.clone();
+    insta::dynamic_redaction(move |content, _path| {
+        content
+            .as_str()
+            .unwrap()
+            .replace(directory_uri.as_str(), "[tmp]/")
+    })
 }
 
 fn find_diagnostics(fixture: &str, settings: serde_json::Value) -> Result<DiagnosticResult> {
@@ -23,7 +34,7 @@ fn find_diagnostics(fixture: &str, settings: serde_json::Value) -> Result<Diagno
     let mut disk_files = Vec::new();
     let fixture = fixture::parse(fixture);
     for file in fixture.files {
-        if file.lang == "log" {
+        if matches!(file.lang, "log" | "blg") {
             client.store_on_disk(file.name, &file.text)?;
             disk_files.push(client.uri(file.name)?);
         } else {
@@ -42,11 +53,7 @@ fn find_diagnostics(fixture: &str, settings: serde_json::Value) -> Result<Diagno
 
     let result = client.shutdown()?;
 
-    let uri = Url::from_directory_path(result.directory.path()).unwrap();
-    let uri_redaction = insta::dynamic_redaction(move |content, _path| {
-        content.as_str().unwrap().replace(uri.as_str(), "[tmp]/")
-    });
-
+    let directory_uri = Url::from_directory_path(result.directory.path()).unwrap();
     let all_diagnostics = result
         .incoming
         .notifications
@@ -61,7 +68,7 @@ fn find_diagnostics(fixture: &str, settings: serde_json::Value) -> Result<Diagno
 
     Ok(DiagnosticResult {
         all_diagnostics,
-        uri_redaction,
+        directory_uri,
     })
 }
 
@@ -69,7 +76,8 @@ macro_rules! assert_symbols {
     ($result:expr) => {
         let result = $result;
         assert_json_snapshot!(result.all_diagnostics, {
-            ".$key" => result.uri_redaction
+            ".$key" => uri_redaction(&result.directory_uri),
+            ".*[].relatedInformation[].location.uri" => uri_redaction(&result.directory_uri)
         });
     };
 }
@@ -262,3 +270,174 @@ fn build_log_filter_ignored() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn bibtex_log() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \bibliography{main}
+%SRC \cite{foo, bar}
+
+%BIB main.bib
+%SRC @article{foo, author = {Foo}}
+%SRC @article{foo, author = {Foo}}
+
+%BLG main.blg
+%SRC This is BibTeX, Version 0.99d (TeX Live 2022)
+%SRC The top-level auxiliary file: main.aux
+%SRC The style file: plain.bst
+%SRC Database file #1: main.bib
+%SRC Repeated entry---line 2 of file main.bib
+%SRC  : @article{foo
+%SRC  :             ,
+%SRC I'm skipping whatever remains of this entry
+%SRC Warning--I didn't find a database entry for "bar"
+%SRC (There was 1 error message)"#,
+        serde_json::json!({})
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn biber_log() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{main.bib}
+%SRC \cite{foo, bar}
+
+%BIB main.bib
+%SRC @article{foo, author = {Foo}}
+%SRC @article{foo, author = {Foo}}
+
+%BLG main.blg
+%SRC [0] Config.pm:307> INFO - This is Biber 2.17
+%SRC [150] Utils.pm:411> WARN - Duplicate entry key 'foo' in file 'main.bib', skipping ...
+%SRC [160] Utils.pm:411> WARN - I didn't find a database entry for 'bar' (section 0)
+%SRC [190] Biber.pm:131> INFO - WARNINGS: 2"#,
+        serde_json::json!({})
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn biber_log_encoded_file_name() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{Literatur Übersicht.bib}
+%SRC \cite{foo}
+
+%BIB Literatur Übersicht.bib
+%SRC @article{foo, author = {Foo}}
+%SRC @article{foo, author = {Foo}}
+
+%BLG main.blg
+%SRC [0] Config.pm:307> INFO - This is Biber 2.17
+%SRC [150] Utils.pm:411> WARN - Duplicate entry key 'foo' in file 'Literatur Übersicht.bib', skipping ...
+%SRC [190] Biber.pm:131> INFO - WARNINGS: 1"#,
+        serde_json::json!({})
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn label_references() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \label{foo}
+%SRC \label{foo}
+%SRC \label{bar}
+%SRC \label{baz}
+%SRC \ref{bar}
+%SRC \ref{qux}
+"#,
+        serde_json::json!({
+            "diagnostics": {
+                "unusedLabels": true
+            }
+        })
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn citations() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \addbibresource{main.bib}
+%SRC \cite{foo, bar}
+
+%BIB main.bib
+%SRC @article{foo, author = {Foo}}
+%SRC @article{baz, author = {Baz}}
+%SRC @article{baz, author = {Baz}}
+%SRC @article{qux, author = {Qux}}
+"#,
+        serde_json::json!({
+            "diagnostics": {
+                "unusedEntries": true
+            }
+        })
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn citations_without_bibliography() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \cite{foo}
+"#,
+        serde_json::json!({})
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_keys() -> Result<()> {
+    assert_symbols!(find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \hypersetup{colorlinks, pdfstartpage=2, linkcolour=blue}
+%SRC \includegraphics[width=3cm, natwidth=2cm, hieght=2cm]{foo}
+%SRC \begin{figure}[!htbq]
+%SRC \end{figure}
+%SRC \begin{tikzpicture}[my style]
+%SRC \end{tikzpicture}
+"#,
+        serde_json::json!({})
+    )?);
+
+    Ok(())
+}
+
+#[test]
+fn unknown_keys_disabled() -> Result<()> {
+    let result = find_diagnostics(
+        r#"
+%TEX main.tex
+%SRC \includegraphics[width=3cm, hieght=2cm]{foo}
+%SRC \begin{figure}[!htbq]
+%SRC \end{figure}
+"#,
+        serde_json::json!({
+            "diagnostics": {
+                "unknownKeys": false
+            }
+        }),
+    )?;
+
+    assert!(result.all_diagnostics.values().all(Vec::is_empty));
+    Ok(())
+}
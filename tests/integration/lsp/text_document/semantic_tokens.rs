@@ -0,0 +1,158 @@
+use anyhow::Result;
+use insta::assert_snapshot;
+use lsp_types::{
+    request::{SemanticTokensFullRequest, SemanticTokensRangeRequest},
+    ClientCapabilities, Range, SemanticTokensLegend, SemanticTokensParams,
+    SemanticTokensRangeParams, SemanticTokensRangeResult, SemanticTokensResult,
+    SemanticTokensServerCapabilities, TextDocumentIdentifier,
+};
+
+use crate::lsp::{client::Client, fixture};
+
+fn render(
+    legend: &SemanticTokensLegend,
+    text: &str,
+    tokens: Vec<lsp_types::SemanticToken>,
+) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut output = String::new();
+    let mut line = 0;
+    let mut start = 0;
+    for token in tokens {
+        line += token.delta_line;
+        start = if token.delta_line == 0 {
+            start + token.delta_start
+        } else {
+            token.delta_start
+        };
+
+        let token_text: String = lines[line as usize]
+            .chars()
+            .skip(start as usize)
+            .take(token.length as usize)
+            .collect();
+
+        let mut modifiers = Vec::new();
+        for (i, modifier) in legend.token_modifiers.iter().enumerate() {
+            if token.token_modifiers_bitset & (1 << i) != 0 {
+                modifiers.push(modifier.as_str());
+            }
+        }
+
+        output.push_str(&format!(
+            "{}:{} {:?} {} {}\n",
+            line,
+            start,
+            token_text,
+            legend.token_types[token.token_type as usize].as_str(),
+            modifiers.join(",")
+        ));
+    }
+
+    output
+}
+
+fn find_tokens(fixture: &str, range: Option<Range>) -> Result<String> {
+    let mut client = Client::spawn()?;
+    let result = client.initialize(ClientCapabilities::default(), None)?;
+    let legend = match result.capabilities.semantic_tokens_provider.unwrap() {
+        SemanticTokensServerCapabilities::SemanticTokensOptions(options) => options.legend,
+        SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(options) => {
+            options.semantic_tokens_options.legend
+        }
+    };
+
+    let fixture = fixture::parse(fixture);
+    let text = fixture.files[0].text.clone();
+    for file in fixture.files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    let text_document = TextDocumentIdentifier::new(client.uri(fixture.cursor.unwrap().name)?);
+    let tokens = match range {
+        Some(range) => {
+            match client.request::<SemanticTokensRangeRequest>(SemanticTokensRangeParams {
+                text_document,
+                range,
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })? {
+                Some(SemanticTokensRangeResult::Tokens(tokens)) => tokens.data,
+                Some(SemanticTokensRangeResult::Partial(tokens)) => tokens.data,
+                None => Vec::new(),
+            }
+        }
+        None => match client.request::<SemanticTokensFullRequest>(SemanticTokensParams {
+            text_document,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })? {
+            Some(SemanticTokensResult::Tokens(tokens)) => tokens.data,
+            Some(SemanticTokensResult::Partial(tokens)) => tokens.data,
+            None => Vec::new(),
+        },
+    };
+
+    client.shutdown()?;
+    Ok(render(&legend, &text, tokens))
+}
+
+#[test]
+fn latex_full() -> Result<()> {
+    assert_snapshot!(find_tokens(
+        r#"
+%TEX main.tex
+%SRC \documentclass{article}
+%SRC \newcommand{\foo}{bar} % baz
+%SRC \begin{document}
+%SRC \section{Qux}\label{sec:qux}
+%SRC See \ref{sec:qux} and \cite{knuth}, $x = \foo$.
+%SRC \begin{equation}
+%SRC     E = mc^2
+%SRC \end{equation}
+%SRC \begin{verbatim}
+%SRC \foo
+%SRC \end{verbatim}
+%SRC \end{document}
+%CUR ^
+"#,
+        None
+    )?);
+    Ok(())
+}
+
+#[test]
+fn latex_range() -> Result<()> {
+    assert_snapshot!(find_tokens(
+        r#"
+%TEX main.tex
+%SRC \section{Foo}
+%SRC \label{sec:foo}
+%SRC \ref{sec:foo}
+%CUR ^
+"#,
+        Some(Range::new(
+            lsp_types::Position::new(1, 0),
+            lsp_types::Position::new(1, 14)
+        ))
+    )?);
+    Ok(())
+}
+
+#[test]
+fn bibtex_full() -> Result<()> {
+    assert_snapshot!(find_tokens(
+        r#"
+%BIB main.bib
+%SRC @string{jan = "January"}
+%SRC @article{foo,
+%SRC     author = {Foo \"{B}ar},
+%SRC     month = jan,
+%SRC     year = 2020,
+%SRC }
+%CUR ^
+"#,
+        None
+    )?);
+    Ok(())
+}
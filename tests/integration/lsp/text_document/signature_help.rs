@@ -0,0 +1,135 @@
+use anyhow::Result;
+use lsp_types::{request::SignatureHelpRequest, ClientCapabilities, SignatureHelpParams};
+
+use crate::lsp::{client::Client, fixture};
+
+fn check(fixture: &str, expected: Option<(&str, Option<u32>)>) -> Result<()> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+
+    let fixture = fixture::parse(fixture);
+    for file in fixture.files {
+        client.open(file.name, file.lang, file.text)?;
+    }
+
+    let actual_help = client.request::<SignatureHelpRequest>(SignatureHelpParams {
+        context: None,
+        text_document_position_params: fixture.cursor.unwrap().into_params(&client)?,
+        work_done_progress_params: Default::default(),
+    })?;
+
+    client.shutdown()?;
+
+    let actual = actual_help
+        .as_ref()
+        .map(|help| (help.signatures[0].label.as_str(), help.active_parameter));
+
+    assert_eq!(actual, expected);
+    Ok(())
+}
+
+#[test]
+fn command_new_command() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \newcommand{\foo}[3][bar]{#1}
+%SRC \foo[baz]{qux}{}
+%CUR                ^
+"#,
+        Some((r"\foo[#1]{#2}{#3}", Some(2))),
+    )
+}
+
+#[test]
+fn command_missing_optional() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \newcommand{\foo}[2][bar]{#1}
+%SRC \foo{qux}
+%CUR       ^
+"#,
+        Some((r"\foo[#1]{#2}", Some(1))),
+    )
+}
+
+#[test]
+fn command_xparse() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \NewDocumentCommand{\foo}{s m r()}{#1}
+%SRC \foo*{bar}(
+%CUR            ^
+"#,
+        Some((r"\foo*{#2}(#3)", Some(1))),
+    )
+}
+
+#[test]
+fn command_unclosed_group() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \newcommand{\foo}[1]{#1}
+%SRC \foo{
+%CUR      ^
+"#,
+        Some((r"\foo{#1}", Some(0))),
+    )
+}
+
+#[test]
+fn command_outside_group() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \newcommand{\foo}[1]{#1}
+%SRC \foo{bar}
+%CUR          ^
+"#,
+        None,
+    )
+}
+
+#[test]
+fn environment_new_environment() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \newenvironment{foo}[2][bar]{}{}
+%SRC \begin{foo}[baz]{qux}
+%CUR                   ^
+%SRC \end{foo}
+"#,
+        Some((r"\begin{foo}[#1]{#2}", Some(1))),
+    )
+}
+
+#[test]
+fn environment_xparse() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \NewDocumentEnvironment{foo}{m m}{}{}
+%SRC \begin{foo}{bar}{}
+%CUR                  ^
+%SRC \end{foo}
+"#,
+        Some((r"\begin{foo}{#1}{#2}", Some(1))),
+    )
+}
+
+#[test]
+fn environment_undefined() -> Result<()> {
+    check(
+        r#"
+%TEX main.tex
+%SRC \begin{foo}{bar}
+%CUR             ^
+%SRC \end{foo}
+"#,
+        None,
+    )
+}
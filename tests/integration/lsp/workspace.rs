@@ -1 +1,2 @@
+mod did_change_watched_files;
 mod symbol;
@@ -0,0 +1,92 @@
+use anyhow::Result;
+use lsp_types::{
+    notification::DidChangeWatchedFiles, request::GotoDefinition, ClientCapabilities,
+    DidChangeWatchedFilesParams, FileChangeType, FileEvent, GotoDefinitionParams,
+    GotoDefinitionResponse, Position, TextDocumentIdentifier, TextDocumentPositionParams,
+};
+
+use crate::lsp::client::Client;
+
+fn setup() -> Result<Client> {
+    let mut client = Client::spawn()?;
+    client.initialize(ClientCapabilities::default(), None)?;
+    client.store_on_disk("child.tex", r#"\label{foo}"#)?;
+    client.open(
+        "main.tex",
+        "latex",
+        String::from("\\include{child}\n\\ref{foo}"),
+    )?;
+
+    Ok(client)
+}
+
+fn notify(client: &mut Client, name: &str, typ: FileChangeType) -> Result<()> {
+    client.notify::<DidChangeWatchedFiles>(DidChangeWatchedFilesParams {
+        changes: vec![FileEvent::new(client.uri(name)?, typ)],
+    })
+}
+
+fn find_definitions(client: &mut Client) -> Result<usize> {
+    let count = client
+        .request::<GotoDefinition>(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(client.uri("main.tex")?),
+                Position::new(1, 6),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })?
+        .map_or(0, |response| match response {
+            GotoDefinitionResponse::Link(links) => links.len(),
+            GotoDefinitionResponse::Array(_) | GotoDefinitionResponse::Scalar(_) => unreachable!(),
+        });
+
+    Ok(count)
+}
+
+#[test]
+fn reload_changed_file() -> Result<()> {
+    let mut client = setup()?;
+    assert_eq!(find_definitions(&mut client)?, 1);
+
+    client.store_on_disk("child.tex", r#"\label{bar}"#)?;
+    notify(&mut client, "child.tex", FileChangeType::CHANGED)?;
+    assert_eq!(find_definitions(&mut client)?, 0);
+
+    client.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn remove_deleted_file() -> Result<()> {
+    let mut client = setup()?;
+    assert_eq!(find_definitions(&mut client)?, 1);
+
+    std::fs::remove_file(client.uri("child.tex")?.to_file_path().unwrap())?;
+    notify(&mut client, "child.tex", FileChangeType::DELETED)?;
+    assert_eq!(find_definitions(&mut client)?, 0);
+
+    client.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn keep_open_document() -> Result<()> {
+    let mut client = setup()?;
+    client.open("child.tex", "latex", String::from(r#"\label{foo}"#))?;
+    notify(&mut client, "child.tex", FileChangeType::DELETED)?;
+    assert_eq!(find_definitions(&mut client)?, 1);
+
+    client.shutdown()?;
+    Ok(())
+}
+
+#[test]
+fn ignore_missing_file() -> Result<()> {
+    let mut client = setup()?;
+    notify(&mut client, "missing.tex", FileChangeType::CREATED)?;
+    assert_eq!(find_definitions(&mut client)?, 1);
+
+    client.shutdown()?;
+    Ok(())
+}